@@ -0,0 +1,73 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use minesweeper_lib::{
+    board::{Board, BoardPoint},
+    game::{MinesweeperBuilder, MinesweeperOpts, Play},
+};
+
+const SIZES: [(&str, usize, usize, usize); 4] = [
+    ("beginner", 9, 9, 10),
+    ("intermediate", 16, 16, 40),
+    ("expert", 16, 30, 99),
+    ("100x100", 100, 100, 2000),
+];
+
+fn init(c: &mut Criterion) {
+    let mut group = c.benchmark_group("init");
+    for (name, rows, cols, num_mines) in SIZES {
+        let opts = MinesweeperOpts {
+            rows,
+            cols,
+            num_mines,
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| MinesweeperBuilder::new(black_box(opts)).unwrap().init())
+        });
+    }
+    group.finish();
+}
+
+fn first_click_cascade(c: &mut Criterion) {
+    let mut group = c.benchmark_group("first_click_cascade");
+    for (name, rows, cols, _) in SIZES {
+        // a single mine - the first click opens (nearly) the whole board
+        let opts = MinesweeperOpts {
+            rows,
+            cols,
+            num_mines: 1,
+        };
+        let play = Play::reveal(
+            0,
+            BoardPoint {
+                row: rows / 2,
+                col: cols / 2,
+            },
+        );
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || MinesweeperBuilder::new(opts).unwrap().with_seed(0).init(),
+                |mut game| game.play(black_box(play)).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn neighbors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("neighbors");
+    let board = Board::new(100, 100, 0u8);
+    let cached = board.clone().with_neighbor_cache();
+    for (name, board) in [("computed", &board), ("cached", &cached)] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                (0..board.size())
+                    .map(|i| board.neighbors(&board.point_from_index(i)).len())
+                    .sum::<usize>()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, init, first_click_cascade, neighbors);
+criterion_main!(benches);
@@ -1,13 +1,28 @@
 use ansi_term::Style;
-use std::io;
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path, path::PathBuf, time::Instant};
 
 use minesweeper_lib::{
+    auto::{next_move, AutoMove},
     board::{Board, BoardPoint},
     cell::PlayerCell,
-    game::{Action, MinesweeperBuilder, MinesweeperOpts, Play, PlayOutcome},
+    client::ClientPlayer,
+    game::{
+        Action, CompletedMinesweeper, GameStatus, MinesweeperBuilder, MinesweeperOpts, Play,
+        PlayOutcome,
+    },
+    replay::{ReplayPosition, Replayable},
 };
 
-fn underline(input: &str) -> ansi_term::ANSIGenericString<str> {
+/// Completed game as written by `--save` and read by `--replay`
+#[derive(Serialize, Deserialize)]
+struct ReplayFile {
+    board: Board<PlayerCell>,
+    log: Vec<(Play, PlayOutcome)>,
+    players: Vec<ClientPlayer>,
+}
+
+fn underline(input: &str) -> ansi_term::ANSIGenericString<'_, str> {
     Style::new().underline().paint(input)
 }
 
@@ -15,7 +30,14 @@ fn main() {
     let flags = xflags::parse_or_exit! {
         optional -i,--intermediate
         optional -e, --expert
+        optional -r, --replay file: PathBuf
+        optional -s, --save file: PathBuf
+        optional -a, --auto
     };
+    if let Some(file) = flags.replay {
+        replay(&file);
+        return;
+    }
     let opts = if flags.expert {
         MinesweeperOpts {
             cols: 30,
@@ -35,17 +57,23 @@ fn main() {
             num_mines: 10,
         }
     };
-    let mut game = MinesweeperBuilder::new(opts).unwrap().init();
+    let mut game = MinesweeperBuilder::new(opts).unwrap().with_log().init();
+    let start = Instant::now();
     while !game.is_over() {
         print_board(&game.player_board(0));
 
-        let Some(play) = read_play() else {
+        let play = if flags.auto {
+            auto_play(&game.player_board(0), opts.num_mines)
+        } else {
+            read_play()
+        };
+        let Some(play) = play else {
             continue;
         };
 
         let res = game.play(play);
         if let Err(e) = res {
-            println!("Invalid action - try again: {:?}", e);
+            println!("Invalid action - try again: {e}");
             continue;
         }
         match res.unwrap() {
@@ -55,6 +83,74 @@ fn main() {
             PlayOutcome::Flag(_) => println!("Flagged"),
         }
     }
+    if flags.auto {
+        let won = game.status() == GameStatus::Won;
+        let secs = start.elapsed().as_secs_f64();
+        let three_bv = game.three_bv();
+        println!(
+            "{} - 3BV: {}, 3BV/s: {:.2}",
+            if won { "Win" } else { "Loss" },
+            three_bv,
+            three_bv as f64 / secs
+        );
+    }
+    if let Some(file) = flags.save {
+        save(&file, game.complete());
+    }
+}
+
+fn save(file: &Path, game: CompletedMinesweeper) {
+    let player = ClientPlayer {
+        player_id: 0,
+        score: game.player_score(0).unwrap(),
+        dead: game.player_dead(0).unwrap(),
+        victory_click: game.player_victory_click(0).unwrap(),
+        ..Default::default()
+    };
+    let replay_file = ReplayFile {
+        board: game.viewer_board_final(),
+        log: game.recover_log().unwrap(),
+        players: vec![player],
+    };
+    let json = serde_json::to_string(&replay_file).expect("Failed to serialize game");
+    fs::write(file, json).expect("Failed to write replay file");
+    println!("Saved replay to {}", file.display());
+}
+
+fn replay(file: &Path) {
+    let json = fs::read_to_string(file).expect("Failed to read replay file");
+    let ReplayFile {
+        board,
+        log,
+        players,
+    } = serde_json::from_str(&json).expect("Invalid replay file");
+    let game = CompletedMinesweeper::from_log(board, log, players);
+    let Some(mut replay) = game.replay(None) else {
+        println!("Replay file has no game log");
+        return;
+    };
+    let len = replay.len();
+    loop {
+        print_board(replay.current_board());
+        match replay.current_pos() {
+            ReplayPosition::End => {
+                println!("End of replay");
+                break;
+            }
+            pos => println!(
+                "Play {}/{} - press Enter to continue",
+                pos.to_num(len),
+                len - 1
+            ),
+        }
+        let mut input = String::new();
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line");
+        if replay.advance().is_err() {
+            break;
+        }
+    }
 }
 
 fn print_board(board: &Board<PlayerCell>) {
@@ -71,6 +167,17 @@ fn print_board(board: &Board<PlayerCell>) {
     }
 }
 
+fn auto_play(board: &Board<PlayerCell>, num_mines: usize) -> Option<Play> {
+    let auto_move = next_move(board, num_mines)?;
+    let point = auto_move.point();
+    match auto_move {
+        AutoMove::Start(_) => println!("Starting at {} {}", point.row, point.col),
+        AutoMove::Safe(_) => println!("Revealing safe cell {} {}", point.row, point.col),
+        AutoMove::Guess(_) => println!("Guessing {} {}", point.row, point.col),
+    }
+    Some(Play::reveal(0, point))
+}
+
 fn read_play() -> Option<Play> {
     println!("Input action & 2 numbers `{{c|d|f}} {{row}} {{col}}` as play:");
     let mut play = String::new();
@@ -87,7 +194,7 @@ fn read_play() -> Option<Play> {
 
     let action = match play.next().unwrap() {
         "c" => Action::Reveal,
-        "d" => Action::RevealAdjacent,
+        "d" => Action::Chord,
         "f" => Action::Flag,
         _ => {
             println!("Bad action - try again");
@@ -96,14 +203,14 @@ fn read_play() -> Option<Play> {
     };
     let row = play.next().unwrap().parse();
     let Ok(row) = row else {
-            println!("Invalid row - try again: {:?}", row);
-            return None;
-        };
+        println!("Invalid row - try again: {:?}", row);
+        return None;
+    };
     let col = play.next().unwrap().parse();
     let Ok(col) = col else {
-            println!("Invalid col - try again: {:?}", col);
-            return None;
-        };
+        println!("Invalid col - try again: {:?}", col);
+        return None;
+    };
 
     Some(Play {
         player: 0,
@@ -12,19 +12,14 @@ use crate::{
     upair::UnorderedPair,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum AnalyzedCell {
     Mine,
     Empty,
+    #[default]
     Undetermined,
 }
 
-impl Default for AnalyzedCell {
-    fn default() -> Self {
-        Self::Undetermined
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AnalysisCell {
     Hidden(AnalyzedCell),
@@ -50,9 +45,41 @@ pub struct AnalysisUpdate {
     pub to: Option<AnalyzedCell>,
 }
 
+/// Well known patterns with forced plays, recognized by [`MinesweeperAnalysis::find_patterns`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisPattern {
+    /// `1 2 1` along a wall - mines next to both 1s
+    OneTwoOne,
+    /// `1 2 2 1` along a wall - mines next to both 2s
+    OneTwoTwoOne,
+}
+
+impl AnalysisPattern {
+    /// Numbers along the wall, and which of the adjacent cells (starting one before the first
+    /// number and ending one after the last) are mines
+    fn layout(self) -> (&'static [u8], &'static [bool]) {
+        match self {
+            AnalysisPattern::OneTwoOne => (&[1, 2, 1], &[false, true, false, true, false]),
+            AnalysisPattern::OneTwoTwoOne => {
+                (&[1, 2, 2, 1], &[false, false, true, true, false, false])
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternMatch {
+    pub pattern: AnalysisPattern,
+    /// Revealed cells making up the pattern
+    pub numbers: Vec<BoardPoint>,
+    pub guaranteed_plays: Vec<(BoardPoint, AnalyzedCell)>,
+}
+
 pub struct MinesweeperAnalysis {
     analysis_board: Board<AnalysisCell>,
     fifty_fiftys: Vec<UnorderedPair<BoardPoint>>,
+    num_mines: Option<usize>,
+    trivial_only: bool,
 }
 
 impl MinesweeperAnalysis {
@@ -86,9 +113,32 @@ impl MinesweeperAnalysis {
         Self {
             analysis_board,
             fifty_fiftys: Vec::new(),
+            num_mines: None,
+            trivial_only: false,
         }
     }
 
+    /// Lets [`Self::analyze_board`] finish endgames by counting - when the known mines are all
+    /// of them, or the undetermined cells are exactly the mines left, every undetermined cell
+    /// is decided
+    pub fn with_mine_count(mut self, num_mines: usize) -> Self {
+        self.num_mines = Some(num_mines);
+        self
+    }
+
+    /// Only makes the obvious deductions - numbers whose mines are all known, or whose
+    /// undetermined neighbors must all be mines.  Skips the 50/50 bookkeeping, which is slow on
+    /// huge boards.
+    pub fn trivial_only(mut self) -> Self {
+        self.trivial_only = true;
+        self
+    }
+
+    /// Pairs of cells known to hold exactly one mine between them
+    pub fn fifty_fiftys(&self) -> &[UnorderedPair<BoardPoint>] {
+        &self.fifty_fiftys
+    }
+
     pub fn analyze_board(&mut self) -> Vec<AnalysisUpdate> {
         let points_to_analyze = self
             .analysis_board
@@ -112,7 +162,162 @@ impl MinesweeperAnalysis {
                     .collect::<Vec<_>>()
             })
             .collect();
-        self.analyze_cells(points_to_analyze)
+        let mut updates = self.analyze_cells(points_to_analyze);
+        updates.append(&mut self.analyze_mine_count());
+        updates
+    }
+
+    /// Decides every undetermined cell if the global mine count forces it - needs
+    /// [`Self::with_mine_count`]
+    fn analyze_mine_count(&mut self) -> Vec<AnalysisUpdate> {
+        let Some(num_mines) = self.num_mines else {
+            return Vec::new();
+        };
+        let points = (0..self.analysis_board.size())
+            .map(|i| self.analysis_board.point_from_index(i))
+            .collect::<Vec<_>>();
+        let known_mines = points.iter().filter(|p| self.is_mine(p)).count();
+        let undetermined = points
+            .into_iter()
+            .filter(|p| self.analysis_board[p] == AnalysisCell::Hidden(AnalyzedCell::Undetermined))
+            .collect::<Vec<_>>();
+        let remaining = num_mines.saturating_sub(known_mines);
+        let ac = if remaining == 0 {
+            AnalyzedCell::Empty
+        } else if remaining == undetermined.len() {
+            AnalyzedCell::Mine
+        } else {
+            return Vec::new();
+        };
+        undetermined
+            .into_iter()
+            .map(|point| {
+                self.analysis_board[point] = AnalysisCell::Hidden(ac);
+                if ac == AnalyzedCell::Mine {
+                    self.analysis_board
+                        .neighbors(&point)
+                        .iter()
+                        .for_each(|nbp| {
+                            if let AnalysisCell::Revealed(c) = self.analysis_board[nbp] {
+                                // reduce neighboring cell numbers
+                                self.analysis_board[nbp] = AnalysisCell::Revealed(c.decrement());
+                            }
+                        });
+                }
+                AnalysisUpdate {
+                    point,
+                    from: None,
+                    to: Some(ac),
+                }
+            })
+            .collect()
+    }
+
+    /// Runs [`Self::analyze_board`] and returns the whole resulting analysis grid at once -
+    /// `None` for revealed and still undetermined cells, matching what applying each
+    /// [`AnalysisUpdate`] in order would produce
+    pub fn analyze_board_diff(&mut self) -> Board<Option<AnalyzedCell>> {
+        self.analyze_board();
+        let board = &self.analysis_board;
+        Board::new_with(board.rows(), board.cols(), |p| match board[p] {
+            AnalysisCell::Hidden(ac @ (AnalyzedCell::Mine | AnalyzedCell::Empty)) => Some(ac),
+            _ => None,
+        })
+    }
+
+    /// Finds `1 2 1` and `1 2 2 1` patterns lying along a line of undetermined cells, where
+    /// the numbers have no other undetermined neighbors.  Doesn't change the analysis.
+    pub fn find_patterns(&self) -> Vec<PatternMatch> {
+        // (wall direction, direction along the wall)
+        const ORIENTATIONS: [((isize, isize), (isize, isize)); 4] = [
+            ((-1, 0), (0, 1)),
+            ((1, 0), (0, 1)),
+            ((0, -1), (1, 0)),
+            ((0, 1), (1, 0)),
+        ];
+        let mut matches = Vec::new();
+        (0..self.analysis_board.size())
+            .map(|i| self.analysis_board.point_from_index(i))
+            .filter(|p| {
+                matches!(
+                    self.analysis_board[p],
+                    AnalysisCell::Revealed(Cell::Empty(1))
+                )
+            })
+            .for_each(|start| {
+                for (wall, along) in ORIENTATIONS {
+                    for pattern in [AnalysisPattern::OneTwoOne, AnalysisPattern::OneTwoTwoOne] {
+                        if let Some(found) = self.match_pattern(pattern, start, wall, along) {
+                            matches.push(found);
+                        }
+                    }
+                }
+            });
+        matches
+    }
+
+    fn match_pattern(
+        &self,
+        pattern: AnalysisPattern,
+        start: BoardPoint,
+        wall: (isize, isize),
+        along: (isize, isize),
+    ) -> Option<PatternMatch> {
+        let board = &self.analysis_board;
+        let (pattern_nums, pattern_mines) = pattern.layout();
+        let numbers = (0..pattern_nums.len() as isize)
+            .map(|i| offset_point(board, start, along.0 * i, along.1 * i))
+            .collect::<Option<Vec<_>>>()?;
+        if numbers
+            .iter()
+            .zip(pattern_nums)
+            .any(|(p, &x)| board[p] != AnalysisCell::Revealed(Cell::Empty(x)))
+        {
+            return None;
+        }
+        // the wall runs from one before the first number to one after the last
+        let wall_cells = (-1..=pattern_nums.len() as isize)
+            .map(|i| offset_point(board, start, wall.0 + along.0 * i, wall.1 + along.1 * i))
+            .collect::<Vec<_>>();
+        // the cells directly next to the numbers must be there; the ends may be off the board
+        if wall_cells[1..=pattern_nums.len()]
+            .iter()
+            .any(Option::is_none)
+        {
+            return None;
+        }
+        let wall_points = wall_cells.iter().flatten().copied().collect::<Vec<_>>();
+        let undetermined =
+            |p: &BoardPoint| board[p] == AnalysisCell::Hidden(AnalyzedCell::Undetermined);
+        if !wall_points.iter().all(undetermined) {
+            return None;
+        }
+        let other_undetermined = numbers.iter().any(|np| {
+            board
+                .neighbors(np)
+                .iter()
+                .any(|p| undetermined(p) && !wall_points.contains(p))
+        });
+        if other_undetermined {
+            return None;
+        }
+        let guaranteed_plays = wall_cells
+            .iter()
+            .zip(pattern_mines)
+            .filter_map(|(p, &mine)| {
+                let ac = if mine {
+                    AnalyzedCell::Mine
+                } else {
+                    AnalyzedCell::Empty
+                };
+                p.map(|p| (p, ac))
+            })
+            .collect();
+        Some(PatternMatch {
+            pattern,
+            numbers,
+            guaranteed_plays,
+        })
     }
 
     pub fn analyze_cells(&mut self, points_to_analyze: Vec<BoardPoint>) -> Vec<AnalysisUpdate> {
@@ -121,7 +326,12 @@ impl MinesweeperAnalysis {
         let mut points_to_reanalyze = points_to_analyze.iter().copied().collect::<HashSet<_>>();
 
         points_to_analyze.into_iter().for_each(|bp| {
-            let res = perform_checks(&bp, &self.analysis_board, &self.fifty_fiftys);
+            let res = perform_checks(
+                &bp,
+                &self.analysis_board,
+                &self.fifty_fiftys,
+                self.trivial_only,
+            );
             if res.found_fifty_fiftys.is_some() || !res.guaranteed_plays.is_empty() {
                 has_updates = true;
             }
@@ -319,6 +529,27 @@ impl MinesweeperAnalysis {
     }
 }
 
+impl Board<PlayerCell> {
+    /// Each revealed opening - a connected region of revealed zero cells plus the numbered cells
+    /// bordering it, which a single click on the region would have cleared.  Border cells can
+    /// belong to more than one opening.
+    pub fn openings(&self) -> Vec<HashSet<BoardPoint>> {
+        self.openings_by(
+            |pc| matches!(pc, PlayerCell::Revealed(rc) if rc.contents == Cell::Empty(0)),
+            |pc| matches!(pc, PlayerCell::Revealed(_)),
+        )
+    }
+}
+
+fn offset_point<T>(
+    board: &Board<T>,
+    point: BoardPoint,
+    rows: isize,
+    cols: isize,
+) -> Option<BoardPoint> {
+    point.offset(rows, cols).filter(|p| board.is_in_bounds(*p))
+}
+
 fn neighbor_info(
     point: &BoardPoint,
     analysis_board: &Board<AnalysisCell>,
@@ -356,6 +587,7 @@ fn perform_checks(
     point: &BoardPoint,
     analysis_board: &Board<AnalysisCell>,
     fifty_fiftys: &[UnorderedPair<BoardPoint>],
+    trivial_only: bool,
 ) -> AnalysisResult {
     let cell = analysis_board[point];
     assert!(matches!(cell, AnalysisCell::Revealed(Cell::Empty(_))));
@@ -405,6 +637,9 @@ fn perform_checks(
     // it should be impossible for cell_num to be greater than num_undetermined
     // cells
     assert!(cell_num < num_undetermined);
+    if trivial_only {
+        return analysis_result;
+    }
 
     let fifty_fifty_pairs = find_fifty_fifty_pairs(&undetermined_points);
     let (non_fifty_fiftys, fifty_fifty_points) = undetermined_points.iter().fold(
@@ -604,6 +839,8 @@ fn perform_checks(
 // TODO - write unit tests
 #[cfg(test)]
 mod test {
+    use crate::cell::{HiddenCell, RevealedCell};
+
     use super::*;
 
     fn visual_to_board(sboard: &str) -> Board<AnalysisCell> {
@@ -636,6 +873,178 @@ mod test {
 
     struct TestCase(MinesweeperAnalysis, Board<AnalysisCell>);
 
+    fn analysis_of(sboard: &str) -> MinesweeperAnalysis {
+        MinesweeperAnalysis {
+            analysis_board: visual_to_board(sboard),
+            fifty_fiftys: vec![],
+            num_mines: None,
+            trivial_only: false,
+        }
+    }
+
+    fn plays(plays: &[(usize, usize, AnalyzedCell)]) -> Vec<(BoardPoint, AnalyzedCell)> {
+        plays
+            .iter()
+            .map(|&(row, col, ac)| (BoardPoint { row, col }, ac))
+            .collect()
+    }
+
+    #[test]
+    fn fifty_fiftys_after_analysis() {
+        let mut analysis = analysis_of(
+            "
+            --
+            11
+            00
+            ",
+        );
+        analysis.analyze_board();
+        let pair = UnorderedPair::new(BoardPoint { row: 0, col: 1 }, BoardPoint { row: 0, col: 0 });
+        assert!(analysis.fifty_fiftys().contains(&pair));
+        assert!(analysis.fifty_fiftys().iter().all(|p| *p == pair));
+        assert_eq!(pair.to_string(), "(0, 0) / (0, 1)");
+    }
+
+    #[test]
+    fn find_one_two_one() {
+        let analysis = analysis_of(
+            "
+            -----
+            11211
+            00000
+            ",
+        );
+        let found = analysis.find_patterns();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].pattern, AnalysisPattern::OneTwoOne);
+        assert_eq!(
+            found[0].guaranteed_plays,
+            plays(&[
+                (0, 0, AnalyzedCell::Empty),
+                (0, 1, AnalyzedCell::Mine),
+                (0, 2, AnalyzedCell::Empty),
+                (0, 3, AnalyzedCell::Mine),
+                (0, 4, AnalyzedCell::Empty),
+            ])
+        );
+    }
+
+    #[test]
+    fn find_one_two_two_one() {
+        let analysis = analysis_of(
+            "
+            000000
+            012210
+            ------
+            ",
+        );
+        let found = analysis.find_patterns();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].pattern, AnalysisPattern::OneTwoTwoOne);
+        assert_eq!(
+            found[0].guaranteed_plays,
+            plays(&[
+                (2, 0, AnalyzedCell::Empty),
+                (2, 1, AnalyzedCell::Empty),
+                (2, 2, AnalyzedCell::Mine),
+                (2, 3, AnalyzedCell::Mine),
+                (2, 4, AnalyzedCell::Empty),
+                (2, 5, AnalyzedCell::Empty),
+            ])
+        );
+    }
+
+    #[test]
+    fn find_vertical_pattern_at_edge() {
+        let analysis = analysis_of(
+            "
+            -10
+            -20
+            -10
+            ",
+        );
+        let found = analysis.find_patterns();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].pattern, AnalysisPattern::OneTwoOne);
+        assert_eq!(
+            found[0].guaranteed_plays,
+            plays(&[
+                (0, 0, AnalyzedCell::Mine),
+                (1, 0, AnalyzedCell::Empty),
+                (2, 0, AnalyzedCell::Mine),
+            ])
+        );
+    }
+
+    #[test]
+    fn pattern_needs_a_closed_side() {
+        let analysis = analysis_of(
+            "
+            -----
+            11211
+            --000
+            ",
+        );
+        assert!(analysis.find_patterns().is_empty());
+    }
+
+    #[test]
+    fn mine_count_finishes_endgame() {
+        let sboard = "
+            --20
+            --20
+            ";
+        let corner = [BoardPoint { row: 0, col: 0 }, BoardPoint { row: 1, col: 0 }];
+        let analyzed = |analysis: &mut MinesweeperAnalysis| {
+            let diff = analysis.analyze_board_diff();
+            corner.map(|p| diff[p])
+        };
+
+        // the 2s only reach the inner column
+        assert_eq!(analyzed(&mut analysis_of(sboard)), [None, None]);
+        let mut all_found = analysis_of(sboard).with_mine_count(2);
+        assert_eq!(
+            analyzed(&mut all_found),
+            [Some(AnalyzedCell::Empty), Some(AnalyzedCell::Empty)]
+        );
+        let mut all_left = analysis_of(sboard).with_mine_count(4);
+        assert_eq!(
+            analyzed(&mut all_left),
+            [Some(AnalyzedCell::Mine), Some(AnalyzedCell::Mine)]
+        );
+        let mut undecided = analysis_of(sboard).with_mine_count(3);
+        assert_eq!(analyzed(&mut undecided), [None, None]);
+    }
+
+    #[test]
+    fn trivial_only_skips_fifty_fiftys() {
+        let sboard = "
+            ----
+            -210
+            -100
+            ";
+        let mut full = analysis_of(sboard);
+        let mut fast = analysis_of(sboard).trivial_only();
+        let full = full.analyze_board_diff();
+        let fast_diff = fast.analyze_board_diff();
+
+        // the 1 and 0 on the right decide the top right
+        for (col, ac) in [
+            (1, AnalyzedCell::Mine),
+            (2, AnalyzedCell::Empty),
+            (3, AnalyzedCell::Empty),
+        ] {
+            let point = BoardPoint { row: 0, col };
+            assert_eq!(full[point], Some(ac));
+            assert_eq!(fast_diff[point], Some(ac));
+        }
+        // the corner needs 50/50 reasoning
+        let corner = BoardPoint { row: 0, col: 0 };
+        assert_eq!(full[corner], Some(AnalyzedCell::Empty));
+        assert_eq!(fast_diff[corner], None);
+        assert!(fast.fifty_fiftys().is_empty());
+    }
+
     #[test]
     fn complex_reveal() {
         let cases: Vec<TestCase> = vec![
@@ -660,6 +1069,8 @@ mod test {
                             BoardPoint { row: 4, col: 3 },
                         ),
                     ],
+                    num_mines: None,
+                    trivial_only: false,
                 },
                 visual_to_board(
                     "
@@ -682,6 +1093,8 @@ mod test {
                     ",
                     ),
                     fifty_fiftys: vec![],
+                    num_mines: None,
+                    trivial_only: false,
                 },
                 visual_to_board(
                     "
@@ -704,6 +1117,8 @@ mod test {
                     ",
                     ),
                     fifty_fiftys: vec![],
+                    num_mines: None,
+                    trivial_only: false,
                 },
                 visual_to_board(
                     "
@@ -725,6 +1140,8 @@ mod test {
                     ",
                     ),
                     fifty_fiftys: vec![],
+                    num_mines: None,
+                    trivial_only: false,
                 },
                 visual_to_board(
                     "
@@ -757,4 +1174,86 @@ mod test {
                 });
         }
     }
+
+    #[test]
+    fn analyze_board_diff_matches_updates() {
+        let sboard = "
+            -100
+            -100
+            121m
+            ----
+            ";
+        let mut by_updates = analysis_of(sboard);
+        let mut by_diff = analysis_of(sboard);
+
+        let mut expected = Board::new(4, 4, None);
+        // known before analyzing - part of the grid but not of the updates
+        expected[BoardPoint { row: 2, col: 3 }] = Some(AnalyzedCell::Mine);
+        by_updates
+            .analyze_board()
+            .into_iter()
+            .for_each(|update| expected[update.point] = update.to);
+        let diff = by_diff.analyze_board_diff();
+
+        assert!(diff == expected);
+        assert_eq!(diff[BoardPoint { row: 2, col: 2 }], None);
+        assert_eq!(
+            diff[BoardPoint { row: 3, col: 2 }],
+            Some(AnalyzedCell::Mine)
+        );
+        assert_eq!(
+            diff[BoardPoint { row: 3, col: 3 }],
+            Some(AnalyzedCell::Empty)
+        );
+    }
+
+    #[test]
+    fn openings_group_zero_regions() {
+        let board = Board::from_vec(
+            "
+            001--
+            001--
+            111--
+            ---11
+            ---10
+            "
+            .trim()
+            .lines()
+            .map(|row| {
+                row.trim()
+                    .chars()
+                    .map(|c| match c.to_digit(10) {
+                        Some(n) => PlayerCell::Revealed(RevealedCell {
+                            player: 0,
+                            contents: Cell::Empty(n as u8),
+                        }),
+                        None => PlayerCell::Hidden(HiddenCell::Empty),
+                    })
+                    .collect()
+            })
+            .collect(),
+        );
+        let point = |row, col| BoardPoint { row, col };
+        let mut openings = board.openings();
+        openings.sort_by_key(|opening| opening.len());
+        assert_eq!(openings.len(), 2);
+        assert_eq!(
+            openings[0],
+            HashSet::from([point(3, 3), point(3, 4), point(4, 3), point(4, 4)])
+        );
+        assert_eq!(
+            openings[1],
+            HashSet::from([
+                point(0, 0),
+                point(0, 1),
+                point(0, 2),
+                point(1, 0),
+                point(1, 1),
+                point(1, 2),
+                point(2, 0),
+                point(2, 1),
+                point(2, 2),
+            ])
+        );
+    }
 }
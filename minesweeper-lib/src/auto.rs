@@ -0,0 +1,222 @@
+use std::{collections::HashSet, ops::Range};
+
+use anyhow::Result;
+
+use crate::{
+    analysis::{AnalyzedCell, MinesweeperAnalysis},
+    board::{Board, BoardPoint},
+    cell::PlayerCell,
+    game::{GameStatus, MinesweeperBuilder, MinesweeperOpts, Play},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoMove {
+    /// First click - the game guarantees it isn't a mine
+    Start(BoardPoint),
+    /// Analysis determined the cell can't be a mine
+    Safe(BoardPoint),
+    /// Nothing is guaranteed - cell with the lowest estimated mine probability
+    Guess(BoardPoint),
+}
+
+impl AutoMove {
+    pub fn point(&self) -> BoardPoint {
+        match self {
+            AutoMove::Start(p) | AutoMove::Safe(p) | AutoMove::Guess(p) => *p,
+        }
+    }
+}
+
+/// Picks the next cell to reveal on `board`, or `None` if nothing is left hidden
+pub fn next_move(board: &Board<PlayerCell>, num_mines: usize) -> Option<AutoMove> {
+    if board
+        .find_point(|c| matches!(c, PlayerCell::Revealed(_)))
+        .is_none()
+    {
+        return Some(AutoMove::Start(BoardPoint { row: 0, col: 0 }));
+    }
+
+    let mut analysis = MinesweeperAnalysis::init(board).with_mine_count(num_mines);
+    let updates = analysis.analyze_board();
+    let is_hidden = |p: &BoardPoint| matches!(board[p], PlayerCell::Hidden(_));
+    if let Some(update) = updates
+        .iter()
+        .find(|u| u.to == Some(AnalyzedCell::Empty) && is_hidden(&u.point))
+    {
+        return Some(AutoMove::Safe(update.point));
+    }
+
+    let known_mines = updates
+        .iter()
+        .filter(|u| u.to == Some(AnalyzedCell::Mine))
+        .map(|u| u.point)
+        .chain(
+            (0..board.size())
+                .map(|i| board.point_from_index(i))
+                .filter(|p| is_revealed_mine(&board[p])),
+        )
+        .collect::<HashSet<_>>();
+    let unknown = (0..board.size())
+        .map(|i| board.point_from_index(i))
+        .filter(|p| is_hidden(p) && !known_mines.contains(p))
+        .collect::<Vec<_>>();
+    if unknown.is_empty() {
+        return None;
+    }
+
+    let remaining_mines = num_mines.saturating_sub(known_mines.len());
+    let global_probability = remaining_mines as f64 / unknown.len() as f64;
+    // estimate each cell by its most dangerous revealed neighbor
+    let probability = |point: &BoardPoint| {
+        board
+            .neighbors(point)
+            .iter()
+            .filter_map(|np| match board[np] {
+                PlayerCell::Revealed(rc) => rc.number().map(|x| (np, x as usize)),
+                PlayerCell::Hidden(_) => None,
+            })
+            .map(|(np, x)| {
+                let neighbors = board.neighbors(np);
+                let mines = neighbors.iter().filter(|p| known_mines.contains(p)).count();
+                let hidden = neighbors
+                    .iter()
+                    .filter(|p| is_hidden(p) && !known_mines.contains(p))
+                    .count();
+                x.saturating_sub(mines) as f64 / hidden as f64
+            })
+            .reduce(f64::max)
+            .unwrap_or(global_probability)
+    };
+    unknown
+        .iter()
+        .map(|p| (p, probability(p)))
+        .reduce(|best, next| if next.1 < best.1 { next } else { best })
+        .map(|(p, _)| AutoMove::Guess(*p))
+}
+
+/// What the simulated player does when analysis can't find a safe cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Reveal the least risky cell and keep going
+    Guess,
+    /// Give up and count the game as lost - the win rate is then the share of boards
+    /// solvable without guessing
+    NoGuess,
+}
+
+/// Totals from [`simulate`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimulationReport {
+    pub games: usize,
+    pub wins: usize,
+    pub total_3bv: usize,
+    /// First clicks and cells analysis proved safe
+    pub moves: usize,
+    /// Positions which needed a guess - with `Strategy::NoGuess` at most one per game
+    pub guesses: usize,
+}
+
+impl SimulationReport {
+    pub fn win_rate(&self) -> f64 {
+        ratio(self.wins, self.games)
+    }
+
+    pub fn average_3bv(&self) -> f64 {
+        ratio(self.total_3bv, self.games)
+    }
+
+    /// Share of all moves which were guesses
+    pub fn guess_frequency(&self) -> f64 {
+        ratio(self.guesses, self.moves + self.guesses)
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// Auto-plays a single player game for each seed with [`next_move`]
+pub fn simulate(
+    opts: MinesweeperOpts,
+    seeds: Range<u64>,
+    strategy: Strategy,
+) -> Result<SimulationReport> {
+    let mut report = SimulationReport::default();
+    for seed in seeds {
+        let mut game = MinesweeperBuilder::new(opts)?.with_seed(seed).init();
+        while !game.is_over() {
+            let Some(auto_move) = next_move(&game.player_board(0), opts.num_mines) else {
+                break;
+            };
+            if let AutoMove::Guess(_) = auto_move {
+                report.guesses += 1;
+                if strategy == Strategy::NoGuess {
+                    break;
+                }
+            } else {
+                report.moves += 1;
+            }
+            game.play(Play::reveal(0, auto_move.point()))?;
+        }
+        report.games += 1;
+        if game.status() == GameStatus::Won {
+            report.wins += 1;
+        }
+        // mines can move on the first click, so count once the layout is final
+        report.total_3bv += game.three_bv();
+    }
+    Ok(report)
+}
+
+fn is_revealed_mine(cell: &PlayerCell) -> bool {
+    matches!(cell, PlayerCell::Revealed(rc) if rc.is_mine())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::{MinesweeperBuilder, MinesweeperOpts, Play, PlayOutcome};
+
+    const OPTS: MinesweeperOpts = MinesweeperOpts {
+        rows: 9,
+        cols: 9,
+        num_mines: 10,
+    };
+
+    #[test]
+    fn auto_play_fixed_seed() {
+        let mut game = MinesweeperBuilder::new(OPTS).unwrap().with_seed(42).init();
+        let mut guesses = 0;
+        while !game.is_over() {
+            let auto_move = next_move(&game.player_board(0), OPTS.num_mines).unwrap();
+            let outcome = game.play(Play::reveal(0, auto_move.point())).unwrap();
+            match auto_move {
+                AutoMove::Start(_) | AutoMove::Safe(_) => {
+                    assert!(!matches!(outcome, PlayOutcome::Failure(_)))
+                }
+                AutoMove::Guess(_) => guesses += 1,
+            }
+        }
+        assert!(game.player_victory_click(0).unwrap());
+        assert!(guesses < OPTS.num_mines);
+    }
+
+    #[test]
+    fn simulate_beginner() {
+        let guessing = simulate(OPTS, 0..10, Strategy::Guess).unwrap();
+        assert_eq!(guessing.games, 10);
+        assert!(guessing.wins > 0 && guessing.wins <= 10);
+        assert!(guessing.average_3bv() > 0.0);
+        assert!(guessing.guess_frequency() < 1.0);
+
+        let no_guess = simulate(OPTS, 0..10, Strategy::NoGuess).unwrap();
+        assert_eq!(no_guess.games, 10);
+        // safe moves never lose, so each game is either solved or stops at its first guess
+        assert_eq!(no_guess.wins + no_guess.guesses, no_guess.games);
+        assert_eq!(no_guess.total_3bv, guessing.total_3bv);
+    }
+}
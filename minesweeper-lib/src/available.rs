@@ -0,0 +1,110 @@
+use fixedbitset::FixedBitSet;
+
+use crate::board::BoardPoint;
+
+/// Set of board points backed by a bitset indexed `row * cols + col`
+#[derive(Clone, Debug)]
+pub(crate) struct AvailableCells {
+    cols: usize,
+    len: usize,
+    bits: FixedBitSet,
+}
+
+impl AvailableCells {
+    pub(crate) fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            cols,
+            len: 0,
+            bits: FixedBitSet::with_capacity(rows * cols),
+        }
+    }
+
+    pub(crate) fn from_points(
+        rows: usize,
+        cols: usize,
+        points: impl IntoIterator<Item = BoardPoint>,
+    ) -> Self {
+        let mut available = Self::new(rows, cols);
+        points.into_iter().for_each(|p| {
+            available.insert(p);
+        });
+        available
+    }
+
+    fn index(&self, point: &BoardPoint) -> usize {
+        point.index(self.cols)
+    }
+
+    pub(crate) fn insert(&mut self, point: BoardPoint) -> bool {
+        let index = self.index(&point);
+        let inserted = !self.bits.put(index);
+        if inserted {
+            self.len += 1;
+        }
+        inserted
+    }
+
+    pub(crate) fn remove(&mut self, point: &BoardPoint) -> bool {
+        let index = self.index(point);
+        let removed = self.bits.contains(index);
+        if removed {
+            self.bits.set(index, false);
+            self.len -= 1;
+        }
+        removed
+    }
+
+    #[cfg(test)]
+    pub(crate) fn contains(&self, point: &BoardPoint) -> bool {
+        self.bits.contains(self.index(point))
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Points in row-major order
+    pub(crate) fn iter(&self) -> impl Iterator<Item = BoardPoint> + '_ {
+        self.bits
+            .ones()
+            .map(|i| BoardPoint::from_index(i, self.cols))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_remove_contains() {
+        let mut available = AvailableCells::new(3, 4);
+        let point = BoardPoint { row: 2, col: 1 };
+        assert!(available.is_empty());
+        assert!(available.insert(point));
+        assert!(!available.insert(point));
+        assert!(available.contains(&point));
+        assert!(!available.contains(&BoardPoint { row: 1, col: 2 }));
+        assert_eq!(available.len(), 1);
+        assert!(available.remove(&point));
+        assert!(!available.remove(&point));
+        assert!(available.is_empty());
+    }
+
+    #[test]
+    fn iter_in_board_order() {
+        let points = [
+            BoardPoint { row: 2, col: 3 },
+            BoardPoint { row: 0, col: 1 },
+            BoardPoint { row: 1, col: 0 },
+        ];
+        let available = AvailableCells::from_points(3, 4, points);
+        let mut sorted = points.to_vec();
+        sorted.sort();
+        assert_eq!(available.iter().collect::<Vec<_>>(), sorted);
+    }
+}
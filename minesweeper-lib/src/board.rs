@@ -1,11 +1,14 @@
 use core::fmt;
 use std::{
+    collections::HashSet,
     fmt::{Debug, Display, Formatter},
     ops::{Index, IndexMut},
     slice::{Chunks, ChunksMut, Iter, IterMut},
+    sync::Arc,
 };
 
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use tinyvec::{array_vec, ArrayVec};
 
 impl<T> From<&Board<T>> for Vec<Vec<T>>
@@ -34,20 +37,71 @@ where
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum BoardError {
+    #[error("Row {row} has {len} cells, expected {expected}")]
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        len: usize,
+    },
+}
+
+impl<T> TryFrom<Vec<Vec<T>>> for Board<T> {
+    type Error = BoardError;
+
+    /// Like [`Board::from_vec`], but checks every row is the same length - an empty `Vec`
+    /// gives an empty board
+    fn try_from(value: Vec<Vec<T>>) -> Result<Self, Self::Error> {
+        let expected = value.first().map_or(0, Vec::len);
+        if let Some((row, cells)) = value
+            .iter()
+            .enumerate()
+            .find(|(_, cells)| cells.len() != expected)
+        {
+            return Err(BoardError::RaggedRow {
+                row,
+                expected,
+                len: cells.len(),
+            });
+        }
+        let rows = value.len();
+        Ok(Board {
+            rows,
+            cols: expected,
+            board: value.into_iter().flatten().collect(),
+            neighbor_cache: None,
+        })
+    }
+}
+
+/// Boards with fewer cells than this compute neighbors on the fly instead of caching them
+pub const NEIGHBOR_CACHE_MIN_SIZE: usize = 1000;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Board<T> {
     rows: usize,
     cols: usize,
     board: Vec<T>,
+    #[serde(skip)]
+    neighbor_cache: Option<Arc<[ArrayVec<[BoardPoint; 8]>]>>,
+}
+
+impl<T: PartialEq> PartialEq for Board<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rows == other.rows && self.cols == other.cols && self.board == other.board
+    }
 }
 
+impl<T: Eq> Eq for Board<T> {}
+
 impl<T: Debug> Debug for Board<T> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let rows = (0..self.rows)
             .collect::<Vec<usize>>()
             .iter()
             .map(|row| {
-                let row_slice = &self.board[(row * self.cols)..(row * self.cols + self.cols)];
+                let row_slice = self.row(*row).unwrap_or_default();
                 let row_flat = row_slice
                     .iter()
                     .fold(String::new(), |acc, item| acc + &format!("{:?}", item));
@@ -68,7 +122,7 @@ impl<T: Display> Display for Board<T> {
             .collect::<Vec<usize>>()
             .iter()
             .map(|row| {
-                let row_slice = &self.board[(row * self.cols)..(row * self.cols + self.cols)];
+                let row_slice = self.row(*row).unwrap_or_default();
                 let row_flat = row_slice
                     .iter()
                     .fold(String::new(), |acc, item| acc + &format!("{}", item));
@@ -87,14 +141,14 @@ impl<T> Index<&BoardPoint> for Board<T> {
     type Output = T;
 
     fn index(&self, point: &BoardPoint) -> &Self::Output {
-        let index = point.row * self.cols + point.col;
+        let index = self.index_from_point(*point);
         &self.board[index]
     }
 }
 
 impl<T> IndexMut<&BoardPoint> for Board<T> {
     fn index_mut(&mut self, point: &BoardPoint) -> &mut Self::Output {
-        let index = point.row * self.cols + point.col;
+        let index = self.index_from_point(*point);
         &mut self.board[index]
     }
 }
@@ -103,14 +157,14 @@ impl<T> Index<BoardPoint> for Board<T> {
     type Output = T;
 
     fn index(&self, point: BoardPoint) -> &Self::Output {
-        let index = point.row * self.cols + point.col;
+        let index = self.index_from_point(point);
         &self.board[index]
     }
 }
 
 impl<T> IndexMut<BoardPoint> for Board<T> {
     fn index_mut(&mut self, point: BoardPoint) -> &mut Self::Output {
-        let index = point.row * self.cols + point.col;
+        let index = self.index_from_point(point);
         &mut self.board[index]
     }
 }
@@ -125,9 +179,29 @@ impl<T> Board<T> {
             rows,
             cols,
             board: vec![item; total],
+            neighbor_cache: None,
+        }
+    }
+
+    /// Builds a board by calling `f` for every point, in row-major order
+    pub fn new_with(rows: usize, cols: usize, mut f: impl FnMut(BoardPoint) -> T) -> Self {
+        let board = (0..rows * cols)
+            .map(|i| f(BoardPoint::from_index(i, cols)))
+            .collect();
+        Board {
+            rows,
+            cols,
+            board,
+            neighbor_cache: None,
         }
     }
 
+    /// Builds a board from rows of cells - the first row sets the width.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `vec` is empty.  Row lengths aren't checked, so ragged rows give a garbled
+    /// board - use `Board::try_from` for untrusted input.
     pub fn from_vec(vec: Vec<Vec<T>>) -> Self {
         let rows = vec.len();
         let cols = vec[0].len();
@@ -135,18 +209,18 @@ impl<T> Board<T> {
             rows,
             cols,
             board: vec.into_iter().flatten().collect(),
+            neighbor_cache: None,
         }
     }
 
+    /// Point stored at `index` in row-major order
     pub fn point_from_index(&self, index: usize) -> BoardPoint {
-        BoardPoint {
-            row: index / self.cols,
-            col: index % self.cols,
-        }
+        BoardPoint::from_index(index, self.cols)
     }
 
+    /// Row-major index of `point` - the inverse of [`Board::point_from_index`]
     pub fn index_from_point(&self, point: BoardPoint) -> usize {
-        point.row & (self.cols + point.col)
+        point.index(self.cols)
     }
 
     pub fn rows(&self) -> usize {
@@ -165,14 +239,31 @@ impl<T> Board<T> {
         self.board.is_empty()
     }
 
-    pub fn rows_iter(&self) -> Chunks<T> {
+    pub fn rows_iter(&self) -> Chunks<'_, T> {
         self.board.chunks(self.cols)
     }
 
-    pub fn rows_iter_mut(&mut self) -> ChunksMut<T> {
+    pub fn rows_iter_mut(&mut self) -> ChunksMut<'_, T> {
         self.board.chunks_mut(self.cols)
     }
 
+    /// Cells of a single row, or `None` if `row` is out of bounds
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        if row >= self.rows {
+            return None;
+        }
+        let start = self.index_from_point(BoardPoint { row, col: 0 });
+        Some(&self.board[start..start + self.cols])
+    }
+
+    /// Cells of a single column from top to bottom, or `None` if `col` is out of bounds
+    pub fn col(&self, col: usize) -> Option<impl Iterator<Item = &T>> {
+        if col >= self.cols {
+            return None;
+        }
+        Some(self.board.iter().skip(col).step_by(self.cols))
+    }
+
     pub fn iter(&self) -> Iter<'_, T> {
         self.board.iter()
     }
@@ -181,11 +272,165 @@ impl<T> Board<T> {
         self.board.iter_mut()
     }
 
+    pub fn count_where(&self, pred: impl Fn(&T) -> bool) -> usize {
+        self.board.iter().filter(|&item| pred(item)).count()
+    }
+
+    /// Finds the first point (in row-major order) matching the predicate
+    pub fn find_point(&self, pred: impl Fn(&T) -> bool) -> Option<BoardPoint> {
+        self.board
+            .iter()
+            .position(pred)
+            .map(|index| self.point_from_index(index))
+    }
+
+    /// Cells of `other` which differ from this board, in row-major order - applying them with
+    /// `apply_diff` turns this board into `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the boards aren't the same size.
+    pub fn diff(&self, other: &Self) -> Vec<(BoardPoint, T)>
+    where
+        T: PartialEq + Clone,
+    {
+        assert_eq!(
+            (self.rows, self.cols),
+            (other.rows, other.cols),
+            "Can't diff boards of different sizes"
+        );
+        self.board
+            .iter()
+            .zip(other.board.iter())
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(index, (_, new))| (self.point_from_index(index), new.clone()))
+            .collect()
+    }
+
+    /// Sets each point in `diff` to its cell
+    pub fn apply_diff(&mut self, diff: impl IntoIterator<Item = (BoardPoint, T)>) {
+        diff.into_iter()
+            .for_each(|(point, item)| self[point] = item);
+    }
+
+    pub fn flip_horizontal(&self) -> Self
+    where
+        T: Clone,
+    {
+        Board {
+            rows: self.rows,
+            cols: self.cols,
+            board: self
+                .rows_iter()
+                .flat_map(|row| row.iter().rev().cloned())
+                .collect(),
+            neighbor_cache: self.neighbor_cache.clone(),
+        }
+    }
+
+    pub fn flip_vertical(&self) -> Self
+    where
+        T: Clone,
+    {
+        Board {
+            rows: self.rows,
+            cols: self.cols,
+            board: self.rows_iter().rev().flatten().cloned().collect(),
+            neighbor_cache: self.neighbor_cache.clone(),
+        }
+    }
+
+    /// Rotates clockwise - rows and cols are swapped
+    pub fn rotate_90(&self) -> Self
+    where
+        T: Clone,
+    {
+        let rows = self.cols;
+        let cols = self.rows;
+        let board = (0..rows)
+            .flat_map(|row| {
+                (0..cols).map(move |col| {
+                    self[BoardPoint {
+                        row: self.rows - 1 - col,
+                        col: row,
+                    }]
+                    .clone()
+                })
+            })
+            .collect();
+        Board {
+            rows,
+            cols,
+            board,
+            neighbor_cache: None,
+        }
+    }
+
     pub fn is_in_bounds(&self, point: BoardPoint) -> bool {
         point.row < self.rows && point.col < self.cols
     }
 
+    /// Precomputes every cell's neighbors so `neighbors` becomes a lookup - boards smaller than
+    /// `NEIGHBOR_CACHE_MIN_SIZE` are returned unchanged
+    pub fn with_neighbor_cache(mut self) -> Self {
+        if self.size() >= NEIGHBOR_CACHE_MIN_SIZE && self.neighbor_cache.is_none() {
+            self.neighbor_cache = Some(
+                (0..self.size())
+                    .map(|i| self.compute_neighbors(&self.point_from_index(i)))
+                    .collect(),
+            );
+        }
+        self
+    }
+
+    pub fn has_neighbor_cache(&self) -> bool {
+        self.neighbor_cache.is_some()
+    }
+
+    /// Each opening - a connected region of cells where `is_zero`, plus the cells bordering it
+    /// where `is_border`, which a single click on the region clears.  Border cells can belong to
+    /// more than one opening.
+    pub fn openings_by(
+        &self,
+        is_zero: impl Fn(&T) -> bool,
+        is_border: impl Fn(&T) -> bool,
+    ) -> Vec<HashSet<BoardPoint>> {
+        let mut visited = Board::new(self.rows, self.cols, false);
+        let mut openings = Vec::new();
+        for start in (0..self.size()).map(|i| self.point_from_index(i)) {
+            if visited[start] || !is_zero(&self[start]) {
+                continue;
+            }
+            visited[start] = true;
+            let mut opening = HashSet::from([start]);
+            let mut to_visit = vec![start];
+            while let Some(visit) = to_visit.pop() {
+                for neighbor in self.neighbors(&visit) {
+                    let cell = &self[neighbor];
+                    let zero = is_zero(cell);
+                    if !zero && !is_border(cell) {
+                        continue;
+                    }
+                    if opening.insert(neighbor) && zero {
+                        visited[neighbor] = true;
+                        to_visit.push(neighbor);
+                    }
+                }
+            }
+            openings.push(opening);
+        }
+        openings
+    }
+
     pub fn neighbors(&self, point: &BoardPoint) -> ArrayVec<[BoardPoint; 8]> {
+        match &self.neighbor_cache {
+            Some(cache) => cache[self.index_from_point(*point)],
+            None => self.compute_neighbors(point),
+        }
+    }
+
+    fn compute_neighbors(&self, point: &BoardPoint) -> ArrayVec<[BoardPoint; 8]> {
         let mut neighbors = array_vec!([BoardPoint; 8]);
 
         let row = point.row;
@@ -234,13 +479,7 @@ fn unsigned_diff<T>(first: T, second: T) -> usize
 where
     T: Into<usize>,
 {
-    let first = first.into();
-    let second = second.into();
-    if first >= second {
-        first - second
-    } else {
-        second - first
-    }
+    first.into().abs_diff(second.into())
 }
 
 #[derive(
@@ -252,7 +491,31 @@ pub struct BoardPoint {
 }
 
 impl BoardPoint {
-    pub(crate) fn is_neighbor(&self, p2: &BoardPoint) -> bool {
+    /// Point at `index` in a row-major grid `cols` wide - all index arithmetic goes through
+    /// here and [`BoardPoint::index`] so rows and columns can't get swapped
+    pub fn from_index(index: usize, cols: usize) -> Self {
+        BoardPoint {
+            row: index / cols,
+            col: index % cols,
+        }
+    }
+
+    /// Index of the point in a row-major grid `cols` wide
+    pub fn index(&self, cols: usize) -> usize {
+        self.row * cols + self.col
+    }
+
+    /// Point shifted by `rows` and `cols` - `None` if it would go below zero.  Doesn't check
+    /// the upper bounds of any board.
+    pub fn offset(&self, rows: isize, cols: isize) -> Option<BoardPoint> {
+        Some(BoardPoint {
+            row: self.row.checked_add_signed(rows)?,
+            col: self.col.checked_add_signed(cols)?,
+        })
+    }
+
+    /// Whether the points touch, including diagonally - a point isn't its own neighbor
+    pub fn is_neighbor(&self, p2: &BoardPoint) -> bool {
         if self == p2 {
             // not neighbor to self
             return false;
@@ -260,3 +523,255 @@ impl BoardPoint {
         unsigned_diff(self.row, p2.row) <= 1 && unsigned_diff(self.col, p2.col) <= 1
     }
 }
+
+impl Display for BoardPoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cell::{Cell, HiddenCell, PlayerCell, RevealedCell};
+
+    fn numbered_board(rows: usize, cols: usize) -> Board<usize> {
+        Board {
+            rows,
+            cols,
+            board: (0..rows * cols).collect(),
+            neighbor_cache: None,
+        }
+    }
+
+    #[test]
+    fn neighbor_cache_matches_computed() {
+        for (rows, cols) in [(1, 1000), (1000, 1), (2, 500), (40, 40), (37, 91)] {
+            let board = Board::new(rows, cols, 0).with_neighbor_cache();
+            assert!(board.has_neighbor_cache());
+            for i in 0..board.size() {
+                let point = board.point_from_index(i);
+                assert_eq!(board.neighbors(&point), board.compute_neighbors(&point));
+            }
+            // flips keep the same dimensions, so they can share the cache
+            let flipped = board.flip_vertical();
+            assert!(flipped.has_neighbor_cache());
+            assert_eq!(flipped, board);
+        }
+    }
+
+    #[test]
+    fn index_point_round_trip() {
+        let board = numbered_board(3, 5);
+        for i in 0..board.size() {
+            let point = board.point_from_index(i);
+            assert_eq!(board.index_from_point(point), i);
+            assert_eq!(board[point], i);
+        }
+        let last = BoardPoint { row: 2, col: 4 };
+        assert_eq!(board.point_from_index(0), BoardPoint { row: 0, col: 0 });
+        assert_eq!(board.point_from_index(4), BoardPoint { row: 0, col: 4 });
+        assert_eq!(board.point_from_index(5), BoardPoint { row: 1, col: 0 });
+        assert_eq!(board.point_from_index(14), last);
+        assert_eq!(board.index_from_point(last), 14);
+        assert_eq!(BoardPoint::from_index(14, 5), last);
+        assert_eq!(last.index(5), 14);
+    }
+
+    #[test]
+    fn row_and_col_access() {
+        let board = numbered_board(3, 5);
+        assert_eq!(board.row(1), Some(&[5, 6, 7, 8, 9][..]));
+        assert_eq!(board.row(2), Some(&[10, 11, 12, 13, 14][..]));
+        assert_eq!(board.row(3), None);
+        assert_eq!(
+            board.col(3).unwrap().copied().collect::<Vec<_>>(),
+            vec![3, 8, 13]
+        );
+        assert_eq!(
+            board.col(4).unwrap().copied().collect::<Vec<_>>(),
+            vec![4, 9, 14]
+        );
+        assert!(board.col(5).is_none());
+    }
+
+    #[test]
+    fn try_from_rectangular() {
+        let board = Board::try_from(vec![vec![0, 1, 2], vec![3, 4, 5]]).unwrap();
+        assert_eq!((board.rows(), board.cols()), (2, 3));
+        assert_eq!(board, numbered_board(2, 3));
+        let empty = Board::<usize>::try_from(Vec::new()).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn try_from_ragged() {
+        let err = Board::try_from(vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7]]).unwrap_err();
+        assert_eq!(
+            err,
+            BoardError::RaggedRow {
+                row: 2,
+                expected: 3,
+                len: 2
+            }
+        );
+        assert_eq!(err.to_string(), "Row 2 has 2 cells, expected 3");
+    }
+
+    #[test]
+    fn neighbor_cache_skips_small_boards() {
+        let board = Board::new(16, 30, 0).with_neighbor_cache();
+        assert!(!board.has_neighbor_cache());
+        assert_eq!(board.neighbors(&BoardPoint { row: 0, col: 0 }).len(), 3);
+    }
+
+    #[test]
+    fn offset_works() {
+        let point = BoardPoint { row: 1, col: 2 };
+        assert_eq!(point.offset(1, -2), Some(BoardPoint { row: 2, col: 0 }));
+        assert_eq!(point.offset(-1, 3), Some(BoardPoint { row: 0, col: 5 }));
+        assert_eq!(point.offset(0, 0), Some(point));
+        assert_eq!(point.offset(-2, 0), None);
+        assert_eq!(point.offset(0, -3), None);
+    }
+
+    #[test]
+    fn is_neighbor_works() {
+        let point = BoardPoint { row: 1, col: 1 };
+        assert!(!point.is_neighbor(&point));
+        assert!(point.is_neighbor(&BoardPoint { row: 0, col: 1 }));
+        assert!(point.is_neighbor(&BoardPoint { row: 1, col: 2 }));
+        assert!(point.is_neighbor(&BoardPoint { row: 0, col: 0 }));
+        assert!(point.is_neighbor(&BoardPoint { row: 2, col: 2 }));
+        assert!(point.is_neighbor(&BoardPoint { row: 2, col: 0 }));
+        assert!(!point.is_neighbor(&BoardPoint { row: 3, col: 1 }));
+        assert!(!point.is_neighbor(&BoardPoint { row: 1, col: 3 }));
+        assert!(!point.is_neighbor(&BoardPoint { row: 3, col: 3 }));
+        // all neighbors from Board::neighbors agree
+        let board = Board::new(3, 3, 0);
+        let neighbors = board.neighbors(&point);
+        assert_eq!(neighbors.len(), 8);
+        assert!(neighbors.iter().all(|p| p.is_neighbor(&point)));
+    }
+
+    #[test]
+    fn new_with_stores_index() {
+        let board = Board::new_with(3, 4, |p| p.row * 4 + p.col);
+        assert_eq!(board.rows(), 3);
+        assert_eq!(board.cols(), 4);
+        assert!(board.iter().copied().eq(0..12));
+        assert_eq!(board[BoardPoint { row: 2, col: 1 }], 9);
+    }
+
+    #[test]
+    fn count_where_counts_mines() {
+        let board = Board::from_vec(vec![
+            vec![Cell::Mine, Cell::Empty(2), Cell::Mine],
+            vec![Cell::Empty(1), Cell::Empty(2), Cell::Empty(1)],
+        ]);
+        assert_eq!(board.count_where(|c| matches!(c, Cell::Mine)), 2);
+        assert_eq!(board.count_where(|c| matches!(c, Cell::Empty(2))), 2);
+        assert_eq!(board.count_where(|c| matches!(c, Cell::Empty(0))), 0);
+    }
+
+    #[test]
+    fn find_point_finds_first_revealed() {
+        let hidden = PlayerCell::Hidden(HiddenCell::Empty);
+        let revealed = PlayerCell::Revealed(RevealedCell {
+            player: 0,
+            contents: Cell::Empty(1),
+        });
+        let mut board = Board::new(3, 4, hidden);
+        assert_eq!(
+            board.find_point(|c| matches!(c, PlayerCell::Revealed(_))),
+            None
+        );
+        board[BoardPoint { row: 2, col: 1 }] = revealed;
+        board[BoardPoint { row: 1, col: 3 }] = revealed;
+        assert_eq!(
+            board.find_point(|c| matches!(c, PlayerCell::Revealed(_))),
+            Some(BoardPoint { row: 1, col: 3 })
+        );
+    }
+
+    #[test]
+    fn apply_diff_round_trip() {
+        let hidden = PlayerCell::Hidden(HiddenCell::Empty);
+        let flag = PlayerCell::Hidden(HiddenCell::Flag);
+        let revealed = PlayerCell::Revealed(RevealedCell {
+            player: 1,
+            contents: Cell::Empty(2),
+        });
+        let mut a = Board::new(3, 4, hidden);
+        a[BoardPoint { row: 0, col: 1 }] = flag;
+        let mut b = a.clone();
+        b[BoardPoint { row: 0, col: 1 }] = hidden;
+        b[BoardPoint { row: 1, col: 2 }] = revealed;
+        b[BoardPoint { row: 2, col: 3 }] = flag;
+
+        let diff = a.diff(&b);
+        assert_eq!(
+            diff,
+            vec![
+                (BoardPoint { row: 0, col: 1 }, hidden),
+                (BoardPoint { row: 1, col: 2 }, revealed),
+                (BoardPoint { row: 2, col: 3 }, flag),
+            ]
+        );
+        a.apply_diff(diff);
+        assert_eq!(a, b);
+        assert!(a.diff(&b).is_empty());
+    }
+
+    #[test]
+    fn flip_horizontal_works() {
+        let board = numbered_board(2, 3);
+        let flipped = board.flip_horizontal();
+        assert_eq!(flipped, Board::from_vec(vec![vec![2, 1, 0], vec![5, 4, 3]]));
+        assert_eq!(flipped.flip_horizontal(), board);
+    }
+
+    #[test]
+    fn flip_vertical_works() {
+        let board = numbered_board(2, 3);
+        let flipped = board.flip_vertical();
+        assert_eq!(flipped, Board::from_vec(vec![vec![3, 4, 5], vec![0, 1, 2]]));
+        assert_eq!(flipped.flip_vertical(), board);
+    }
+
+    #[test]
+    fn rotate_90_works() {
+        let board = numbered_board(2, 3);
+        let rotated = board.rotate_90();
+        assert_eq!(rotated.rows(), 3);
+        assert_eq!(rotated.cols(), 2);
+        assert_eq!(
+            rotated,
+            Board::from_vec(vec![vec![3, 0], vec![4, 1], vec![5, 2]])
+        );
+        assert_eq!(rotated.rotate_90().rotate_90().rotate_90(), board);
+    }
+
+    #[test]
+    fn openings_by_shares_borders() {
+        // 0s split by a column of 1s, with a blocked cell the openings can't reach
+        let board = Board::from_vec(vec![vec![0, 1, 0], vec![0, 1, 0], vec![9, 1, 0]]);
+        let point = |row, col| BoardPoint { row, col };
+        let mut openings = board.openings_by(|&c| c == 0, |&c| c < 9);
+        openings.sort_by_key(|o| o.iter().min().copied());
+        assert_eq!(openings.len(), 2);
+        assert_eq!(
+            openings[0],
+            HashSet::from([
+                point(0, 0),
+                point(1, 0),
+                point(0, 1),
+                point(1, 1),
+                point(2, 1)
+            ])
+        );
+        assert_eq!(openings[1].len(), 6);
+        assert!(openings[1].contains(&point(1, 1)));
+        assert!(openings.iter().all(|o| !o.contains(&point(2, 0))));
+    }
+}
@@ -17,24 +17,17 @@ impl Default for PlayerCell {
     }
 }
 
+/// Single character used for terminal output - revealed cells match [`RevealedCell`]
 impl Display for PlayerCell {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::Hidden(hc) => match hc {
                 HiddenCell::Empty => write!(f, "-"),
                 HiddenCell::Mine => write!(f, "*"),
-                HiddenCell::Flag => write!(f, "f"),
-                HiddenCell::FlagMine => write!(f, "F"),
+                HiddenCell::Flag | HiddenCell::FlagMine => write!(f, "F"),
+                HiddenCell::Question => write!(f, "?"),
             },
-            Self::Revealed(rc) => write!(
-                f,
-                "{}",
-                if let Some(v) = rc.contents.value() {
-                    format!("{v}")
-                } else {
-                    "X".to_string()
-                }
-            ),
+            Self::Revealed(rc) => write!(f, "{rc}"),
         }
     }
 }
@@ -62,6 +55,20 @@ impl PlayerCell {
         }
     }
 
+    pub fn add_question(self) -> Self {
+        match self {
+            Self::Hidden(HiddenCell::Empty) => Self::Hidden(HiddenCell::Question),
+            _ => self,
+        }
+    }
+
+    pub fn remove_question(self) -> Self {
+        match self {
+            Self::Hidden(HiddenCell::Question) => Self::Hidden(HiddenCell::Empty),
+            _ => self,
+        }
+    }
+
     pub fn into_hidden(self) -> Self {
         match self {
             Self::Hidden(_) => self,
@@ -83,6 +90,8 @@ pub enum HiddenCell {
     Flag,
     #[serde(rename = "fm", alias = "FlagMine")]
     FlagMine, // post-game only
+    #[serde(rename = "q", alias = "Question")]
+    Question,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -93,6 +102,26 @@ pub struct RevealedCell {
     pub contents: Cell,
 }
 
+impl RevealedCell {
+    pub fn number(&self) -> Option<u8> {
+        self.contents.value()
+    }
+
+    pub fn is_mine(&self) -> bool {
+        self.contents.is_mine()
+    }
+}
+
+impl Display for RevealedCell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.contents {
+            Cell::Empty(0) => write!(f, " "),
+            Cell::Empty(x) => write!(f, "{x}"),
+            Cell::Mine => write!(f, "*"),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct CellState {
     pub revealed: bool,
@@ -114,16 +143,21 @@ impl Default for Cell {
 }
 
 impl Cell {
+    /// Most neighbors a cell can have on a square board
+    pub const MAX_NEIGHBORS: u8 = 8;
+
+    /// Saturates at [`Cell::MAX_NEIGHBORS`]
     pub fn increment(self) -> Self {
         match self {
-            Self::Empty(x) => Cell::Empty(x + 1),
+            Self::Empty(x) => Cell::Empty(x.saturating_add(1).min(Self::MAX_NEIGHBORS)),
             Self::Mine => Cell::Mine,
         }
     }
 
+    /// Saturates at 0
     pub fn decrement(self) -> Self {
         match self {
-            Self::Empty(x) => Cell::Empty(x - 1),
+            Self::Empty(x) => Cell::Empty(x.saturating_sub(1)),
             Self::Mine => Cell::Mine,
         }
     }
@@ -153,3 +187,95 @@ impl Cell {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn revealed_cell_display() {
+        let revealed = |contents| RevealedCell {
+            player: 0,
+            contents,
+        };
+        assert_eq!(revealed(Cell::Empty(0)).to_string(), " ");
+        (1..=8).for_each(|x| assert_eq!(revealed(Cell::Empty(x)).to_string(), x.to_string()));
+        assert_eq!(revealed(Cell::Mine).to_string(), "*");
+    }
+
+    #[test]
+    fn player_cell_display() {
+        let revealed = |contents| {
+            PlayerCell::Revealed(RevealedCell {
+                player: 0,
+                contents,
+            })
+        };
+        assert_eq!(PlayerCell::Hidden(HiddenCell::Empty).to_string(), "-");
+        assert_eq!(PlayerCell::Hidden(HiddenCell::Flag).to_string(), "F");
+        assert_eq!(PlayerCell::Hidden(HiddenCell::FlagMine).to_string(), "F");
+        assert_eq!(PlayerCell::Hidden(HiddenCell::Mine).to_string(), "*");
+        assert_eq!(PlayerCell::Hidden(HiddenCell::Question).to_string(), "?");
+        assert_eq!(revealed(Cell::Empty(0)).to_string(), " ");
+        assert_eq!(revealed(Cell::Empty(3)).to_string(), "3");
+        assert_eq!(revealed(Cell::Mine).to_string(), "*");
+    }
+
+    #[test]
+    fn revealed_cell_accessors() {
+        let empty = RevealedCell {
+            player: 1,
+            contents: Cell::Empty(3),
+        };
+        assert_eq!(empty.number(), Some(3));
+        assert!(!empty.is_mine());
+        let mine = RevealedCell {
+            player: 1,
+            contents: Cell::Mine,
+        };
+        assert_eq!(mine.number(), None);
+        assert!(mine.is_mine());
+    }
+
+    #[test]
+    fn increment_saturates() {
+        assert_eq!(Cell::Empty(0).increment(), Cell::Empty(1));
+        assert_eq!(
+            Cell::Empty(Cell::MAX_NEIGHBORS).increment(),
+            Cell::Empty(Cell::MAX_NEIGHBORS)
+        );
+        assert_eq!(
+            Cell::Empty(u8::MAX).increment(),
+            Cell::Empty(Cell::MAX_NEIGHBORS)
+        );
+        assert_eq!(Cell::Mine.increment(), Cell::Mine);
+    }
+
+    #[test]
+    fn decrement_saturates() {
+        assert_eq!(Cell::Empty(1).decrement(), Cell::Empty(0));
+        assert_eq!(Cell::Empty(0).decrement(), Cell::Empty(0));
+        assert_eq!(Cell::Mine.decrement(), Cell::Mine);
+    }
+
+    #[test]
+    fn question_round_trip() {
+        let cell = PlayerCell::Hidden(HiddenCell::Question);
+        let json = serde_json::to_string(&cell).unwrap();
+        assert_eq!(json, "\"q\"");
+        assert_eq!(serde_json::from_str::<PlayerCell>(&json).unwrap(), cell);
+        assert_eq!(
+            serde_json::from_str::<PlayerCell>("\"Question\"").unwrap(),
+            cell
+        );
+    }
+
+    #[test]
+    fn question_marking() {
+        let cell = PlayerCell::default().add_question();
+        assert_eq!(cell, PlayerCell::Hidden(HiddenCell::Question));
+        assert_eq!(cell.add_flag(), cell);
+        assert_eq!(cell.remove_question(), PlayerCell::default());
+        assert_eq!(format!("{}", cell), "?");
+    }
+}
@@ -1,17 +1,20 @@
 use std::cmp::max;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 
+use crate::available::AvailableCells;
 use crate::board::{Board, BoardPoint};
 use crate::cell::{Cell, CellState, HiddenCell, PlayerCell, RevealedCell};
 use crate::client::ClientPlayer;
 use crate::replay::MinesweeperReplay;
 
-use anyhow::{bail, Ok, Result};
-use rand::{seq::SliceRandom, thread_rng};
+use anyhow::{anyhow, bail, Ok, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::{rngs::StdRng, seq::SliceRandom, RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use tinyvec::ArrayVec;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct MinesweeperOpts {
     pub rows: usize,
     pub cols: usize,
@@ -19,32 +22,115 @@ pub struct MinesweeperOpts {
 }
 
 impl MinesweeperOpts {
-    fn validate(&self) -> bool {
-        if self.rows == 0 || self.cols == 0 || self.num_mines == 0 {
-            return false;
+    /// Builds options from a fraction of cells which should be mines, e.g. `0.2` for 20%
+    pub fn from_density(rows: usize, cols: usize, density: f64) -> Result<Self> {
+        if !(density > 0.0 && density < 1.0) {
+            bail!("Mine density must be between 0 and 1")
+        }
+        let num_mines = ((rows * cols) as f64 * density).round() as usize;
+        let opts = MinesweeperOpts {
+            rows,
+            cols,
+            num_mines,
+        };
+        opts.validate()?;
+        Ok(opts)
+    }
+
+    pub fn validate(&self) -> Result<(), OptsError> {
+        if self.rows == 0 || self.cols == 0 {
+            return Err(OptsError::ZeroDimension);
+        }
+        if self.num_mines == 0 {
+            return Err(OptsError::ZeroMines);
         }
         let total = self.rows * self.cols;
-        self.num_mines < total
+        if self.num_mines >= total {
+            return Err(OptsError::TooManyMines {
+                num_mines: self.num_mines,
+                total,
+            });
+        }
+        Result::Ok(())
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum OptsError {
+    #[error("Rows and columns must be greater than 0")]
+    ZeroDimension,
+    #[error("Number of mines must be greater than 0")]
+    ZeroMines,
+    #[error("Too many mines ({num_mines}) - must be fewer than the number of cells ({total})")]
+    TooManyMines { num_mines: usize, total: usize },
+    #[error("Mine at {0} is outside of the board")]
+    MineOutOfBounds(BoardPoint),
+    #[error("Mine at {0} is listed more than once")]
+    DuplicateMine(BoardPoint),
+}
+
+/// Why a [`Play`] was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum PlayError {
+    #[error("Game is over")]
+    GameOver,
+    #[error("Player {0} doesn't exist")]
+    UnknownPlayer(usize),
+    #[error("Tried to play as dead player")]
+    DeadPlayer,
+    #[error("Tried to play point outside of playzone")]
+    OutOfBounds,
+    #[error("Tried to play already revealed cell")]
+    AlreadyRevealed,
+    #[error("Tried to play flagged cell")]
+    FlaggedCell,
+    #[error("Tried to place more flags than there are mines")]
+    FlagLimit,
+    #[error("Tried to double-click cell that isn't revealed")]
+    NotRevealed,
+    #[error("Tried to double-click cell without a number")]
+    NoNumber,
+    #[error("Tried to double-click with wrong number of flagged neighbors.  Expected {expected} got {got}")]
+    DoubleClickMismatch { expected: u8, got: u8 },
+}
+
+/// Randomness for mine placement - any RNG can be plugged in with [`MinesweeperBuilder::with_rng`]
+type GameRng = Box<dyn RngCore + Send + Sync>;
+
 pub struct MinesweeperBuilder {
     opts: MinesweeperOpts,
     players: Option<usize>,
     log: bool,
     superclick: bool,
+    flag_limit: bool,
+    question_marks: bool,
+    lenient_chord: bool,
+    seed: Option<u64>,
+    rng: Option<GameRng>,
+    min_opening: Option<usize>,
+    mines_at: Option<Vec<BoardPoint>>,
+    win_condition: WinCondition,
 }
 
+/// Cap on re-rolls for [`MinesweeperBuilder::with_min_opening`] so impossible thresholds still finish
+const MAX_OPENING_REROLLS: usize = 100;
+
 impl MinesweeperBuilder {
     pub fn new(opts: MinesweeperOpts) -> Result<Self> {
-        if !opts.validate() {
-            bail!("Invalid minesweeper options")
-        }
+        opts.validate()?;
         Ok(Self {
             opts,
             players: None,
             log: false,
             superclick: false,
+            flag_limit: false,
+            question_marks: false,
+            lenient_chord: false,
+            seed: None,
+            rng: None,
+            min_opening: None,
+            mines_at: None,
+            win_condition: WinCondition::default(),
         })
     }
 
@@ -63,16 +149,84 @@ impl MinesweeperBuilder {
         self
     }
 
-    pub fn init(self) -> Minesweeper {
+    pub fn with_flag_limit(mut self) -> Self {
+        self.flag_limit = true;
+        self
+    }
+
+    pub fn with_question_marks(mut self) -> Self {
+        self.question_marks = true;
+        self
+    }
+
+    /// Double-clicking a number without the right count of flags around it does nothing,
+    /// instead of failing with [`PlayError::DoubleClickMismatch`]
+    pub fn with_lenient_chord(mut self) -> Self {
+        self.lenient_chord = true;
+        self
+    }
+
+    /// Same seed and options always generate the same game
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Uses `rng` for planting and replanting mines, e.g. a mock to force where mines go -
+    /// takes priority over [`MinesweeperBuilder::with_seed`]
+    pub fn with_rng(mut self, rng: impl RngCore + Send + Sync + 'static) -> Self {
+        self.rng = Some(Box::new(rng));
+        self
+    }
+
+    pub fn with_win_condition(mut self, win_condition: WinCondition) -> Self {
+        self.win_condition = win_condition;
+        self
+    }
+
+    /// Re-rolls the board until it has an opening of at least `size` zero cells
+    pub fn with_min_opening(mut self, size: usize) -> Self {
+        self.min_opening = Some(size);
+        self
+    }
+
+    /// Plants exactly `points` instead of random mines - `num_mines` becomes `points.len()`.
+    ///
+    /// Mines still move off a player's first click like any other game.
+    pub fn with_mines_at(mut self, points: Vec<BoardPoint>) -> Result<Self, OptsError> {
+        let mut seen = HashSet::with_capacity(points.len());
+        for point in points.iter() {
+            if point.row >= self.opts.rows || point.col >= self.opts.cols {
+                return Err(OptsError::MineOutOfBounds(*point));
+            }
+            if !seen.insert(*point) {
+                return Err(OptsError::DuplicateMine(*point));
+            }
+        }
+        self.opts.num_mines = points.len();
+        self.opts.validate()?;
+        self.mines_at = Some(points);
+        Result::Ok(self)
+    }
+
+    fn plant_board(&self, rng: &mut GameRng) -> (Board<(Cell, CellState)>, AvailableCells) {
         let mut board = Board::new(
             self.opts.rows,
             self.opts.cols,
             (Cell::default(), CellState::default()),
-        );
+        )
+        .with_neighbor_cache();
         let mut available: Vec<_> = (0..board.size())
             .map(|x| board.point_from_index(x))
             .collect();
-        available.shuffle(&mut thread_rng());
+        match &self.mines_at {
+            Some(mines) => {
+                // fixed mines go first so the rest of the board stays available
+                available.retain(|p| !mines.contains(p));
+                available.splice(0..0, mines.iter().copied());
+            }
+            None => available.shuffle(rng),
+        }
         let points_to_plant = &available[0..self.opts.num_mines];
         points_to_plant.iter().for_each(|x| {
             board[x].0 = board[x].0.plant().unwrap();
@@ -82,18 +236,74 @@ impl MinesweeperBuilder {
                 board[c].0 = board[c].0.increment();
             });
         });
-        let available = available.into_iter().skip(self.opts.num_mines).collect();
+        let available = AvailableCells::from_points(
+            self.opts.rows,
+            self.opts.cols,
+            available.into_iter().skip(self.opts.num_mines),
+        );
+        (board, available)
+    }
+
+    pub fn init(mut self) -> Minesweeper {
+        let mut rng: GameRng = match (self.rng.take(), self.seed) {
+            (Some(rng), _) => rng,
+            (None, Some(seed)) => Box::new(StdRng::seed_from_u64(seed)),
+            (None, None) => Box::new(StdRng::from_entropy()),
+        };
+        let (mut board, mut available) = self.plant_board(&mut rng);
+        if let (Some(min_opening), None) = (self.min_opening, &self.mines_at) {
+            let mut largest = board.largest_opening_size();
+            let mut rerolls = 0;
+            while largest < min_opening && rerolls < MAX_OPENING_REROLLS {
+                let (new_board, new_available) = self.plant_board(&mut rng);
+                let new_largest = new_board.largest_opening_size();
+                // keep the best board in case the threshold is never met
+                if new_largest > largest {
+                    (board, available, largest) = (new_board, new_available, new_largest);
+                }
+                rerolls += 1;
+            }
+        }
         Minesweeper {
             available,
             players: vec![Player::default(); self.players.unwrap_or(1)],
             board,
             superclick: self.superclick,
+            flag_limit: if self.flag_limit {
+                Some(self.opts.num_mines)
+            } else {
+                None
+            },
+            question_marks: self.question_marks,
+            lenient_chord: self.lenient_chord,
             log: if self.log { Some(Vec::new()) } else { None },
+            rng,
+            timed_out: false,
+            win_condition: self.win_condition,
+            moves: 0,
         }
     }
 }
 
 impl Board<(Cell, CellState)> {
+    /// Every opening (connected region of zero cells) with its numbered border
+    fn openings(&self) -> Vec<HashSet<BoardPoint>> {
+        self.openings_by(|(cell, _)| *cell == Cell::Empty(0), |_| true)
+    }
+
+    fn largest_opening_size(&self) -> usize {
+        self.openings()
+            .iter()
+            .map(|opening| {
+                opening
+                    .iter()
+                    .filter(|p| self[*p].0 == Cell::Empty(0))
+                    .count()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
     fn viewer_board(&self, is_final: bool) -> Board<PlayerCell> {
         let mut new_board =
             Board::<PlayerCell>::new(self.rows(), self.cols(), PlayerCell::default());
@@ -149,37 +359,94 @@ pub struct Play {
     pub point: BoardPoint,
 }
 
+impl Play {
+    pub fn reveal(player: usize, point: BoardPoint) -> Self {
+        Play {
+            player,
+            action: Action::Reveal,
+            point,
+        }
+    }
+
+    pub fn flag(player: usize, point: BoardPoint) -> Self {
+        Play {
+            player,
+            action: Action::Flag,
+            point,
+        }
+    }
+
+    /// Reveals the hidden neighbors of a satisfied number - see `Action::Chord`
+    pub fn chord(player: usize, point: BoardPoint) -> Self {
+        Play {
+            player,
+            action: Action::Chord,
+            point,
+        }
+    }
+}
+
 pub struct Minesweeper {
-    available: HashSet<BoardPoint>,
+    available: AvailableCells,
     players: Vec<Player>,
     board: Board<(Cell, CellState)>,
     log: Option<Vec<(Play, PlayOutcome)>>,
     superclick: bool,
+    flag_limit: Option<usize>,
+    question_marks: bool,
+    lenient_chord: bool,
+    rng: GameRng,
+    timed_out: bool,
+    win_condition: WinCondition,
+    /// Accepted plays so far - orders when players reached their scores
+    moves: usize,
 }
 
 impl Minesweeper {
-    fn handle_flag(&mut self, player: usize, cell_point: &BoardPoint) -> Result<PlayOutcome> {
+    fn handle_flag(
+        &mut self,
+        player: usize,
+        cell_point: &BoardPoint,
+    ) -> Result<PlayOutcome, PlayError> {
         let (_, cell_state) = &self.board[cell_point];
         if cell_state.revealed {
-            bail!("Tried to play already revealed cell")
+            return Err(PlayError::AlreadyRevealed);
         }
+        // cycles Flag -> Question -> Empty when question marks are enabled
         let player_cell = if self.players[player].flags.contains(cell_point) {
             self.players[player].flags.remove(cell_point);
+            if self.question_marks {
+                self.players[player].questions.insert(*cell_point);
+                PlayerCell::Hidden(HiddenCell::Question)
+            } else {
+                PlayerCell::Hidden(HiddenCell::Empty)
+            }
+        } else if self.players[player].questions.contains(cell_point) {
+            self.players[player].questions.remove(cell_point);
             PlayerCell::Hidden(HiddenCell::Empty)
         } else {
+            if let Some(limit) = self.flag_limit {
+                if self.players[player].flags.len() >= limit {
+                    return Err(PlayError::FlagLimit);
+                }
+            }
             self.players[player].flags.insert(*cell_point);
             PlayerCell::Hidden(HiddenCell::Flag)
         };
-        Ok(PlayOutcome::Flag((*cell_point, player_cell)))
+        Result::Ok(PlayOutcome::Flag((*cell_point, player_cell)))
     }
 
-    fn handle_click(&mut self, player: usize, cell_point: &BoardPoint) -> Result<PlayOutcome> {
+    fn handle_click(
+        &mut self,
+        player: usize,
+        cell_point: &BoardPoint,
+    ) -> Result<PlayOutcome, PlayError> {
         let (_, cell_state) = &self.board[cell_point];
         if cell_state.revealed {
-            bail!("Tried to play already revealed cell")
+            return Err(PlayError::AlreadyRevealed);
         }
         if self.players[player].flags.contains(cell_point) {
-            bail!("Tried to play flagged cell")
+            return Err(PlayError::FlaggedCell);
         }
         let mut update_revealed = None::<Vec<BoardPoint>>;
         if !(self.players[player].played) && self.has_no_revealed_nearby(cell_point) {
@@ -192,7 +459,7 @@ impl Minesweeper {
             Cell::Mine => {
                 self.reveal(player, cell_point);
                 self.players[player].dead = true;
-                Ok(PlayOutcome::Failure((
+                Result::Ok(PlayOutcome::Failure((
                     *cell_point,
                     RevealedCell {
                         player,
@@ -201,7 +468,7 @@ impl Minesweeper {
                 )))
             }
             Cell::Empty(x) if x == &0 => {
-                let mut revealed_points = self.reveal_neighbors(player, cell_point)?;
+                let mut revealed_points = self.reveal_neighbors(player, cell_point);
                 if let Some(updated_points) = update_revealed {
                     revealed_points.extend(updated_points);
                 }
@@ -218,10 +485,10 @@ impl Minesweeper {
                     })
                     .collect::<Vec<_>>();
                 self.players[player].score += revealed_points.len();
-                if self.available.is_empty() {
-                    Ok(PlayOutcome::Victory(revealed_points))
+                if self.is_won() {
+                    Result::Ok(PlayOutcome::Victory(revealed_points))
                 } else {
-                    Ok(PlayOutcome::Success(revealed_points))
+                    Result::Ok(PlayOutcome::Success(revealed_points))
                 }
             }
             Cell::Empty(_) => {
@@ -234,40 +501,57 @@ impl Minesweeper {
                         contents: self.board[cell_point].0,
                     },
                 )];
-                if self.available.is_empty() {
-                    Ok(PlayOutcome::Victory(revealed_point))
+                if self.is_won() {
+                    Result::Ok(PlayOutcome::Victory(revealed_point))
                 } else {
-                    Ok(PlayOutcome::Success(revealed_point))
+                    Result::Ok(PlayOutcome::Success(revealed_point))
                 }
             }
         }
     }
 
-    fn handle_double_click(
-        &mut self,
-        player: usize,
-        cell_point: &BoardPoint,
-    ) -> Result<PlayOutcome> {
+    /// Rejects a double-click on a cell which isn't a revealed number with exactly that many
+    /// flagged neighbors - revealed mines count as flagged
+    fn check_double_click(&self, player: usize, cell_point: &BoardPoint) -> Result<(), PlayError> {
         let (cell, cell_state) = &self.board[cell_point];
         if !cell_state.revealed {
-            bail!("Tried to double-click cell that isn't revealed")
+            return Err(PlayError::NotRevealed);
         }
-        let neighbors = self.board.neighbors(cell_point);
-        let flagged_neighbors = neighbors
-            .iter()
-            .copied()
+        let flagged_neighbors = self
+            .board
+            .neighbors(cell_point)
+            .into_iter()
             .filter(|c| self.players[player].flags.contains(c) || self.is_revealed_mine(*c));
         if let Cell::Empty(x) = cell {
             if *x == 0 {
-                bail!("Tried to double-click zero space")
+                return Err(PlayError::NoNumber);
             }
-            let flagged_count = flagged_neighbors.count();
-            if *x as usize != flagged_count {
-                bail!("Tried to double-click with wrong number of flagged neighbors.  Expected {x} got {flagged_count}")
+            // at most 8 neighbors
+            let flagged_count = flagged_neighbors.count() as u8;
+            if *x != flagged_count {
+                return Err(PlayError::DoubleClickMismatch {
+                    expected: *x,
+                    got: flagged_count,
+                });
             }
         } else {
-            bail!("Tried to double-click mine")
+            return Err(PlayError::NoNumber);
+        }
+        Result::Ok(())
+    }
+
+    fn handle_double_click(
+        &mut self,
+        player: usize,
+        cell_point: &BoardPoint,
+    ) -> Result<PlayOutcome, PlayError> {
+        match self.check_double_click(player, cell_point) {
+            Err(PlayError::DoubleClickMismatch { .. }) if self.lenient_chord => {
+                return Result::Ok(PlayOutcome::Success(Vec::new()));
+            }
+            res => res?,
         }
+        let neighbors = self.board.neighbors(cell_point);
         let unflagged_neighbors = neighbors
             .iter()
             .copied()
@@ -281,7 +565,7 @@ impl Minesweeper {
         if let Some(c) = has_mine {
             self.reveal(player, &c);
             self.players[player].dead = true;
-            return Ok(PlayOutcome::Failure((
+            return Result::Ok(PlayOutcome::Failure((
                 c,
                 RevealedCell {
                     player,
@@ -301,7 +585,25 @@ impl Minesweeper {
                 acc.combine(res)
             },
         );
-        Ok(combined_outcome)
+        Result::Ok(combined_outcome)
+    }
+
+    fn is_won(&self) -> bool {
+        match self.win_condition {
+            WinCondition::RevealAllSafe => self.available.is_empty(),
+            WinCondition::FlagAllMines => self
+                .players
+                .iter()
+                .any(|p| !p.dead && self.flags_all_mines(p)),
+        }
+    }
+
+    /// Whether `player` has flagged every hidden mine and nothing else
+    fn flags_all_mines(&self, player: &Player) -> bool {
+        let hidden_mines = self
+            .board
+            .count_where(|(cell, state)| cell.is_mine() && !state.revealed);
+        player.flags.len() == hidden_mines && player.flags.iter().all(|f| self.board[f].0.is_mine())
     }
 
     fn is_revealed_mine(&self, cell_point: BoardPoint) -> bool {
@@ -318,36 +620,44 @@ impl Minesweeper {
             self.available.remove(cell_point);
             self.players.iter_mut().for_each(|p| {
                 p.flags.remove(cell_point);
+                p.questions.remove(cell_point);
             });
             true
         }
     }
 
-    fn reveal_neighbors(
-        &mut self,
-        player: usize,
-        cell_point: &BoardPoint,
-    ) -> Result<Vec<BoardPoint>> {
+    /// Reveals the opening around the zero at `cell_point` - points come back breadth-first, so
+    /// the clicked cell is first and each point is no further from it than the ones after
+    fn reveal_neighbors(&mut self, player: usize, cell_point: &BoardPoint) -> Vec<BoardPoint> {
+        // worklist of revealed zeros rather than recursion - a cascade can cover a whole board
         self.reveal(player, cell_point);
-        let final_vec = vec![*cell_point];
-        let neighbors = self.board.neighbors(cell_point);
-        neighbors.iter().try_fold(final_vec, |mut acc, c| {
-            let item = self.board[c];
-            if item.1.revealed {
-                return Ok(acc);
-            }
-            if let Cell::Empty(x) = item.0 {
-                if x == 0 {
-                    let mut recur_acc = self.reveal_neighbors(player, c)?;
-                    acc.append(&mut recur_acc)
-                } else if self.reveal(player, c) {
-                    acc.push(*c)
+        let mut revealed = vec![*cell_point];
+        let mut to_expand = VecDeque::from([*cell_point]);
+        while let Some(point) = to_expand.pop_front() {
+            for c in self.board.neighbors(&point) {
+                let item = self.board[c];
+                if item.1.revealed {
+                    continue;
+                }
+                match item.0 {
+                    Cell::Empty(0) => {
+                        self.reveal(player, &c);
+                        revealed.push(c);
+                        to_expand.push_back(c);
+                    }
+                    Cell::Empty(_) => {
+                        if self.reveal(player, &c) {
+                            revealed.push(c);
+                        }
+                    }
+                    Cell::Mine => {
+                        // a zero can't border a mine - skip rather than panic on a bad board
+                        log::error!("Revealed zero at {point:?} next to mine at {c:?}");
+                    }
                 }
-            } else {
-                bail!("Called reveal neighbors when there is a mine nearby")
             }
-            Ok(acc)
-        })
+        }
+        revealed
     }
 
     fn has_no_revealed_nearby(&self, cell_point: &BoardPoint) -> bool {
@@ -375,54 +685,64 @@ impl Minesweeper {
     }
 
     fn unplant(&mut self, cell_point: &BoardPoint, rem_neighbors: bool) -> Vec<BoardPoint> {
-        let mut updated_revealed = HashSet::new();
-        let mut to_replant = if rem_neighbors { Some(0) } else { None };
-
         let neighbors = self.board.neighbors(cell_point);
-
-        let was_mine = self.board[cell_point].0.is_mine();
-        if was_mine {
-            let neighboring_mines = neighbors
-                .iter()
-                .copied()
-                .fold(0, |acc, c| acc + bool_to_u8(self.board[c].0.is_mine()));
-
-            // set value to number of neighboring mine
-            self.board[cell_point].0 = self.board[cell_point].0.unplant(neighboring_mines).unwrap();
-
-            if rem_neighbors {
-                if let Some(unplanted_mines) = &mut to_replant {
-                    *unplanted_mines += 1;
-                }
-            }
+        let mut to_unplant = vec![*cell_point];
+        if rem_neighbors {
+            to_unplant.extend(
+                neighbors
+                    .iter()
+                    .copied()
+                    .filter(|c| self.board[c].0.is_mine()),
+            );
         }
 
-        neighbors.iter().for_each(|i| {
-            let new = if was_mine {
-                if self.board[i].1.revealed {
-                    updated_revealed.insert(*i);
-                }
-                self.board[i].0.decrement()
-            } else {
-                self.board[i].0
-            };
-            if rem_neighbors && matches!(new, Cell::Mine) {
-                updated_revealed.extend(self.unplant(i, false));
-                if let Some(unplanted_mines) = &mut to_replant {
-                    *unplanted_mines += 1;
-                }
-            } else {
-                self.board[i].0 = new;
+        let mut updated_revealed = HashSet::new();
+        let mut unplanted_mines = 0;
+        for point in to_unplant {
+            if self.unplant_single(&point, &mut updated_revealed) {
+                unplanted_mines += 1;
             }
-        });
+        }
 
-        if let Some(unplanted_mines) = to_replant {
+        if rem_neighbors {
+            // replant everything at once so availability is only shuffled a single time
             self.replant(unplanted_mines, cell_point, neighbors);
         }
 
         updated_revealed.into_iter().collect()
     }
 
+    /// Turns a mine into an empty cell, fixing up neighbor counts - returns whether it was a mine
+    fn unplant_single(
+        &mut self,
+        cell_point: &BoardPoint,
+        updated_revealed: &mut HashSet<BoardPoint>,
+    ) -> bool {
+        if !self.board[cell_point].0.is_mine() {
+            return false;
+        }
+        let neighbors = self.board.neighbors(cell_point);
+        let neighboring_mines = neighbors
+            .iter()
+            .copied()
+            .fold(0, |acc, c| acc + bool_to_u8(self.board[c].0.is_mine()));
+
+        // set value to number of neighboring mine
+        self.board[cell_point].0 = self.board[cell_point].0.unplant(neighboring_mines).unwrap();
+
+        neighbors.iter().for_each(|i| {
+            if self.board[i].1.revealed {
+                updated_revealed.insert(*i);
+            }
+            if self.board[i].0 == Cell::Empty(0) {
+                // neighbor of a mine can't be zero - decrement saturates, so don't panic
+                log::error!("Unplanted mine at {cell_point:?} with zero neighbor at {i:?}");
+            }
+            self.board[i].0 = self.board[i].0.decrement();
+        });
+        true
+    }
+
     fn replant(
         &mut self,
         unplanted_mines: usize,
@@ -438,17 +758,16 @@ impl Minesweeper {
                 .iter()
                 .any(|c| self.board[c].1.revealed)
         };
+        // available iterates in board order, so seeded games replant the same way
         let mut take_available: Vec<BoardPoint> = self
             .available
             .iter()
-            .filter(|&bp| bp != first_cell && !neighbors.contains(bp) && !has_revealed_neighbor(bp))
-            .copied()
+            .filter(|bp| bp != first_cell && !neighbors.contains(bp) && !has_revealed_neighbor(bp))
             .collect::<Vec<_>>();
-        let mut rng = thread_rng();
-        take_available.shuffle(&mut rng);
+        take_available.shuffle(&mut self.rng);
         if unplanted_mines > take_available.len() {
             let mut unplanted_points = neighbors;
-            unplanted_points.shuffle(&mut rng);
+            unplanted_points.shuffle(&mut self.rng);
             take_available.extend(unplanted_points);
         }
         take_available.iter().take(unplanted_mines).for_each(|x| {
@@ -463,30 +782,54 @@ impl Minesweeper {
             players: self.players,
             board: self.board.viewer_board(true),
             log: self.log,
+            rules: PlayRules {
+                flag_limit: self.flag_limit.is_some(),
+                question_marks: self.question_marks,
+                lenient_chord: self.lenient_chord,
+                win_condition: self.win_condition,
+            },
         }
     }
 
-    pub fn play(&mut self, play: Play) -> Result<PlayOutcome> {
+    /// Applies `play` - rejected plays leave the game unchanged.  `PlayError` converts into
+    /// `anyhow::Error` for callers which don't care why.
+    pub fn play(&mut self, play: Play) -> Result<PlayOutcome, PlayError> {
         if self.is_over() {
-            bail!("Game is over")
+            return Err(PlayError::GameOver);
         }
-        if self.players[play.player].dead {
-            bail!("Tried to play as dead player")
+        let Some(player) = self.players.get(play.player) else {
+            return Err(PlayError::UnknownPlayer(play.player));
+        };
+        if player.dead {
+            return Err(PlayError::DeadPlayer);
         }
         if !self.board.is_in_bounds(play.point) {
-            bail!("Tried to play point outside of playzone")
+            return Err(PlayError::OutOfBounds);
         }
+        let score_before = player.score;
         let play_res = match play.action {
             Action::Reveal => self.handle_click(play.player, &play.point),
             Action::RevealAdjacent => self.handle_double_click(play.player, &play.point),
             Action::Flag => self.handle_flag(play.player, &play.point),
         };
-        if self.available.is_empty() {
+        if self.is_won() {
             // game is over
             self.players[play.player].victory_click = true;
         }
+        // lenient chords which did nothing don't count as a move, and aren't recorded
+        let did_nothing =
+            matches!(&play_res, Result::Ok(PlayOutcome::Success(cells)) if cells.is_empty());
+        if play_res.is_ok() && !did_nothing {
+            self.moves += 1;
+            if self.players[play.player].score != score_before {
+                self.players[play.player].scored_at = self.moves;
+            }
+        }
         // record play if applicable
         let _ = play_res.as_ref().map(|outcome| {
+            if did_nothing {
+                return;
+            }
             if let Some(history) = &mut self.log {
                 history.push((play, outcome.clone()));
             }
@@ -494,6 +837,15 @@ impl Minesweeper {
         play_res
     }
 
+    /// Whether `player` double-clicking `point` would be accepted - for highlighting numbers
+    /// whose flags are all placed
+    pub fn is_chordable(&self, player: usize, point: BoardPoint) -> bool {
+        !self.is_over()
+            && self.players.get(player).is_some_and(|p| !p.dead)
+            && self.board.is_in_bounds(point)
+            && self.check_double_click(player, &point).is_ok()
+    }
+
     pub fn player_score(&self, player: usize) -> Result<usize> {
         if player > self.players.len() - 1 {
             bail!("Player {player} doesn't exist")
@@ -532,6 +884,18 @@ impl Minesweeper {
         }
     }
 
+    /// The single player with the top score - ties go to whoever reached the score first, then
+    /// to the lowest player number
+    pub fn first_to_top_score(&self) -> Option<usize> {
+        let top_score = self.current_top_score()?;
+        self.players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.score == top_score)
+            .min_by_key(|&(player, p)| (p.scored_at, player))
+            .map(|(player, _)| player)
+    }
+
     pub fn player_victory_click(&self, player: usize) -> Result<bool> {
         if player > self.players.len() - 1 {
             bail!("Player {player} doesn't exist")
@@ -539,138 +903,792 @@ impl Minesweeper {
         Ok(self.players[player].victory_click)
     }
 
-    pub fn is_over(&self) -> bool {
-        self.available.is_empty() || self.players.iter().all(|x| x.dead)
-    }
-
-    pub fn viewer_board(&self) -> Board<PlayerCell> {
-        self.board.viewer_board(false)
-    }
-
-    pub fn player_board(&self, player: usize) -> Board<PlayerCell> {
-        let mut return_board = self.viewer_board();
-        for f in self.players[player].flags.iter() {
-            if let PlayerCell::Hidden(_) = return_board[f] {
-                return_board[f] = return_board[f].add_flag()
-            }
-        }
-        return_board
-    }
-}
-
-pub struct CompletedMinesweeper {
-    players: Vec<Player>,
-    board: Board<PlayerCell>,
-    log: Option<Vec<(Play, PlayOutcome)>>,
-}
-
-impl CompletedMinesweeper {
-    pub fn from_log(
-        board: Board<PlayerCell>,
-        log: Vec<(Play, PlayOutcome)>,
-        players: Vec<ClientPlayer>,
-    ) -> CompletedMinesweeper {
-        let players_len = players.len();
-        let mut players =
-            players
-                .into_iter()
-                .fold(vec![Player::default(); players_len], |mut acc, p| {
-                    acc[p.player_id].score = p.score;
-                    acc[p.player_id].dead = p.dead;
-                    acc[p.player_id].victory_click = p.victory_click;
-                    acc
-                });
-        log.iter()
-            .filter(|item| matches!(item.1, PlayOutcome::Flag(_)))
-            .for_each(|item| {
-                if let PlayOutcome::Flag((point, _)) = item.1 {
-                    players[item.0.player].flags.insert(point);
-                }
-            });
-        CompletedMinesweeper {
-            players,
-            board,
-            log: Some(log),
-        }
+    /// Score, death, victory click and top score for `player` read together, or `None` if the
+    /// player doesn't exist
+    pub fn player_snapshot(&self, player: usize) -> Option<PlayerSnapshot> {
+        let top_score = self.current_top_score();
+        self.players
+            .get(player)
+            .map(|p| PlayerSnapshot::new(p, top_score))
     }
 
-    pub fn recover_log(self) -> Option<Vec<(Play, PlayOutcome)>> {
-        self.log
+    /// `player_snapshot` for every player, in player order
+    pub fn all_player_snapshots(&self) -> Vec<PlayerSnapshot> {
+        let top_score = self.current_top_score();
+        self.players
+            .iter()
+            .map(|p| PlayerSnapshot::new(p, top_score))
+            .collect()
     }
-}
 
-impl CompletedMinesweeper {
-    pub fn player_score(&self, player: usize) -> Result<usize> {
+    /// Spoiler: how many of the player's flags are on mines
+    pub fn flagged_correctly(&self, player: usize) -> Result<usize> {
         if player > self.players.len() - 1 {
             bail!("Player {player} doesn't exist")
         }
-        Ok(self.players[player].score)
+        Ok(self.players[player]
+            .flags
+            .iter()
+            .filter(|f| self.board[*f].0.is_mine())
+            .count())
     }
 
-    pub fn player_dead(&self, player: usize) -> Result<bool> {
+    /// Spoiler: the player's flags which aren't on mines, in row-major order
+    pub fn misflags(&self, player: usize) -> Result<Vec<BoardPoint>> {
         if player > self.players.len() - 1 {
             bail!("Player {player} doesn't exist")
         }
-        Ok(self.players[player].dead)
+        let mut misflags = self.players[player]
+            .flags
+            .iter()
+            .filter(|f| !self.board[*f].0.is_mine())
+            .copied()
+            .collect::<Vec<_>>();
+        misflags.sort();
+        Ok(misflags)
     }
 
-    pub fn player_victory_click(&self, player: usize) -> Result<bool> {
-        if player > self.players.len() - 1 {
-            bail!("Player {player} doesn't exist")
-        }
-        Ok(self.players[player].victory_click)
+    pub fn is_over(&self) -> bool {
+        self.end_reason().is_some()
     }
 
-    pub fn top_score(&self) -> Option<usize> {
-        if self.players.len() < 2 {
-            None
-        } else {
-            let top_score = self.players.iter().fold(0, |acc, p| max(p.score, acc));
-            match top_score {
-                0 => None,
-                score => Some(score),
-            }
+    /// Whether the game is running, won or lost - for callers which don't care why it ended
+    pub fn status(&self) -> GameStatus {
+        match self.end_reason() {
+            None => GameStatus::InProgress,
+            Some(GameEndReason::BoardCleared) => GameStatus::Won,
+            Some(GameEndReason::AllDead | GameEndReason::TimedOut) => GameStatus::Lost,
         }
     }
 
-    pub fn player_top_score(&self, player: usize) -> Result<bool> {
-        if player > self.players.len() - 1 {
-            bail!("Player {player} doesn't exist")
-        }
-        if self.players.len() < 2 {
-            Ok(false) // no top_score in single player
+    /// Why the game ended, or `None` if it's still in progress
+    pub fn end_reason(&self) -> Option<GameEndReason> {
+        if self.is_won() {
+            Some(GameEndReason::BoardCleared)
+        } else if self.players.iter().all(|x| x.dead) {
+            Some(GameEndReason::AllDead)
+        } else if self.timed_out {
+            Some(GameEndReason::TimedOut)
         } else {
-            let top_score = self.players.iter().fold(0, |acc, p| max(p.score, acc));
-            Ok(self.players[player].score == top_score && top_score != 0)
+            None
         }
     }
 
-    pub fn viewer_board_final(&self) -> Board<PlayerCell> {
-        self.board.clone()
+    /// Ends the game early - e.g. when the server gives up waiting on players
+    pub fn time_out(&mut self) {
+        self.timed_out = true;
     }
 
-    pub fn player_board_final(&self, player: usize) -> Board<PlayerCell> {
-        let mut return_board = self.viewer_board_final();
-        for f in self.players[player].flags.iter() {
-            if let PlayerCell::Hidden(_) = return_board[f] {
-                return_board[f] = return_board[f].add_flag()
-            }
+    /// Takes `player` out of the game as if they'd hit a mine - a resign button, or a player
+    /// gone too long.  Resigned players count as dead, so the game ends once nobody is left.
+    pub fn resign(&mut self, player: usize) -> Result<(), PlayError> {
+        if self.is_over() {
+            return Err(PlayError::GameOver);
+        }
+        let Some(player) = self.players.get_mut(player) else {
+            return Err(PlayError::UnknownPlayer(player));
+        };
+        if player.dead {
+            return Err(PlayError::DeadPlayer);
+        }
+        player.dead = true;
+        Result::Ok(())
+    }
+
+    /// Restarts the game on the same mine layout - all cells hidden and players reset.  Players
+    /// count as having played, so first clicks don't move mines.
+    pub fn reset(&mut self) {
+        self.board
+            .iter_mut()
+            .for_each(|(_, cs)| *cs = CellState::default());
+        self.available = AvailableCells::from_points(
+            self.board.rows(),
+            self.board.cols(),
+            (0..self.board.size())
+                .map(|i| self.board.point_from_index(i))
+                .filter(|p| !self.board[p].0.is_mine()),
+        );
+        self.players.iter_mut().for_each(|p| {
+            *p = Player {
+                played: true,
+                ..Player::default()
+            }
+        });
+        if let Some(log) = &mut self.log {
+            log.clear();
+        }
+        self.timed_out = false;
+        self.moves = 0;
+    }
+
+    /// Full board as text, one line per row of [`PlayerCell`]s as they display - `*` mine
+    /// (hidden or revealed), `-` hidden safe cell, a digit for a revealed number and a space for
+    /// a revealed zero
+    pub fn to_ascii(&self) -> String {
+        self.board
+            .viewer_board(true)
+            .rows_iter()
+            .map(|row| row.iter().map(PlayerCell::to_string).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Single player game from [`Minesweeper::to_ascii`] text - spaces are revealed zeros, so
+    /// lines are taken as they are.  Revealed cells are credited to player 0 and every mine
+    /// starts hidden.  Boards without any mines are allowed.
+    pub fn from_ascii(ascii: &str) -> Result<Minesweeper> {
+        let rows = ascii
+            .lines()
+            .map(|row| row.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let cols = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != cols) {
+            bail!("All rows must be the same length")
+        }
+        let cells = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row, chars)| {
+                chars
+                    .iter()
+                    .enumerate()
+                    .map(move |(col, c)| (BoardPoint { row, col }, *c))
+            })
+            .collect::<Vec<_>>();
+        if let Some((point, c)) = cells
+            .iter()
+            .find(|(_, c)| !matches!(c, '*' | '-' | ' ' | '1'..='8'))
+        {
+            bail!("Unknown cell {c:?} at {point}")
+        }
+        let mines = cells
+            .iter()
+            .filter(|(_, c)| *c == '*')
+            .map(|(point, _)| *point)
+            .collect::<Vec<_>>();
+        let mut builder = MinesweeperBuilder::new(MinesweeperOpts {
+            rows: rows.len(),
+            cols,
+            num_mines: 1,
+        })?;
+        if mines.is_empty() {
+            // `with_mines_at` wants at least one mine, but an all-safe board is still a board
+            builder.opts.num_mines = 0;
+            builder.mines_at = Some(mines);
+        } else {
+            builder = builder.with_mines_at(mines)?;
+        }
+        let mut game = builder.init();
+        for (point, c) in cells {
+            let number = match c {
+                ' ' => 0,
+                '1'..='8' => c as u8 - b'0',
+                _ => continue,
+            };
+            if game.board[point].0 != Cell::Empty(number) {
+                bail!("Number at {point} doesn't match neighboring mines")
+            }
+            game.reveal(0, &point);
+            game.players[0].score += 1;
+        }
+        game.players[0].played = game.board.iter().any(|(_, state)| state.revealed);
+        Ok(game)
+    }
+
+    /// Sets up a game continuing from `board` (e.g. a practice position).
+    ///
+    /// Mines are placed so every revealed number is satisfied - cells forced by the numbers are
+    /// always mines, the rest of `num_mines` go randomly into hidden cells away from the numbers.
+    /// Flags and question marks on `board` are kept as player 0's.  Errors if the search for a
+    /// layout runs past its budget.
+    pub fn from_player_board(board: Board<PlayerCell>, num_mines: usize) -> Result<Minesweeper> {
+        MinesweeperOpts {
+            rows: board.rows(),
+            cols: board.cols(),
+            num_mines,
+        }
+        .validate()?;
+        let mut rng: GameRng = Box::new(StdRng::from_entropy());
+        let mines = layout_mines(&board, num_mines, &mut rng)?;
+        let points = (0..board.size())
+            .map(|i| board.point_from_index(i))
+            .collect::<Vec<_>>();
+
+        let players = board
+            .iter()
+            .filter_map(|c| match c {
+                PlayerCell::Revealed(rc) => Some(rc.player + 1),
+                PlayerCell::Hidden(_) => None,
+            })
+            .max()
+            .unwrap_or(1);
+        let mut game = Minesweeper {
+            available: AvailableCells::from_points(
+                board.rows(),
+                board.cols(),
+                points.iter().copied(),
+            ),
+            players: vec![
+                Player {
+                    played: true,
+                    ..Player::default()
+                };
+                players
+            ],
+            board: Board::new(
+                board.rows(),
+                board.cols(),
+                (Cell::default(), CellState::default()),
+            )
+            .with_neighbor_cache(),
+            log: None,
+            superclick: false,
+            flag_limit: None,
+            question_marks: false,
+            lenient_chord: false,
+            rng,
+            timed_out: false,
+            win_condition: WinCondition::default(),
+            moves: 0,
+        };
+        mines.iter().for_each(|p| game.plant(p));
+        for p in points.iter() {
+            match board[p] {
+                PlayerCell::Revealed(rc) => {
+                    if rc.contents != game.board[p].0 {
+                        bail!("Revealed cell at {p:?} doesn't match the mine layout")
+                    }
+                    game.board[p].1 = CellState {
+                        revealed: true,
+                        player: Some(rc.player),
+                    };
+                    game.available.remove(p);
+                }
+                PlayerCell::Hidden(HiddenCell::Flag | HiddenCell::FlagMine) => {
+                    game.players[0].flags.insert(*p);
+                }
+                PlayerCell::Hidden(HiddenCell::Question) => {
+                    game.players[0].questions.insert(*p);
+                }
+                PlayerCell::Hidden(HiddenCell::Empty | HiddenCell::Mine) => {}
+            }
+        }
+        Ok(game)
+    }
+
+    /// Replays a recorded log onto a fresh game without re-running the plays.
+    ///
+    /// The log is trusted rather than checked - use [`CompletedMinesweeper::verify_log`] for
+    /// that.  Revealed contents in the log are taken as the mine layout: if this game's mines
+    /// disagree with them, mines are laid out again to match (hidden cells the log says nothing
+    /// about are filled using the game's rng).  Outcomes are then applied as recorded, so
+    /// first-click mine moves aren't repeated and nothing is re-randomized.
+    pub fn apply_log(&mut self, log: &[(Play, PlayOutcome)]) -> Result<()> {
+        if self.board.iter().any(|(_, cs)| cs.revealed) {
+            bail!("Can only apply a log to a game which hasn't been played")
+        }
+        let mut recorded = Board::new(
+            self.board.rows(),
+            self.board.cols(),
+            PlayerCell::Hidden(HiddenCell::Empty),
+        );
+        for (i, (play, outcome)) in log.iter().enumerate() {
+            if play.player >= self.players.len() {
+                bail!("Move {i} is by player {} who doesn't exist", play.player)
+            }
+            let revealed = match outcome {
+                PlayOutcome::Success(cells) | PlayOutcome::Victory(cells) => cells.clone(),
+                PlayOutcome::Failure(cell) => vec![*cell],
+                PlayOutcome::Flag(_) => Vec::new(),
+            };
+            if !self.board.is_in_bounds(play.point)
+                || revealed.iter().any(|(p, _)| !self.board.is_in_bounds(*p))
+            {
+                bail!("Move {i} is outside of playzone")
+            }
+            // later contents win - a first click can renumber cells revealed earlier
+            revealed
+                .into_iter()
+                .for_each(|(p, rc)| recorded[p] = PlayerCell::Revealed(rc));
+        }
+
+        let points = (0..self.board.size())
+            .map(|i| self.board.point_from_index(i))
+            .collect::<Vec<_>>();
+        let layout_matches = points.iter().all(|p| match recorded[p] {
+            PlayerCell::Revealed(rc) => rc.contents == self.board[p].0,
+            PlayerCell::Hidden(_) => true,
+        });
+        if !layout_matches {
+            let mines = layout_mines(&recorded, self.mine_count(), &mut self.rng)?;
+            self.board
+                .iter_mut()
+                .for_each(|(cell, _)| *cell = Cell::default());
+            self.available = AvailableCells::from_points(
+                self.board.rows(),
+                self.board.cols(),
+                points.iter().copied(),
+            );
+            mines.iter().for_each(|p| self.plant(p));
+        }
+
+        for (play, outcome) in log {
+            self.moves += 1;
+            match outcome {
+                PlayOutcome::Success(cells) | PlayOutcome::Victory(cells) => {
+                    cells.iter().for_each(|(p, _)| {
+                        self.reveal(play.player, p);
+                    });
+                    let player = &mut self.players[play.player];
+                    player.score += cells.len();
+                    if !cells.is_empty() {
+                        player.scored_at = self.moves;
+                    }
+                }
+                PlayOutcome::Failure((p, _)) => {
+                    self.reveal(play.player, p);
+                    self.players[play.player].dead = true;
+                }
+                PlayOutcome::Flag((p, player_cell)) => {
+                    let player = &mut self.players[play.player];
+                    player.flags.remove(p);
+                    player.questions.remove(p);
+                    match player_cell {
+                        PlayerCell::Hidden(HiddenCell::Flag) => {
+                            player.flags.insert(*p);
+                        }
+                        PlayerCell::Hidden(HiddenCell::Question) => {
+                            player.questions.insert(*p);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            if play.action != Action::Flag {
+                self.players[play.player].played = true;
+            }
+            if self.is_won() {
+                self.players[play.player].victory_click = true;
+            }
+            if let Some(history) = &mut self.log {
+                history.push((*play, outcome.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Log of every play so far, if the game keeps one - e.g. to save a game which is still
+    /// running
+    pub fn get_log(&self) -> Option<Vec<(Play, PlayOutcome)>> {
+        self.log.clone()
+    }
+
+    /// Plays every move of `log` again, checking each outcome against the recorded one.  A
+    /// game built with the same options and seed as the logged one ends up exactly where the
+    /// log left off - flags and all - e.g. to resume a game after a server restart.
+    pub fn replay_log(&mut self, log: &[(Play, PlayOutcome)]) -> Result<()> {
+        for (i, (play, outcome)) in log.iter().enumerate() {
+            if play.player >= self.players.len() {
+                bail!(
+                    "Log diverges at move {i}: player {} doesn't exist",
+                    play.player
+                )
+            }
+            let replayed = self
+                .play(*play)
+                .map_err(|e| anyhow!("Log diverges at move {i}: {e}"))?;
+            if &replayed != outcome {
+                bail!("Log diverges at move {i}: expected {replayed:?}, found {outcome:?}")
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of zero cells in the largest opening (connected region of zero cells)
+    pub fn largest_opening_size(&self) -> usize {
+        self.board.largest_opening_size()
+    }
+
+    /// Minimum number of clicks needed to clear the board (3BV)
+    pub fn three_bv(&self) -> usize {
+        // one click clears each whole opening, including its numbered border
+        let openings = self.board.openings();
+        let mut counted = Board::new(self.board.rows(), self.board.cols(), false);
+        openings.iter().flatten().for_each(|p| counted[p] = true);
+        openings.len()
+            + self
+                .board
+                .iter()
+                .zip(counted.iter())
+                .filter(|((cell, _), &counted)| !counted && !cell.is_mine())
+                .count()
+    }
+
+    /// Board safe to send to anyone - never includes flags or question marks
+    pub fn viewer_board(&self) -> Board<PlayerCell> {
+        self.board.viewer_board(false)
+    }
+
+    /// Debug check for boards sent while a game is running - only `complete` boards may show
+    /// mines which haven't been revealed
+    pub fn assert_no_mine_leak(board: &Board<PlayerCell>) {
+        debug_assert!(
+            !board.iter().any(|cell| matches!(
+                cell,
+                PlayerCell::Hidden(HiddenCell::Mine | HiddenCell::FlagMine)
+            )),
+            "In-progress board shows hidden mines"
+        );
+    }
+
+    /// Board for a single player - includes only that player's flags and question marks
+    pub fn player_board(&self, player: usize) -> Board<PlayerCell> {
+        let mut return_board = self.viewer_board();
+        for f in self.players[player].flags.iter() {
+            if let PlayerCell::Hidden(_) = return_board[f] {
+                return_board[f] = return_board[f].add_flag()
+            }
+        }
+        for q in self.players[player].questions.iter() {
+            return_board[q] = return_board[q].add_question()
+        }
+        return_board
+    }
+
+    /// Spoiler: every mine on the board, in row-major order.  Meant for tooling and tests -
+    /// never send this to players.
+    pub fn mine_positions(&self) -> Vec<BoardPoint> {
+        (0..self.board.size())
+            .map(|i| self.board.point_from_index(i))
+            .filter(|p| self.board[p].0.is_mine())
+            .collect()
+    }
+
+    /// Spoiler: number of mines currently on the board (first-click unplanting can move them)
+    pub fn mine_count(&self) -> usize {
+        self.board.count_where(|(cell, _)| cell.is_mine())
+    }
+
+    /// Options for starting another game like this one
+    pub fn settings(&self) -> MinesweeperOpts {
+        MinesweeperOpts {
+            rows: self.board.rows(),
+            cols: self.board.cols(),
+            num_mines: self.mine_count(),
+        }
+    }
+}
+
+pub struct CompletedMinesweeper {
+    players: Vec<Player>,
+    board: Board<PlayerCell>,
+    log: Option<Vec<(Play, PlayOutcome)>>,
+    rules: PlayRules,
+}
+
+impl CompletedMinesweeper {
+    pub fn from_log(
+        board: Board<PlayerCell>,
+        log: Vec<(Play, PlayOutcome)>,
+        players: Vec<ClientPlayer>,
+    ) -> CompletedMinesweeper {
+        let players_len = players.len();
+        let mut players =
+            players
+                .into_iter()
+                .fold(vec![Player::default(); players_len], |mut acc, p| {
+                    acc[p.player_id].score = p.score;
+                    acc[p.player_id].dead = p.dead;
+                    acc[p.player_id].victory_click = p.victory_click;
+                    acc
+                });
+        log.iter()
+            .filter(|item| matches!(item.1, PlayOutcome::Flag(_)))
+            .for_each(|item| {
+                if let PlayOutcome::Flag((point, pc)) = item.1 {
+                    let player = &mut players[item.0.player];
+                    player.flags.remove(&point);
+                    player.questions.remove(&point);
+                    match pc {
+                        PlayerCell::Hidden(HiddenCell::Flag) => {
+                            player.flags.insert(point);
+                        }
+                        PlayerCell::Hidden(HiddenCell::Question) => {
+                            player.questions.insert(point);
+                        }
+                        _ => {}
+                    }
+                }
+            });
+        CompletedMinesweeper {
+            players,
+            board,
+            log: Some(log),
+            rules: PlayRules::default(),
+        }
+    }
+
+    /// Rules a game rebuilt with `from_log` was played under, for [`Self::verify_log`] -
+    /// defaults otherwise
+    pub fn with_rules(mut self, rules: PlayRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn recover_log(self) -> Option<Vec<(Play, PlayOutcome)>> {
+        self.log
+    }
+
+    /// Whole game as one URL-safe string, so a replay can be shared without the server - see
+    /// [`Self::from_share_code`].  Only games which kept a log can be shared.
+    pub fn to_share_code(&self) -> Result<String> {
+        let Some(log) = &self.log else {
+            bail!("Game has no log")
+        };
+        let code = ShareCode {
+            rows: self.board.rows(),
+            cols: self.board.cols(),
+            mines: (0..self.board.size())
+                .filter(|&i| is_mine_cell(&self.board[self.board.point_from_index(i)]))
+                .collect(),
+            players: self
+                .players
+                .iter()
+                .map(|p| (p.score, p.dead, p.victory_click))
+                .collect(),
+            log: log.clone(),
+            rules: self.rules,
+        };
+        Ok(URL_SAFE_NO_PAD.encode(serde_json::to_vec(&code)?))
+    }
+
+    /// Rebuilds a game from [`Self::to_share_code`] - the final board comes from the mines
+    /// plus every cell the log revealed
+    pub fn from_share_code(code: &str) -> Result<CompletedMinesweeper> {
+        let code = serde_json::from_slice::<ShareCode>(&URL_SAFE_NO_PAD.decode(code.trim())?)?;
+        if code.rows == 0 || code.cols == 0 {
+            bail!("Share code has an empty board")
+        }
+        if code.rows > SHARE_CODE_MAX_SIDE || code.cols > SHARE_CODE_MAX_SIDE {
+            bail!("Share code board is larger than {SHARE_CODE_MAX_SIDE}x{SHARE_CODE_MAX_SIDE}")
+        }
+        let mut board = Board::new(code.rows, code.cols, PlayerCell::default());
+        for i in code.mines {
+            if i >= board.size() {
+                bail!("Share code has a mine outside of the board")
+            }
+            let point = board.point_from_index(i);
+            board[point] = PlayerCell::Hidden(HiddenCell::Mine);
+        }
+        for (i, (play, outcome)) in code.log.iter().enumerate() {
+            if play.player >= code.players.len() {
+                bail!("Move {i} is by player {} who doesn't exist", play.player)
+            }
+            let revealed = match outcome {
+                PlayOutcome::Success(cells) | PlayOutcome::Victory(cells) => cells.as_slice(),
+                PlayOutcome::Failure(cell) => std::slice::from_ref(cell),
+                PlayOutcome::Flag(_) => &[],
+            };
+            if !board.is_in_bounds(play.point)
+                || revealed.iter().any(|(p, _)| !board.is_in_bounds(*p))
+            {
+                bail!("Move {i} is outside of playzone")
+            }
+            revealed
+                .iter()
+                .for_each(|(p, rc)| board[p] = PlayerCell::Revealed(*rc));
+        }
+        let players = code
+            .players
+            .into_iter()
+            .enumerate()
+            .map(|(player_id, (score, dead, victory_click))| ClientPlayer {
+                player_id,
+                score,
+                dead,
+                victory_click,
+                ..ClientPlayer::default()
+            })
+            .collect();
+        Ok(CompletedMinesweeper::from_log(board, code.log, players).with_rules(code.rules))
+    }
+}
+
+/// Largest board [`CompletedMinesweeper::from_share_code`] will rebuild
+const SHARE_CODE_MAX_SIDE: usize = 100;
+
+/// What a share code holds - the final board is left out since the mines and the log are
+/// enough to rebuild it
+#[derive(Serialize, Deserialize)]
+struct ShareCode {
+    rows: usize,
+    cols: usize,
+    mines: Vec<usize>,
+    /// `(score, dead, victory_click)` for each player
+    players: Vec<(usize, bool, bool)>,
+    log: Vec<(Play, PlayOutcome)>,
+    rules: PlayRules,
+}
+
+impl CompletedMinesweeper {
+    pub fn player_score(&self, player: usize) -> Result<usize> {
+        if player > self.players.len() - 1 {
+            bail!("Player {player} doesn't exist")
+        }
+        Ok(self.players[player].score)
+    }
+
+    pub fn player_dead(&self, player: usize) -> Result<bool> {
+        if player > self.players.len() - 1 {
+            bail!("Player {player} doesn't exist")
+        }
+        Ok(self.players[player].dead)
+    }
+
+    pub fn player_victory_click(&self, player: usize) -> Result<bool> {
+        if player > self.players.len() - 1 {
+            bail!("Player {player} doesn't exist")
+        }
+        Ok(self.players[player].victory_click)
+    }
+
+    pub fn top_score(&self) -> Option<usize> {
+        if self.players.len() < 2 {
+            None
+        } else {
+            let top_score = self.players.iter().fold(0, |acc, p| max(p.score, acc));
+            match top_score {
+                0 => None,
+                score => Some(score),
+            }
+        }
+    }
+
+    pub fn player_top_score(&self, player: usize) -> Result<bool> {
+        if player > self.players.len() - 1 {
+            bail!("Player {player} doesn't exist")
+        }
+        if self.players.len() < 2 {
+            Ok(false) // no top_score in single player
+        } else {
+            let top_score = self.players.iter().fold(0, |acc, p| max(p.score, acc));
+            Ok(self.players[player].score == top_score && top_score != 0)
+        }
+    }
+
+    pub fn viewer_board_final(&self) -> Board<PlayerCell> {
+        self.board.clone()
+    }
+
+    pub fn player_board_final(&self, player: usize) -> Board<PlayerCell> {
+        let mut return_board = self.viewer_board_final();
+        for f in self.players[player].flags.iter() {
+            if let PlayerCell::Hidden(_) = return_board[f] {
+                return_board[f] = return_board[f].add_flag()
+            }
+        }
+        for q in self.players[player].questions.iter() {
+            return_board[q] = return_board[q].add_question()
         }
         return_board
     }
 
+    /// Board as `player` saw it after the first `move_index` moves in the log - everything
+    /// revealed so far, plus the player's own flags and question marks at that point
+    pub fn player_board_at(&self, player: usize, move_index: usize) -> Result<Board<PlayerCell>> {
+        if player > self.players.len() - 1 {
+            bail!("Player {player} doesn't exist")
+        }
+        let Some(log) = &self.log else {
+            bail!("Game has no log")
+        };
+        if move_index > log.len() {
+            bail!("Move {move_index} is past the end of the log")
+        }
+        let mut board = self.board_start();
+        for (play, outcome) in &log[..move_index] {
+            match outcome {
+                PlayOutcome::Success(cells) | PlayOutcome::Victory(cells) => cells
+                    .iter()
+                    .for_each(|(point, rc)| board[point] = PlayerCell::Revealed(*rc)),
+                PlayOutcome::Failure((point, rc)) => board[point] = PlayerCell::Revealed(*rc),
+                PlayOutcome::Flag((point, pc)) if play.player == player => {
+                    let unmarked = board[point].remove_flag().remove_question();
+                    board[point] = match pc {
+                        PlayerCell::Hidden(HiddenCell::Flag) => unmarked.add_flag(),
+                        PlayerCell::Hidden(HiddenCell::Question) => unmarked.add_question(),
+                        _ => unmarked,
+                    };
+                }
+                PlayOutcome::Flag(_) => {}
+            }
+        }
+        Ok(board)
+    }
+
     fn board_start(&self) -> Board<PlayerCell> {
         let mut board = self.board.clone();
         board
             .iter_mut()
-            .for_each(|pc| *pc = pc.into_hidden().remove_flag());
+            .for_each(|pc| *pc = pc.into_hidden().remove_flag().remove_question());
         board
     }
 
+    /// Every mine on the final board, nearest to `from` first, so a loss can be animated
+    /// outward from the explosion.  Mines the same distance away are in row-major order.
+    pub fn mines_by_distance(&self, from: BoardPoint) -> Vec<BoardPoint> {
+        let distance = |p: &BoardPoint| {
+            let rows = p.row.abs_diff(from.row);
+            let cols = p.col.abs_diff(from.col);
+            rows * rows + cols * cols
+        };
+        let mut mines = (0..self.board.size())
+            .map(|i| self.board.point_from_index(i))
+            .filter(|p| is_mine_cell(&self.board[p]))
+            .collect::<Vec<_>>();
+        mines.sort_by_key(distance);
+        mines
+    }
+
+    /// Options for starting another game like this one
+    pub fn settings(&self) -> MinesweeperOpts {
+        MinesweeperOpts {
+            rows: self.board.rows(),
+            cols: self.board.cols(),
+            num_mines: self.board.count_where(is_mine_cell),
+        }
+    }
+
     pub fn get_log(&self) -> Option<Vec<(Play, PlayOutcome)>> {
         Some(self.log.as_ref()?.clone())
     }
 
+    /// Replays the log from `board_start` under the game's rules and checks every recorded
+    /// outcome against the engine
+    pub fn verify_log(&self) -> Result<()> {
+        let Some(log) = &self.log else {
+            bail!("Game has no log")
+        };
+        let board = self.board_start();
+        let num_mines = board
+            .iter()
+            .filter(|pc| matches!(pc, PlayerCell::Hidden(HiddenCell::Mine)))
+            .count();
+        let mut game = Minesweeper::from_player_board(board, num_mines)?;
+        game.players = vec![
+            Player {
+                played: true,
+                ..Player::default()
+            };
+            self.players.len()
+        ];
+        game.flag_limit = self.rules.flag_limit.then_some(num_mines);
+        game.question_marks = self.rules.question_marks;
+        game.lenient_chord = self.rules.lenient_chord;
+        game.win_condition = self.rules.win_condition;
+        game.replay_log(log)
+    }
+
     pub fn replay(&self, player: Option<usize>) -> Option<MinesweeperReplay> {
         let player_log = self
             .log
@@ -690,6 +1708,14 @@ impl CompletedMinesweeper {
     }
 }
 
+fn is_mine_cell(pc: &PlayerCell) -> bool {
+    match pc {
+        PlayerCell::Hidden(HiddenCell::Mine | HiddenCell::FlagMine) => true,
+        PlayerCell::Revealed(rc) => rc.is_mine(),
+        PlayerCell::Hidden(_) => false,
+    }
+}
+
 fn bool_to_u8(b: bool) -> u8 {
     match b {
         true => 1,
@@ -703,20 +1729,48 @@ pub struct Player {
     dead: bool,
     victory_click: bool,
     score: usize,
+    /// Move on which `score` was reached
+    scored_at: usize,
     flags: HashSet<BoardPoint>,
+    questions: HashSet<BoardPoint>,
+}
+
+/// A player's standing at one moment - see [`Minesweeper::player_snapshot`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PlayerSnapshot {
+    pub score: usize,
+    pub dead: bool,
+    pub victory_click: bool,
+    pub top_score: bool,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+impl PlayerSnapshot {
+    fn new(player: &Player, top_score: Option<usize>) -> Self {
+        Self {
+            score: player.score,
+            dead: player.dead,
+            victory_click: player.victory_click,
+            top_score: top_score == Some(player.score),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Action {
     #[serde(rename = "f", alias = "Flag")]
     Flag,
     #[serde(rename = "r", alias = "Reveal")]
     Reveal,
-    #[serde(rename = "ra", alias = "RevealAdjacent")]
+    #[serde(rename = "ra", alias = "RevealAdjacent", alias = "Chord")]
     RevealAdjacent,
 }
 
 impl Action {
+    /// The usual name for `RevealAdjacent` - clicking a number with all its mines flagged
+    /// reveals the rest of its neighbors.  Works in patterns too.
+    #[allow(non_upper_case_globals)]
+    pub const Chord: Action = Action::RevealAdjacent;
+
     pub fn to_str(&self) -> &'static str {
         match self {
             Action::Flag => "Flag",
@@ -726,10 +1780,208 @@ impl Action {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub enum PlayOutcome {
-    #[serde(rename = "s", alias = "Success")]
-    Success(Vec<(BoardPoint, RevealedCell)>),
+/// Mines satisfying every revealed number on `board` - cells forced by the numbers are always
+/// mines, the rest of `num_mines` go into hidden cells away from the numbers using `rng`
+fn layout_mines(
+    board: &Board<PlayerCell>,
+    num_mines: usize,
+    rng: &mut GameRng,
+) -> Result<HashSet<BoardPoint>> {
+    let points = (0..board.size())
+        .map(|i| board.point_from_index(i))
+        .collect::<Vec<_>>();
+    let is_hidden = |p: &BoardPoint| matches!(board[p], PlayerCell::Hidden(_));
+    let mut mines = points
+        .iter()
+        .copied()
+        .filter(|p| match board[p] {
+            PlayerCell::Revealed(rc) => rc.is_mine(),
+            PlayerCell::Hidden(hc) => matches!(hc, HiddenCell::Mine | HiddenCell::FlagMine),
+        })
+        .collect::<HashSet<_>>();
+
+    // each revealed number constrains how many of its unknown neighbors are mines
+    let constraints = points
+        .iter()
+        .filter_map(|p| match board[p] {
+            PlayerCell::Revealed(rc) => rc.number().map(|n| (p, n as usize)),
+            PlayerCell::Hidden(_) => None,
+        })
+        .map(|(p, n)| {
+            let neighbors = board.neighbors(p);
+            let known = neighbors.iter().filter(|np| mines.contains(np)).count();
+            let unknown = neighbors
+                .into_iter()
+                .filter(|np| is_hidden(np) && !mines.contains(np))
+                .collect::<Vec<_>>();
+            if known > n || known + unknown.len() < n {
+                bail!("Revealed number at {p:?} can't be satisfied")
+            }
+            Ok((n - known, unknown))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let frontier = constraints
+        .iter()
+        .flat_map(|(_, unknown)| unknown.iter().copied())
+        .collect::<HashSet<_>>();
+    let mut frontier = frontier.into_iter().collect::<Vec<_>>();
+    frontier.sort();
+    let mut interior = points
+        .iter()
+        .copied()
+        .filter(|p| is_hidden(p) && !mines.contains(p) && !frontier.contains(p))
+        .collect::<Vec<_>>();
+    let Some(remaining) = num_mines.checked_sub(mines.len()) else {
+        bail!("Board has more mines than num_mines")
+    };
+
+    // constraints refer to frontier cells by index, so the search never looks points up
+    let frontier_index = frontier
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (*p, i))
+        .collect::<HashMap<_, _>>();
+    let constraints = constraints
+        .into_iter()
+        .map(|(needed, cells)| {
+            let cells = cells.iter().map(|c| frontier_index[c]).collect::<Vec<_>>();
+            (needed, cells)
+        })
+        .collect::<Vec<_>>();
+    let mut touching = vec![Vec::new(); frontier.len()];
+    for (k, (_, cells)) in constraints.iter().enumerate() {
+        cells.iter().for_each(|&i| touching[i].push(k));
+    }
+    let search = FrontierSearch {
+        constraints,
+        touching,
+        remaining,
+        interior: interior.len(),
+    };
+
+    let mut assignment = Vec::with_capacity(frontier.len());
+    let mut budget = LAYOUT_SEARCH_BUDGET;
+    if !search.assign(&mut assignment, 0, &mut budget)? {
+        bail!("No mine layout matches the revealed numbers")
+    }
+    mines.extend(
+        frontier
+            .iter()
+            .zip(assignment.iter())
+            .filter(|(_, &is_mine)| is_mine)
+            .map(|(p, _)| *p),
+    );
+    interior.shuffle(rng);
+    let remaining = num_mines - mines.len();
+    mines.extend(interior.into_iter().take(remaining));
+    Ok(mines)
+}
+
+/// Most frontier cells [`Minesweeper::from_player_board`] tries before giving up on a board
+const LAYOUT_SEARCH_BUDGET: usize = 1_000_000;
+
+/// Backtracking search for mines in the frontier (the hidden cells next to revealed numbers)
+struct FrontierSearch {
+    /// `(mines_needed, frontier indices)` for each revealed number
+    constraints: Vec<(usize, Vec<usize>)>,
+    /// Indices into `constraints` touching each frontier cell
+    touching: Vec<Vec<usize>>,
+    /// Mines left to place anywhere
+    remaining: usize,
+    /// Hidden cells away from the numbers, which can take whatever the frontier doesn't
+    interior: usize,
+}
+
+impl FrontierSearch {
+    /// Extends `assignment` (mine or not, in frontier order) to satisfy every constraint,
+    /// returning false if it can't be and an error once `budget` runs out
+    fn assign(
+        &self,
+        assignment: &mut Vec<bool>,
+        placed: usize,
+        budget: &mut usize,
+    ) -> Result<bool> {
+        if assignment.len() == self.touching.len() {
+            return Ok(self.remaining - placed <= self.interior);
+        }
+        for is_mine in [true, false] {
+            if *budget == 0 {
+                bail!("Gave up searching for a mine layout")
+            }
+            *budget -= 1;
+            let placed = placed + usize::from(is_mine);
+            if placed > self.remaining {
+                continue;
+            }
+            let index = assignment.len();
+            assignment.push(is_mine);
+            if self.consistent(index, assignment) && self.assign(assignment, placed, budget)? {
+                return Ok(true);
+            }
+            assignment.pop();
+        }
+        Ok(false)
+    }
+
+    /// Whether the constraints touching the cell at `index` can still be met
+    fn consistent(&self, index: usize, assignment: &[bool]) -> bool {
+        self.touching[index].iter().all(|&k| {
+            let (needed, cells) = &self.constraints[k];
+            let (mines, unassigned) =
+                cells
+                    .iter()
+                    .fold((0, 0), |(mines, unassigned), &i| match assignment.get(i) {
+                        Some(&is_mine) => (mines + usize::from(is_mine), unassigned),
+                        None => (mines, unassigned + 1),
+                    });
+            mines <= *needed && mines + unassigned >= *needed
+        })
+    }
+}
+
+/// Options changing how plays are handled, kept with a completed game so its log can be
+/// replayed the same way
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayRules {
+    pub flag_limit: bool,
+    pub question_marks: bool,
+    pub lenient_chord: bool,
+    pub win_condition: WinCondition,
+}
+
+/// What a player has to do to win
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WinCondition {
+    /// Reveal every safe cell - classic minesweeper
+    #[default]
+    RevealAllSafe,
+    /// Flag exactly the hidden mines - revealing every safe cell isn't enough on its own.  The
+    /// winning flag is still a `PlayOutcome::Flag`, so check `is_over` after flagging.
+    FlagAllMines,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEndReason {
+    /// The game was won - the player who made the winning play has `victory_click`
+    BoardCleared,
+    AllDead,
+    TimedOut,
+}
+
+/// Outcome of a game so far - see [`Minesweeper::end_reason`] for why it ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameStatus {
+    InProgress,
+    /// The board was cleared - in multiplayer, even if some players died along the way
+    Won,
+    /// Every player died, or the game timed out
+    Lost,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PlayOutcome {
+    #[serde(rename = "s", alias = "Success")]
+    Success(Vec<(BoardPoint, RevealedCell)>),
     #[serde(rename = "x", alias = "Failure")]
     Failure((BoardPoint, RevealedCell)),
     #[serde(rename = "v", alias = "Victory")]
@@ -788,6 +2040,8 @@ impl PlayOutcome {
 
 #[cfg(test)]
 mod test {
+    use rand::rngs::mock::StepRng;
+
     use crate::board::{Board, BoardPoint};
     use crate::cell::{Cell, CellState};
 
@@ -807,14 +2061,29 @@ mod test {
     const POINT_3_3: BoardPoint = BoardPoint { row: 3, col: 3 };
 
     fn empty_game(player_num: usize) -> Minesweeper {
-        let board = Board::new(9, 9, (Cell::default(), CellState::default()));
-        let available = (0..81).map(|x| board.point_from_index(x)).collect();
+        empty_game_sized(9, 9, player_num)
+    }
+
+    fn empty_game_sized(rows: usize, cols: usize, player_num: usize) -> Minesweeper {
+        let board = Board::new(rows, cols, (Cell::default(), CellState::default()));
+        let available = AvailableCells::from_points(
+            rows,
+            cols,
+            (0..rows * cols).map(|x| board.point_from_index(x)),
+        );
         Minesweeper {
             available,
             players: vec![Player::default(); player_num],
             board,
             log: None,
             superclick: true,
+            flag_limit: None,
+            question_marks: false,
+            lenient_chord: false,
+            rng: Box::new(StdRng::seed_from_u64(0)),
+            timed_out: false,
+            win_condition: WinCondition::default(),
+            moves: 0,
         }
     }
 
@@ -835,15 +2104,6 @@ mod test {
         game
     }
 
-    fn num_mines(game: &Minesweeper, number: usize) {
-        let num_mines = game
-            .board
-            .iter()
-            .filter(|x| matches!(x.0, Cell::Mine))
-            .count();
-        assert_eq!(num_mines, number);
-    }
-
     fn assert_point_cell(game: &Minesweeper, point: BoardPoint, _cell: Cell) {
         let board_cell = game.board[point].0;
         assert!(matches!(board_cell, _cell));
@@ -860,6 +2120,31 @@ mod test {
         assert_eq!(board_cell_state.player, player);
     }
 
+    fn point(row: usize, col: usize) -> BoardPoint {
+        BoardPoint { row, col }
+    }
+
+    fn completed_with_log() -> CompletedMinesweeper {
+        let mut game = set_up_game();
+        game.log = Some(Vec::new());
+        [
+            (0, Action::Reveal, POINT_3_3),
+            (0, Action::Flag, POINT_0_0),
+            (1, Action::Reveal, POINT_0_1),
+            (0, Action::Flag, POINT_0_0),
+        ]
+        .into_iter()
+        .for_each(|(player, action, point)| {
+            game.play(Play {
+                player,
+                action,
+                point,
+            })
+            .unwrap();
+        });
+        game.complete()
+    }
+
     #[test]
     fn create_and_init_game() {
         let game: Minesweeper = MinesweeperBuilder::new(MinesweeperOpts {
@@ -869,7 +2154,7 @@ mod test {
         })
         .unwrap()
         .init();
-        num_mines(&game, 10);
+        assert_eq!(game.mine_count(), 10);
     }
 
     #[test]
@@ -878,7 +2163,7 @@ mod test {
 
         game.plant(&POINT_0_0);
 
-        num_mines(&game, 1);
+        assert_eq!(game.mine_count(), 1);
         assert_eq!(game.available.len(), 9 * 9 - 1);
         assert_point_cell(&game, POINT_0_0, Cell::Mine);
         assert_point_cell(&game, POINT_0_1, Cell::Empty(1));
@@ -888,7 +2173,7 @@ mod test {
 
         game.plant(&POINT_1_1);
 
-        num_mines(&game, 2);
+        assert_eq!(game.mine_count(), 2);
         assert_eq!(game.available.len(), 9 * 9 - 2);
         assert_point_cell(&game, POINT_0_0, Cell::Mine);
         assert_point_cell(&game, POINT_0_1, Cell::Empty(2));
@@ -898,7 +2183,7 @@ mod test {
 
         game.plant(&POINT_1_2);
 
-        num_mines(&game, 3);
+        assert_eq!(game.mine_count(), 3);
         assert_eq!(game.available.len(), 9 * 9 - 3);
         assert_point_cell(&game, POINT_0_0, Cell::Mine);
         assert_point_cell(&game, POINT_0_1, Cell::Empty(3));
@@ -913,7 +2198,7 @@ mod test {
 
         game.unplant(&POINT_0_0, true);
 
-        num_mines(&game, 4);
+        assert_eq!(game.mine_count(), 4);
         assert_eq!(game.available.len(), 9 * 9 - 6);
         assert_point_cell(&game, POINT_0_0, Cell::Empty(0));
         assert_point_cell(&game, POINT_1_1, Cell::Empty(1));
@@ -925,13 +2210,62 @@ mod test {
 
         game.unplant(&POINT_0_2, true);
 
-        num_mines(&game, 4);
+        assert_eq!(game.mine_count(), 4);
         assert_eq!(game.available.len(), 9 * 9 - 6);
         assert_point_cell(&game, POINT_0_2, Cell::Empty(0));
         assert_point_cell(&game, POINT_0_0, Cell::Mine);
         assert_point_cell(&game, POINT_1_1, Cell::Empty(1));
     }
 
+    #[test]
+    fn unplant_dense_board() {
+        let opts = MinesweeperOpts {
+            rows: 9,
+            cols: 9,
+            num_mines: 70,
+        };
+        let center = BoardPoint { row: 4, col: 4 };
+        for seed in 0..50 {
+            let mut game = MinesweeperBuilder::new(opts)
+                .unwrap()
+                .with_seed(seed)
+                .init();
+
+            game.unplant(&center, true);
+
+            assert_eq!(game.mine_count(), 70);
+            assert!(!game.board[center].0.is_mine());
+            (0..game.board.size())
+                .map(|i| game.board.point_from_index(i))
+                .for_each(|p| {
+                    let mines = game
+                        .board
+                        .neighbors(&p)
+                        .iter()
+                        .filter(|c| game.board[*c].0.is_mine())
+                        .count() as u8;
+                    match game.board[p].0 {
+                        Cell::Empty(x) => assert_eq!(x, mines, "seed {seed} point {p:?}"),
+                        Cell::Mine => assert!(!game.available.contains(&p)),
+                    }
+                });
+        }
+    }
+
+    #[test]
+    fn unplant_bad_neighbor_count() {
+        let mut game = empty_game(1);
+        // mine with neighbor counts out of sync
+        game.board[POINT_0_0].0 = Cell::Mine;
+
+        game.unplant(&POINT_0_0, false);
+
+        assert_eq!(game.board[POINT_0_0].0, Cell::Empty(0));
+        assert_eq!(game.board[POINT_0_1].0, Cell::Empty(0));
+        assert_eq!(game.board[POINT_1_0].0, Cell::Empty(0));
+        assert_eq!(game.board[POINT_1_1].0, Cell::Empty(0));
+    }
+
     #[test]
     fn first_play_mine_works() {
         let mut game = set_up_game();
@@ -945,7 +2279,7 @@ mod test {
             .unwrap();
         assert_eq!(res.len(), 4);
 
-        num_mines(&game, 4);
+        assert_eq!(game.mine_count(), 4);
         assert_eq!(game.available.len(), 9 * 9 - 8);
         assert_point_cell(&game, POINT_0_0, Cell::Empty(0));
         point_cell_state(&game, POINT_0_0, true, Some(0));
@@ -966,7 +2300,7 @@ mod test {
         });
         assert_eq!(res.unwrap().len(), 9 * 9 - 8);
 
-        num_mines(&game, 4);
+        assert_eq!(game.mine_count(), 4);
         assert_eq!(game.available.len(), 4); // not mine and not revealed
         assert_point_cell(&game, BoardPoint { row: 8, col: 8 }, Cell::Empty(0));
         point_cell_state(&game, BoardPoint { row: 8, col: 8 }, true, Some(0));
@@ -1052,6 +2386,32 @@ mod test {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn reveal_cascade_is_breadth_first() {
+        let mut game = empty_game_sized(5, 5, 1);
+        game.plant(&POINT_0_0);
+        let click = BoardPoint { row: 4, col: 4 };
+
+        let res = game
+            .play(Play {
+                player: 0,
+                action: Action::Reveal,
+                point: click,
+            })
+            .unwrap();
+        let PlayOutcome::Victory(cells) = res else {
+            panic!("expected victory, got {res:?}");
+        };
+
+        assert_eq!(cells.len(), 5 * 5 - 1);
+        assert_eq!(cells[0].0, click);
+        let distances = cells
+            .iter()
+            .map(|(p, _)| p.row.abs_diff(click.row).max(p.col.abs_diff(click.col)))
+            .collect::<Vec<_>>();
+        assert!(distances.windows(2).all(|w| w[0] <= w[1]), "{distances:?}");
+    }
+
     #[test]
     fn unplant_updated_works() {
         let mut game = set_up_game();
@@ -1114,6 +2474,85 @@ mod test {
         assert!(matches!(res, PlayOutcome::Failure(_)));
     }
 
+    #[test]
+    fn flag_limit_works() {
+        let mut game = set_up_game();
+        game.flag_limit = Some(4);
+
+        let flag = |game: &mut Minesweeper, player: usize, point: BoardPoint| {
+            game.play(Play {
+                player,
+                action: Action::Flag,
+                point,
+            })
+        };
+
+        for col in 0..4 {
+            let res = flag(&mut game, 0, BoardPoint { row: 5, col }).unwrap();
+            assert!(matches!(res, PlayOutcome::Flag(_)));
+        }
+        // one flag more than the number of mines
+        let res = flag(&mut game, 0, BoardPoint { row: 5, col: 4 });
+        assert!(res.is_err());
+
+        // unflagging and re-flagging existing flags is allowed
+        let res = flag(&mut game, 0, BoardPoint { row: 5, col: 0 }).unwrap();
+        assert!(matches!(
+            res,
+            PlayOutcome::Flag((_, PlayerCell::Hidden(HiddenCell::Empty)))
+        ));
+        let res = flag(&mut game, 0, BoardPoint { row: 5, col: 4 }).unwrap();
+        assert!(matches!(
+            res,
+            PlayOutcome::Flag((_, PlayerCell::Hidden(HiddenCell::Flag)))
+        ));
+
+        // other players have their own flags
+        let res = flag(&mut game, 1, BoardPoint { row: 5, col: 0 });
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn question_marks_cycle() {
+        let mut game = set_up_game();
+        game.question_marks = true;
+
+        let cell_point = BoardPoint { row: 5, col: 5 };
+        let mut mark = || {
+            let res = game
+                .play(Play {
+                    player: 0,
+                    action: Action::Flag,
+                    point: cell_point,
+                })
+                .unwrap();
+            let PlayOutcome::Flag((point, pc)) = res else {
+                panic!("Flag action should have flag outcome")
+            };
+            assert_eq!(point, cell_point);
+            pc
+        };
+
+        assert_eq!(mark(), PlayerCell::Hidden(HiddenCell::Flag));
+        assert_eq!(mark(), PlayerCell::Hidden(HiddenCell::Question));
+        assert_eq!(mark(), PlayerCell::Hidden(HiddenCell::Empty));
+        assert_eq!(mark(), PlayerCell::Hidden(HiddenCell::Flag));
+        assert_eq!(mark(), PlayerCell::Hidden(HiddenCell::Question));
+        assert_eq!(
+            game.player_board(0)[cell_point],
+            PlayerCell::Hidden(HiddenCell::Question)
+        );
+
+        // question marks don't block reveal
+        let res = game.play(Play {
+            player: 0,
+            action: Action::Reveal,
+            point: cell_point,
+        });
+        assert!(res.is_ok());
+        assert!(game.players[0].questions.is_empty());
+    }
+
     #[test]
     fn double_click_works() {
         let mut game = set_up_game_no_superclick();
@@ -1160,6 +2599,56 @@ mod test {
         assert!(res.len() == 9 * 9 - 9); // 5 is worst case scenario for replant
     }
 
+    #[test]
+    fn chordable_matches_double_click() {
+        let mut game = set_up_game_no_superclick();
+        let center = BoardPoint { row: 2, col: 2 };
+        assert!(!game.is_chordable(0, center));
+        game.play(Play::reveal(0, center)).unwrap();
+        game.play(Play::flag(0, POINT_1_1)).unwrap();
+        game.play(Play::flag(0, POINT_1_2)).unwrap();
+        assert!(!game.is_chordable(0, center));
+        assert!(game.play(Play::chord(0, center)).is_err());
+
+        game.play(Play::flag(0, POINT_2_1)).unwrap();
+        assert!(game.is_chordable(0, center));
+        // flags are per player
+        assert!(!game.is_chordable(1, center));
+        assert!(!game.is_chordable(2, center));
+        assert!(!game.is_chordable(0, BoardPoint { row: 9, col: 9 }));
+        assert!(game.play(Play::chord(0, center)).is_ok());
+    }
+
+    #[test]
+    fn lenient_chord_is_noop() {
+        let center = BoardPoint { row: 2, col: 2 };
+        let mut strict = set_up_game_no_superclick();
+        let mut lenient = set_up_game_no_superclick();
+        lenient.lenient_chord = true;
+        lenient.log = Some(Vec::new());
+        for game in [&mut strict, &mut lenient] {
+            game.play(Play::reveal(0, center)).unwrap();
+            game.play(Play::flag(0, POINT_1_1)).unwrap();
+        }
+
+        assert_eq!(
+            strict.play(Play::chord(0, center)).unwrap_err(),
+            PlayError::DoubleClickMismatch {
+                expected: 3,
+                got: 1
+            }
+        );
+        let res = lenient.play(Play::chord(0, center)).unwrap();
+        assert_eq!(res, PlayOutcome::Success(Vec::new()));
+        assert_eq!(lenient.log.as_ref().unwrap().len(), 2);
+        assert_eq!(lenient.moves, 2);
+        // other chord errors still apply
+        assert_eq!(
+            lenient.play(Play::chord(0, POINT_0_1)).unwrap_err(),
+            PlayError::NotRevealed
+        );
+    }
+
     #[test]
     fn bad_double_click_fails() {
         let mut game = set_up_game_no_superclick();
@@ -1209,7 +2698,7 @@ mod test {
             })
             .unwrap();
 
-        num_mines(&game, 4);
+        assert_eq!(game.mine_count(), 4);
         assert!(matches!(res, PlayOutcome::Success(_)));
         assert_eq!(game.players[0].score, 4);
     }
@@ -1337,7 +2826,7 @@ mod test {
                 point: POINT_0_0,
             })
             .unwrap();
-        num_mines(&game, 4);
+        assert_eq!(game.mine_count(), 4);
         assert_ne!(game.board[POINT_1_1].0, Cell::Mine);
         assert_ne!(game.board[POINT_0_1].0, Cell::Mine);
         assert_ne!(game.board[POINT_1_0].0, Cell::Mine);
@@ -1352,7 +2841,7 @@ mod test {
                 point: POINT_2_2,
             })
             .unwrap();
-        num_mines(&game, 4);
+        assert_eq!(game.mine_count(), 4);
         assert_ne!(game.board[POINT_1_1].0, Cell::Mine);
         assert_ne!(game.board[POINT_2_1].0, Cell::Mine);
         assert_ne!(game.board[POINT_1_2].0, Cell::Mine);
@@ -1361,4 +2850,1233 @@ mod test {
         assert_ne!(game.board[POINT_2_3].0, Cell::Mine);
         assert_eq!(game.board[POINT_0_0].0, Cell::Mine);
     }
+
+    #[test]
+    fn opts_from_density() {
+        let opts = MinesweeperOpts::from_density(9, 9, 0.2).unwrap();
+        assert_eq!(opts.rows, 9);
+        assert_eq!(opts.cols, 9);
+        assert_eq!(opts.num_mines, 16);
+
+        assert!(MinesweeperOpts::from_density(9, 9, 0.0).is_err());
+        assert!(MinesweeperOpts::from_density(9, 9, 1.0).is_err());
+        assert!(MinesweeperOpts::from_density(9, 9, -0.5).is_err());
+        assert!(MinesweeperOpts::from_density(9, 9, f64::NAN).is_err());
+        // rounds to zero mines
+        assert!(MinesweeperOpts::from_density(2, 2, 0.1).is_err());
+    }
+
+    #[test]
+    fn three_bv_works() {
+        let mut game = empty_game(1);
+        game.plant(&POINT_0_0);
+        // a single opening clears everything
+        assert_eq!(game.three_bv(), 1);
+
+        let mut game = empty_game(1);
+        game.plant(&POINT_0_1);
+        // 0,0 isn't on the border of the opening so needs its own click
+        assert_eq!(game.three_bv(), 2);
+    }
+
+    #[test]
+    fn opts_validate_errors() {
+        let opts = |rows, cols, num_mines| MinesweeperOpts {
+            rows,
+            cols,
+            num_mines,
+        };
+        assert_eq!(opts(9, 9, 10).validate(), Result::Ok(()));
+        assert_eq!(opts(0, 9, 10).validate(), Err(OptsError::ZeroDimension));
+        assert_eq!(opts(9, 0, 10).validate(), Err(OptsError::ZeroDimension));
+        assert_eq!(opts(9, 9, 0).validate(), Err(OptsError::ZeroMines));
+        assert_eq!(
+            opts(9, 9, 81).validate(),
+            Err(OptsError::TooManyMines {
+                num_mines: 81,
+                total: 81
+            })
+        );
+
+        let err = MinesweeperBuilder::new(opts(9, 9, 0)).err().unwrap();
+        assert_eq!(err.downcast_ref(), Some(&OptsError::ZeroMines));
+    }
+
+    #[test]
+    fn largest_opening_size_works() {
+        let mut game = empty_game(1);
+        game.plant(&POINT_0_0);
+        assert_eq!(game.largest_opening_size(), 81 - 4);
+
+        // wall of mines splits the board
+        let mut game = empty_game(1);
+        (0..9).for_each(|row| game.plant(&BoardPoint { row, col: 3 }));
+        assert_eq!(game.largest_opening_size(), 9 * 4);
+    }
+
+    #[test]
+    fn min_opening_rerolls() {
+        let opts = MinesweeperOpts {
+            rows: 16,
+            cols: 30,
+            num_mines: 99,
+        };
+        (0..10).for_each(|seed| {
+            let game = MinesweeperBuilder::new(opts)
+                .unwrap()
+                .with_seed(seed)
+                .with_min_opening(20)
+                .init();
+            assert!(game.largest_opening_size() >= 20);
+        })
+    }
+
+    #[test]
+    fn end_reason_board_cleared() {
+        let mut game = empty_game(2);
+        game.plant(&POINT_0_0);
+        assert_eq!(game.end_reason(), None);
+        game.play(Play {
+            player: 1,
+            action: Action::Reveal,
+            point: POINT_3_3,
+        })
+        .unwrap();
+        assert_eq!(game.end_reason(), Some(GameEndReason::BoardCleared));
+        assert!(game.is_over());
+        assert!(game.player_victory_click(1).unwrap());
+        assert!(!game.player_victory_click(0).unwrap());
+    }
+
+    #[test]
+    fn end_reason_all_dead() {
+        let mut game = set_up_game();
+        game.players.iter_mut().for_each(|p| p.played = true);
+        game.play(Play {
+            player: 0,
+            action: Action::Reveal,
+            point: POINT_0_0,
+        })
+        .unwrap();
+        // one player still alive
+        assert_eq!(game.end_reason(), None);
+        game.play(Play {
+            player: 1,
+            action: Action::Reveal,
+            point: POINT_1_1,
+        })
+        .unwrap();
+        assert_eq!(game.end_reason(), Some(GameEndReason::AllDead));
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn end_reason_timed_out() {
+        let mut game = set_up_game();
+        assert_eq!(game.end_reason(), None);
+        game.time_out();
+        assert_eq!(game.end_reason(), Some(GameEndReason::TimedOut));
+        assert!(game
+            .play(Play {
+                player: 0,
+                action: Action::Reveal,
+                point: POINT_3_3,
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn flags_are_private() {
+        let mut game = set_up_game();
+        let outcome = game
+            .play(Play {
+                player: 0,
+                action: Action::Flag,
+                point: POINT_3_3,
+            })
+            .unwrap();
+        assert!(matches!(outcome, PlayOutcome::Flag(_)));
+
+        assert_eq!(
+            game.player_board(0)[POINT_3_3],
+            PlayerCell::Hidden(HiddenCell::Flag)
+        );
+        assert_eq!(
+            game.player_board(1)[POINT_3_3],
+            PlayerCell::Hidden(HiddenCell::Empty)
+        );
+        assert_eq!(
+            game.viewer_board()[POINT_3_3],
+            PlayerCell::Hidden(HiddenCell::Empty)
+        );
+        let completed = game.complete();
+        assert_eq!(
+            completed.viewer_board_final()[POINT_3_3],
+            PlayerCell::Hidden(HiddenCell::Empty)
+        );
+    }
+
+    #[test]
+    fn reset_keeps_mines() {
+        let mut game = set_up_game();
+        game.players[0].played = true;
+        game.play(Play {
+            player: 0,
+            action: Action::Flag,
+            point: POINT_3_3,
+        })
+        .unwrap();
+        game.play(Play {
+            player: 0,
+            action: Action::Reveal,
+            point: POINT_0_0,
+        })
+        .unwrap();
+        assert!(game.player_dead(0).unwrap());
+        let mines_before = game
+            .board
+            .iter()
+            .map(|(c, _)| c.is_mine())
+            .collect::<Vec<_>>();
+
+        game.reset();
+
+        let mines_after = game
+            .board
+            .iter()
+            .map(|(c, _)| c.is_mine())
+            .collect::<Vec<_>>();
+        assert_eq!(mines_before, mines_after);
+        assert!(game.board.iter().all(|(_, cs)| !cs.revealed));
+        assert_eq!(game.available.len(), 81 - 4);
+        assert!(!game.player_dead(0).unwrap());
+        assert_eq!(game.player_score(0).unwrap(), 0);
+        assert!(game
+            .player_board(0)
+            .iter()
+            .all(|c| *c == PlayerCell::Hidden(HiddenCell::Empty)));
+        assert!(!game.is_over());
+    }
+
+    #[test]
+    fn reset_keeps_mines_on_first_click() {
+        let mut game = set_up_game();
+        game.play(Play::reveal(0, BoardPoint { row: 8, col: 8 }))
+            .unwrap();
+        game.play(Play::reveal(1, POINT_0_1)).unwrap();
+        let mines = game.mine_positions();
+
+        game.reset();
+        assert_eq!(game.player_snapshot(1), Some(PlayerSnapshot::default()));
+        let outcome = game.play(Play::reveal(0, POINT_0_0)).unwrap();
+        assert!(matches!(outcome, PlayOutcome::Failure(_)));
+        assert_eq!(game.mine_positions(), mines);
+        assert_eq!(game.players[0].scored_at, 0);
+        assert_eq!(game.moves, 1);
+    }
+
+    #[test]
+    fn from_player_board_works() {
+        let hidden = PlayerCell::Hidden(HiddenCell::Empty);
+        let revealed = |contents| {
+            PlayerCell::Revealed(RevealedCell {
+                player: 0,
+                contents,
+            })
+        };
+        // mines forced at 0,0 and 0,3
+        let board = Board::from_vec(vec![
+            vec![hidden, hidden, hidden, hidden],
+            vec![
+                revealed(Cell::Empty(1)),
+                revealed(Cell::Empty(1)),
+                revealed(Cell::Empty(1)),
+                revealed(Cell::Empty(1)),
+            ],
+            vec![
+                revealed(Cell::Empty(0)),
+                revealed(Cell::Empty(0)),
+                revealed(Cell::Empty(0)),
+                revealed(Cell::Empty(0)),
+            ],
+        ]);
+        let mut game = Minesweeper::from_player_board(board.clone(), 2).unwrap();
+        assert_eq!(game.viewer_board(), board);
+        assert_eq!(game.board[POINT_0_0].0, Cell::Mine);
+        assert_eq!(game.board[POINT_0_3].0, Cell::Mine);
+        assert_eq!(game.available.len(), 2);
+        let outcome = game
+            .play(Play {
+                player: 0,
+                action: Action::Reveal,
+                point: POINT_0_1,
+            })
+            .unwrap();
+        assert!(matches!(outcome, PlayOutcome::Success(_)));
+
+        // the 1s can't be satisfied by a single mine
+        assert!(Minesweeper::from_player_board(board, 1).is_err());
+    }
+
+    #[test]
+    fn from_player_board_inconsistent() {
+        let hidden = PlayerCell::Hidden(HiddenCell::Empty);
+        let revealed = |contents| {
+            PlayerCell::Revealed(RevealedCell {
+                player: 0,
+                contents,
+            })
+        };
+        let board = Board::from_vec(vec![
+            vec![hidden, revealed(Cell::Empty(3))],
+            vec![hidden, hidden],
+            vec![revealed(Cell::Empty(0)), hidden],
+        ]);
+        assert!(Minesweeper::from_player_board(board, 3).is_err());
+    }
+
+    #[test]
+    fn from_player_board_keeps_marks() {
+        let hidden = PlayerCell::Hidden(HiddenCell::Empty);
+        let flag = PlayerCell::Hidden(HiddenCell::Flag);
+        let question = PlayerCell::Hidden(HiddenCell::Question);
+        let zero = PlayerCell::Revealed(RevealedCell {
+            player: 0,
+            contents: Cell::Empty(0),
+        });
+        let board = Board::from_vec(vec![
+            vec![flag, question, hidden, hidden],
+            vec![hidden, hidden, hidden, hidden],
+            vec![hidden, hidden, hidden, zero],
+        ]);
+        let game = Minesweeper::from_player_board(board.clone(), 2).unwrap();
+        assert_eq!(game.player_board(0), board);
+        assert_eq!(game.viewer_board()[POINT_0_0], hidden);
+    }
+
+    #[test]
+    fn frontier_search_gives_up() {
+        // needs more mines than the frontier can hold, so every assignment gets tried
+        let search = FrontierSearch {
+            constraints: Vec::new(),
+            touching: vec![Vec::new(); 30],
+            remaining: 31,
+            interior: 0,
+        };
+        let mut budget = 1000;
+        assert!(search.assign(&mut Vec::new(), 0, &mut budget).is_err());
+
+        let search = FrontierSearch {
+            remaining: 30,
+            ..search
+        };
+        let mut budget = LAYOUT_SEARCH_BUDGET;
+        assert!(search.assign(&mut Vec::new(), 0, &mut budget).unwrap());
+    }
+
+    #[test]
+    fn cascade_reveals_huge_empty_board() {
+        let mut game = empty_game_sized(100, 100, 1);
+        let res = game
+            .play(Play {
+                player: 0,
+                action: Action::Reveal,
+                point: BoardPoint { row: 50, col: 50 },
+            })
+            .unwrap();
+        let PlayOutcome::Victory(revealed) = res else {
+            panic!("Expected victory, got {:?}", res);
+        };
+        assert_eq!(revealed.len(), 100 * 100);
+        assert_eq!(
+            revealed
+                .iter()
+                .map(|(p, _)| p)
+                .collect::<HashSet<_>>()
+                .len(),
+            100 * 100
+        );
+        assert!(game.available.is_empty());
+    }
+
+    #[test]
+    fn available_tracks_hidden_safe_cells() {
+        let opts = MinesweeperOpts {
+            rows: 16,
+            cols: 30,
+            num_mines: 99,
+        };
+        let hidden_safe = |game: &Minesweeper| {
+            (0..game.board.size())
+                .map(|i| game.board.point_from_index(i))
+                .filter(|p| !game.board[p].0.is_mine() && !game.board[p].1.revealed)
+                .collect::<HashSet<_>>()
+        };
+        let mut game = MinesweeperBuilder::new(opts).unwrap().with_seed(7).init();
+        assert_eq!(
+            game.available.iter().collect::<HashSet<_>>(),
+            hidden_safe(&game)
+        );
+        while !game.is_over() {
+            let auto_move = crate::auto::next_move(&game.player_board(0), opts.num_mines).unwrap();
+            game.play(Play {
+                player: 0,
+                action: Action::Reveal,
+                point: auto_move.point(),
+            })
+            .unwrap();
+            assert_eq!(
+                game.available.iter().collect::<HashSet<_>>(),
+                hidden_safe(&game)
+            );
+            assert_eq!(game.available.len(), hidden_safe(&game).len());
+        }
+    }
+
+    // the short names are stored in game logs and sent to clients - don't change them
+    #[test]
+    fn play_wire_format() {
+        let play = Play {
+            player: 1,
+            action: Action::RevealAdjacent,
+            point: POINT_1_2,
+        };
+        let json = r#"{"p":1,"a":"ra","bp":{"row":1,"col":2}}"#;
+        assert_eq!(serde_json::to_string(&play).unwrap(), json);
+        let long = r#"{"player":1,"action":"RevealAdjacent","point":{"row":1,"col":2}}"#;
+        for json in [json, long] {
+            let de = serde_json::from_str::<Play>(json).unwrap();
+            assert_eq!(
+                (de.player, de.action, de.point),
+                (play.player, play.action, play.point)
+            );
+        }
+    }
+
+    #[test]
+    fn action_wire_format() {
+        for (action, short, long) in [
+            (Action::Flag, r#""f""#, r#""Flag""#),
+            (Action::Reveal, r#""r""#, r#""Reveal""#),
+            (Action::RevealAdjacent, r#""ra""#, r#""RevealAdjacent""#),
+        ] {
+            assert_eq!(serde_json::to_string(&action).unwrap(), short);
+            assert_eq!(serde_json::from_str::<Action>(short).unwrap(), action);
+            assert_eq!(serde_json::from_str::<Action>(long).unwrap(), action);
+        }
+    }
+
+    #[test]
+    fn play_outcome_wire_format() {
+        let revealed = RevealedCell {
+            player: 0,
+            contents: Cell::Empty(1),
+        };
+        let point = r#"{"row":0,"col":1}"#;
+        let cell = r#"{"p":0,"c":{"e":1}}"#;
+        let cases = [
+            (
+                PlayOutcome::Success(vec![(POINT_0_1, revealed)]),
+                format!(r#"{{"s":[[{point},{cell}]]}}"#),
+                format!(r#"{{"Success":[[{point},{cell}]]}}"#),
+            ),
+            (
+                PlayOutcome::Failure((POINT_0_1, revealed)),
+                format!(r#"{{"x":[{point},{cell}]}}"#),
+                format!(r#"{{"Failure":[{point},{cell}]}}"#),
+            ),
+            (
+                PlayOutcome::Victory(vec![(POINT_0_1, revealed)]),
+                format!(r#"{{"v":[[{point},{cell}]]}}"#),
+                format!(r#"{{"Victory":[[{point},{cell}]]}}"#),
+            ),
+            (
+                PlayOutcome::Flag((POINT_0_1, PlayerCell::Hidden(HiddenCell::Flag))),
+                format!(r#"{{"f":[{point},"f"]}}"#),
+                format!(r#"{{"Flag":[{point},"Flag"]}}"#),
+            ),
+        ];
+        for (outcome, short, long) in cases {
+            let expected = format!("{:?}", outcome);
+            assert_eq!(serde_json::to_string(&outcome).unwrap(), short);
+            for json in [short, long] {
+                let de = serde_json::from_str::<PlayOutcome>(&json).unwrap();
+                assert_eq!(format!("{:?}", de), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn verify_log_valid() {
+        assert!(completed_with_log().verify_log().is_ok());
+    }
+
+    #[test]
+    fn verify_log_corrupted() {
+        let completed = completed_with_log();
+        let board = completed.viewer_board_final();
+        let mut log = completed.recover_log().unwrap();
+        log[2].1 = PlayOutcome::Success(vec![(
+            POINT_0_1,
+            RevealedCell {
+                player: 1,
+                contents: Cell::Empty(1),
+            },
+        )]);
+        let players = (0..2)
+            .map(|player_id| ClientPlayer {
+                player_id,
+                ..ClientPlayer::default()
+            })
+            .collect();
+        let err = CompletedMinesweeper::from_log(board, log, players)
+            .verify_log()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("Log diverges at move 2:"));
+    }
+
+    #[test]
+    fn verify_log_keeps_rules() {
+        let mut game = empty_game_sized(2, 2, 1);
+        game.plant(&POINT_0_0);
+        game.players[0].played = true;
+        game.win_condition = WinCondition::FlagAllMines;
+        game.log = Some(Vec::new());
+        [
+            Play::reveal(0, POINT_0_1),
+            Play::reveal(0, POINT_1_0),
+            Play::reveal(0, BoardPoint { row: 1, col: 1 }),
+            Play::flag(0, POINT_0_0),
+        ]
+        .into_iter()
+        .for_each(|play| {
+            game.play(play).unwrap();
+        });
+        let completed = game.complete();
+        assert!(completed.verify_log().is_ok());
+
+        // the last reveal only wins when revealing is enough
+        let players = vec![ClientPlayer {
+            player_id: 0,
+            ..ClientPlayer::default()
+        }];
+        let rebuilt = CompletedMinesweeper::from_log(
+            completed.viewer_board_final(),
+            completed.recover_log().unwrap(),
+            players,
+        );
+        assert!(rebuilt.verify_log().is_err());
+        let rules = PlayRules {
+            win_condition: WinCondition::FlagAllMines,
+            ..PlayRules::default()
+        };
+        assert!(rebuilt.with_rules(rules).verify_log().is_ok());
+    }
+
+    #[test]
+    fn mine_positions_seeded() {
+        let opts = MinesweeperOpts {
+            rows: 9,
+            cols: 9,
+            num_mines: 10,
+        };
+        let game = MinesweeperBuilder::new(opts).unwrap().with_seed(7).init();
+        let positions = game.mine_positions();
+        assert_eq!(positions.len(), 10);
+        assert_eq!(game.mine_count(), 10);
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        let final_board = game.complete().viewer_board_final();
+        assert!(positions.iter().all(|p| matches!(
+            final_board[p].into_hidden(),
+            PlayerCell::Hidden(HiddenCell::Mine)
+        )));
+    }
+
+    #[test]
+    fn play_constructors() {
+        for (play, action) in [
+            (Play::reveal(1, POINT_1_2), Action::Reveal),
+            (Play::flag(1, POINT_1_2), Action::Flag),
+            (Play::chord(1, POINT_1_2), Action::RevealAdjacent),
+        ] {
+            assert_eq!(
+                (play.player, play.action, play.point),
+                (1, action, POINT_1_2)
+            );
+        }
+        assert_eq!(serde_json::to_string(&Action::Chord).unwrap(), r#""ra""#);
+        assert_eq!(
+            serde_json::from_str::<Action>(r#""Chord""#).unwrap(),
+            Action::Chord
+        );
+    }
+
+    #[test]
+    fn chord_alias_matches() {
+        let action = Play::chord(0, POINT_0_0).action;
+        assert!(matches!(action, Action::Chord));
+        assert!(matches!(Action::RevealAdjacent, Action::Chord));
+        assert!(!matches!(Action::Reveal, Action::Chord));
+    }
+
+    #[test]
+    fn replay_log_resumes_game() {
+        let opts = MinesweeperOpts {
+            rows: 9,
+            cols: 9,
+            num_mines: 10,
+        };
+        let new_game = |seed| {
+            MinesweeperBuilder::new(opts)
+                .unwrap()
+                .with_multiplayer(2)
+                .with_seed(seed)
+                .with_log()
+                .init()
+        };
+        let mut game = new_game(3);
+        game.play(Play::reveal(0, BoardPoint { row: 4, col: 4 }))
+            .unwrap();
+        let mines = game.mine_positions();
+        game.play(Play::flag(1, mines[0])).unwrap();
+        let log = game.get_log().unwrap();
+
+        let mut resumed = new_game(3);
+        resumed.replay_log(&log).unwrap();
+        assert_eq!(resumed.mine_positions(), mines);
+        assert_eq!(resumed.player_board(1), game.player_board(1));
+        assert_eq!(
+            resumed.player_score(0).unwrap(),
+            game.player_score(0).unwrap()
+        );
+        assert_eq!(resumed.get_log().unwrap().len(), 2);
+
+        // another seed doesn't reveal the same cells
+        assert!(new_game(4).replay_log(&log).is_err());
+    }
+
+    #[test]
+    fn apply_log_reaches_same_board() {
+        let opts = MinesweeperOpts {
+            rows: 9,
+            cols: 9,
+            num_mines: 10,
+        };
+        let mut game = MinesweeperBuilder::new(opts)
+            .unwrap()
+            .with_multiplayer(2)
+            .with_seed(3)
+            .with_log()
+            .init();
+        game.play(Play::reveal(0, BoardPoint { row: 4, col: 4 }))
+            .unwrap();
+        let mines = game.mine_positions();
+        game.play(Play::flag(0, mines[0])).unwrap();
+        let board = game.viewer_board();
+        let safe = (0..board.size())
+            .map(|i| board.point_from_index(i))
+            .rev()
+            .find(|p| matches!(board[p], PlayerCell::Hidden(_)) && !mines.contains(p))
+            .unwrap();
+        game.play(Play::reveal(1, safe)).unwrap();
+        let log = game.log.clone().unwrap();
+
+        let mut replayed = MinesweeperBuilder::new(opts)
+            .unwrap()
+            .with_multiplayer(2)
+            .with_seed(4)
+            .init();
+        replayed.apply_log(&log).unwrap();
+        assert_eq!(replayed.viewer_board(), game.viewer_board());
+        for player in 0..2 {
+            assert_eq!(replayed.player_board(player), game.player_board(player));
+            assert_eq!(
+                replayed.player_score(player).unwrap(),
+                game.player_score(player).unwrap()
+            );
+        }
+        assert_eq!(replayed.mine_count(), 10);
+
+        assert!(replayed.apply_log(&log).is_err());
+    }
+
+    #[test]
+    fn play_errors() {
+        let mut game = empty_game_sized(3, 4, 2);
+        game.plant(&POINT_0_0);
+        game.plant(&point(2, 0));
+        game.play(Play::reveal(0, POINT_1_2)).unwrap();
+
+        let mut assert_err = |play, err| assert_eq!(game.play(play).unwrap_err(), err);
+        assert_err(Play::reveal(0, point(5, 5)), PlayError::OutOfBounds);
+        assert_err(Play::reveal(2, point(1, 0)), PlayError::UnknownPlayer(2));
+        assert_err(Play::reveal(0, POINT_1_2), PlayError::AlreadyRevealed);
+        assert_err(Play::flag(0, POINT_1_2), PlayError::AlreadyRevealed);
+        assert_err(Play::chord(0, point(1, 0)), PlayError::NotRevealed);
+        assert_err(Play::chord(0, point(1, 3)), PlayError::NoNumber);
+        assert_err(
+            Play::chord(0, POINT_1_1),
+            PlayError::DoubleClickMismatch {
+                expected: 2,
+                got: 0,
+            },
+        );
+
+        game.play(Play::flag(1, point(1, 0))).unwrap();
+        assert_eq!(
+            game.play(Play::reveal(1, point(1, 0))).unwrap_err(),
+            PlayError::FlaggedCell
+        );
+        game.flag_limit = Some(0);
+        assert_eq!(
+            game.play(Play::flag(0, point(1, 0))).unwrap_err(),
+            PlayError::FlagLimit
+        );
+        game.flag_limit = None;
+
+        game.play(Play::reveal(1, POINT_0_0)).unwrap();
+        assert_eq!(
+            game.play(Play::reveal(1, point(1, 0))).unwrap_err(),
+            PlayError::DeadPlayer
+        );
+        game.play(Play::reveal(0, point(1, 0))).unwrap();
+        let err = game.play(Play::reveal(0, point(2, 0))).unwrap_err();
+        assert_eq!(err, PlayError::GameOver);
+        assert_eq!(anyhow::Error::from(err).to_string(), "Game is over");
+    }
+
+    #[test]
+    fn with_mines_at_plants_exactly() {
+        let opts = MinesweeperOpts {
+            rows: 4,
+            cols: 4,
+            num_mines: 10,
+        };
+        let corners = vec![
+            POINT_0_0,
+            BoardPoint { row: 0, col: 3 },
+            BoardPoint { row: 3, col: 0 },
+            BoardPoint { row: 3, col: 3 },
+        ];
+        let game = MinesweeperBuilder::new(opts)
+            .unwrap()
+            .with_mines_at(corners.clone())
+            .unwrap()
+            .with_min_opening(5)
+            .init();
+        let mut sorted = corners.clone();
+        sorted.sort();
+        assert_eq!(game.mine_positions(), sorted);
+        assert_eq!(game.available.len(), 12);
+        let counts = game
+            .board
+            .rows_iter()
+            .map(|row| row.iter().map(|(cell, _)| *cell).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let (m, e) = (Cell::Mine, Cell::Empty);
+        assert_eq!(
+            counts,
+            vec![
+                vec![m, e(1), e(1), m],
+                vec![e(1), e(1), e(1), e(1)],
+                vec![e(1), e(1), e(1), e(1)],
+                vec![m, e(1), e(1), m],
+            ]
+        );
+
+        let builder = || MinesweeperBuilder::new(opts).unwrap();
+        assert_eq!(
+            builder()
+                .with_mines_at(vec![BoardPoint { row: 4, col: 0 }])
+                .err(),
+            Some(OptsError::MineOutOfBounds(BoardPoint { row: 4, col: 0 }))
+        );
+        assert_eq!(
+            builder().with_mines_at(vec![POINT_0_0, POINT_0_0]).err(),
+            Some(OptsError::DuplicateMine(POINT_0_0))
+        );
+        assert_eq!(
+            builder().with_mines_at(Vec::new()).err(),
+            Some(OptsError::ZeroMines)
+        );
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        let ascii = "*1--\n11--\n----\n---*";
+        let mut game = Minesweeper::from_ascii(ascii).unwrap();
+        assert_eq!(game.to_ascii(), ascii);
+        assert_eq!(game.player_score(0).unwrap(), 3);
+
+        game.play(Play::reveal(0, BoardPoint { row: 0, col: 3 }))
+            .unwrap();
+        let ascii = game.to_ascii();
+        assert_eq!(ascii, "*1  \n11  \n  11\n  1*");
+        assert_eq!(Minesweeper::from_ascii(&ascii).unwrap().to_ascii(), ascii);
+        // trailing zeros are cells too
+        let ascii = "*1  \n11  \n    ";
+        assert_eq!(Minesweeper::from_ascii(ascii).unwrap().to_ascii(), ascii);
+
+        assert!(Minesweeper::from_ascii("*2\n--").is_err());
+        assert!(Minesweeper::from_ascii("*-\n-").is_err());
+        assert!(Minesweeper::from_ascii("*?\n--").is_err());
+        assert!(Minesweeper::from_ascii("*1\n10").is_err());
+    }
+
+    #[test]
+    fn mock_rng_controls_replant() {
+        // every draw is 0, so a shuffle rotates the list to start at its second item
+        let rng = StepRng::new(0, 0);
+        let mines = vec![
+            POINT_0_0,
+            POINT_0_1,
+            point(0, 2),
+            point(1, 0),
+            POINT_1_2,
+            point(2, 0),
+            point(2, 1),
+        ];
+        let mut game = MinesweeperBuilder::new(MinesweeperOpts {
+            rows: 3,
+            cols: 3,
+            num_mines: 7,
+        })
+        .unwrap()
+        .with_mines_at(mines)
+        .unwrap()
+        .with_superclick()
+        .with_rng(rng)
+        .init();
+        // worst case - 3 mines move off the first click, but only 2,2 is free away from it, so
+        // two go back next to it
+        let outcome = game.play(Play::reveal(0, POINT_0_0)).unwrap();
+        assert!(!matches!(outcome, PlayOutcome::Failure(_)));
+        assert_eq!(game.to_ascii(), "2-*\n***\n***");
+    }
+
+    #[test]
+    fn in_progress_boards_hide_mines() {
+        let opts = MinesweeperOpts {
+            rows: 9,
+            cols: 9,
+            num_mines: 10,
+        };
+        let mut game = MinesweeperBuilder::new(opts)
+            .unwrap()
+            .with_multiplayer(2)
+            .with_seed(5)
+            .init();
+        let is_hidden_mine = |cell: &PlayerCell| {
+            matches!(
+                cell,
+                PlayerCell::Hidden(HiddenCell::Mine | HiddenCell::FlagMine)
+            )
+        };
+        game.play(Play::reveal(0, BoardPoint { row: 4, col: 4 }))
+            .unwrap();
+        let mines = game.mine_positions();
+        game.play(Play::flag(1, mines[0])).unwrap();
+        game.play(Play::reveal(0, mines[1])).unwrap();
+        for board in [
+            game.viewer_board(),
+            game.player_board(0),
+            game.player_board(1),
+        ] {
+            assert!(!board.iter().any(is_hidden_mine));
+            Minesweeper::assert_no_mine_leak(&board);
+        }
+
+        let final_board = game.complete().viewer_board_final();
+        assert!(final_board.iter().any(is_hidden_mine));
+        let leaked = std::panic::catch_unwind(|| Minesweeper::assert_no_mine_leak(&final_board));
+        assert_eq!(leaked.is_err(), cfg!(debug_assertions));
+    }
+
+    #[test]
+    fn mines_by_distance_spreads_outward() {
+        let mut game = empty_game_sized(5, 5, 1);
+        [
+            point(0, 0),
+            point(2, 3),
+            point(4, 4),
+            point(2, 1),
+            point(3, 2),
+        ]
+        .iter()
+        .for_each(|p| game.plant(p));
+        game.play(Play::reveal(0, point(0, 4))).unwrap();
+        game.play(Play::reveal(0, point(2, 3))).unwrap();
+        let completed = game.complete();
+        assert_eq!(
+            completed.mines_by_distance(point(2, 3)),
+            vec![
+                point(2, 3),
+                point(3, 2),
+                point(2, 1),
+                point(4, 4),
+                point(0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn win_conditions() {
+        let new_game = |win_condition| {
+            let mut game = empty_game_sized(2, 2, 1);
+            game.plant(&POINT_0_0);
+            game.players[0].played = true;
+            game.win_condition = win_condition;
+            game
+        };
+
+        let mut game = new_game(WinCondition::RevealAllSafe);
+        game.play(Play::reveal(0, point(0, 1))).unwrap();
+        game.play(Play::reveal(0, point(1, 0))).unwrap();
+        let outcome = game.play(Play::reveal(0, point(1, 1))).unwrap();
+        assert!(matches!(outcome, PlayOutcome::Victory(_)));
+        assert_eq!(game.end_reason(), Some(GameEndReason::BoardCleared));
+        assert!(game.player_victory_click(0).unwrap());
+
+        let mut game = new_game(WinCondition::FlagAllMines);
+        game.play(Play::reveal(0, point(0, 1))).unwrap();
+        game.play(Play::reveal(0, point(1, 0))).unwrap();
+        // a wrong flag alongside the right one isn't a win
+        game.play(Play::flag(0, point(1, 1))).unwrap();
+        let outcome = game.play(Play::flag(0, POINT_0_0)).unwrap();
+        assert!(matches!(outcome, PlayOutcome::Flag(_)));
+        game.play(Play::flag(0, POINT_0_0)).unwrap();
+        game.play(Play::flag(0, point(1, 1))).unwrap();
+        // every safe cell revealed, but the mine isn't flagged yet
+        let outcome = game.play(Play::reveal(0, point(1, 1))).unwrap();
+        assert!(matches!(outcome, PlayOutcome::Success(_)));
+        assert!(!game.is_over());
+        let outcome = game.play(Play::flag(0, POINT_0_0)).unwrap();
+        assert_eq!(
+            outcome,
+            PlayOutcome::Flag((POINT_0_0, PlayerCell::Hidden(HiddenCell::Flag)))
+        );
+        assert_eq!(game.end_reason(), Some(GameEndReason::BoardCleared));
+        assert!(game.player_victory_click(0).unwrap());
+    }
+
+    #[test]
+    fn flag_won_game_replays() {
+        let mut game = empty_game_sized(2, 2, 1);
+        game.plant(&POINT_0_0);
+        game.players[0].played = true;
+        game.win_condition = WinCondition::FlagAllMines;
+        game.log = Some(Vec::new());
+        game.play(Play::reveal(0, point(0, 1))).unwrap();
+        game.play(Play::reveal(0, point(1, 0))).unwrap();
+        game.play(Play::reveal(0, point(1, 1))).unwrap();
+        game.play(Play::flag(0, POINT_0_0)).unwrap();
+        assert!(game.is_over());
+
+        let completed = game.complete();
+        let log = completed.get_log().unwrap();
+        assert_eq!(log.len(), 4);
+        assert!(matches!(log[3].1, PlayOutcome::Flag((POINT_0_0, _))));
+        let mut replay = completed.replay(Some(0)).unwrap();
+        assert_eq!(
+            replay.seek_to_end()[POINT_0_0],
+            PlayerCell::Hidden(HiddenCell::FlagMine)
+        );
+
+        let players = vec![ClientPlayer {
+            player_id: 0,
+            score: 3,
+            victory_click: true,
+            ..ClientPlayer::default()
+        }];
+        let restored = CompletedMinesweeper::from_log(completed.viewer_board_final(), log, players);
+        assert_eq!(
+            restored.player_board_final(0)[POINT_0_0],
+            PlayerCell::Hidden(HiddenCell::FlagMine)
+        );
+    }
+
+    #[test]
+    fn flag_accuracy() {
+        let mut game = empty_game_sized(3, 3, 2);
+        game.plant(&POINT_0_0);
+        game.plant(&point(2, 2));
+        [POINT_0_0, point(2, 2), point(1, 1), point(0, 2)]
+            .into_iter()
+            .for_each(|p| {
+                game.play(Play::flag(0, p)).unwrap();
+            });
+        game.play(Play::flag(1, point(2, 0))).unwrap();
+
+        assert_eq!(game.flagged_correctly(0).unwrap(), 2);
+        assert_eq!(game.misflags(0).unwrap(), vec![point(0, 2), point(1, 1)]);
+        assert_eq!(game.flagged_correctly(1).unwrap(), 0);
+        assert_eq!(game.misflags(1).unwrap(), vec![point(2, 0)]);
+        assert!(game.flagged_correctly(2).is_err());
+        assert!(game.misflags(2).is_err());
+    }
+
+    #[test]
+    fn flag_by_index_flags_that_cell() {
+        let mut game = empty_game_sized(3, 5, 1);
+        let point = game.board.point_from_index(7);
+        assert_eq!(point, BoardPoint { row: 1, col: 2 });
+        game.play(Play::flag(0, point)).unwrap();
+
+        let board = game.player_board(0);
+        let flagged = (0..board.size())
+            .filter(|&i| board[board.point_from_index(i)] == PlayerCell::Hidden(HiddenCell::Flag))
+            .collect::<Vec<_>>();
+        assert_eq!(flagged, vec![7]);
+        assert_eq!(board.index_from_point(point), 7);
+    }
+
+    #[test]
+    fn status_won_and_lost() {
+        let mut won = set_up_game();
+        assert_eq!(won.status(), GameStatus::InProgress);
+        won.play(Play::reveal(0, BoardPoint { row: 8, col: 8 }))
+            .unwrap();
+        won.play(Play::reveal(0, POINT_0_1)).unwrap();
+        won.play(Play::reveal(0, POINT_1_0)).unwrap();
+        won.play(Play::reveal(0, POINT_0_2)).unwrap();
+        won.play(Play::reveal(0, BoardPoint { row: 2, col: 0 }))
+            .unwrap();
+        assert!(won.is_over());
+        assert_eq!(won.status(), GameStatus::Won);
+
+        let mut lost = set_up_game();
+        lost.play(Play::reveal(0, BoardPoint { row: 8, col: 8 }))
+            .unwrap();
+        lost.play(Play::reveal(0, POINT_1_1)).unwrap();
+        // the other player can still play
+        assert_eq!(lost.status(), GameStatus::InProgress);
+        lost.play(Play::reveal(1, POINT_1_2)).unwrap();
+        assert_eq!(lost.status(), GameStatus::Lost);
+
+        let mut timed_out = set_up_game();
+        timed_out.time_out();
+        assert_eq!(timed_out.status(), GameStatus::Lost);
+    }
+
+    #[test]
+    fn player_board_at_follows_log_prefix() {
+        let completed = completed_with_log();
+        let flagged = |board: &Board<PlayerCell>| {
+            (0..board.size())
+                .map(|i| board.point_from_index(i))
+                .filter(|p| {
+                    matches!(
+                        board[p],
+                        PlayerCell::Hidden(HiddenCell::Flag | HiddenCell::FlagMine)
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let expected = [vec![], vec![], vec![POINT_0_0], vec![POINT_0_0], vec![]];
+        for (k, flags) in expected.iter().enumerate() {
+            let board = completed.player_board_at(0, k).unwrap();
+            assert_eq!(&flagged(&board), flags, "move {k}");
+            assert!(flagged(&completed.player_board_at(1, k).unwrap()).is_empty());
+        }
+        assert!(matches!(
+            completed.player_board_at(0, 1).unwrap()[POINT_3_3],
+            PlayerCell::Revealed(_)
+        ));
+        assert!(matches!(
+            completed.player_board_at(0, 0).unwrap()[POINT_3_3],
+            PlayerCell::Hidden(_)
+        ));
+        assert_eq!(
+            completed.player_board_at(0, 4).unwrap(),
+            completed.player_board_final(0)
+        );
+        assert!(completed.player_board_at(0, 5).is_err());
+        assert!(completed.player_board_at(2, 0).is_err());
+    }
+
+    #[test]
+    fn share_code_round_trip() {
+        let completed = completed_with_log();
+        let code = completed.to_share_code().unwrap();
+        assert!(!code.contains(['+', '/', '=']));
+
+        let shared = CompletedMinesweeper::from_share_code(&code).unwrap();
+        assert!(shared.viewer_board_final() == completed.viewer_board_final());
+        for player in 0..2 {
+            assert!(shared.player_board_final(player) == completed.player_board_final(player));
+            assert_eq!(
+                shared.player_score(player).unwrap(),
+                completed.player_score(player).unwrap()
+            );
+            assert_eq!(
+                shared.player_dead(player).unwrap(),
+                completed.player_dead(player).unwrap()
+            );
+        }
+        assert_eq!(shared.rules, completed.rules);
+        assert_eq!(
+            serde_json::to_string(&shared.recover_log()).unwrap(),
+            serde_json::to_string(&completed.recover_log()).unwrap()
+        );
+
+        assert!(CompletedMinesweeper::from_share_code("not a share code").is_err());
+        assert!(CompletedMinesweeper::from_share_code(&code[..code.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn settings_survive_completion() {
+        let opts = MinesweeperOpts {
+            rows: 9,
+            cols: 9,
+            num_mines: 10,
+        };
+        let mut game = MinesweeperBuilder::new(opts).unwrap().with_seed(3).init();
+        game.play(Play::reveal(0, BoardPoint { row: 4, col: 4 }))
+            .unwrap();
+        let mine = game.mine_positions()[0];
+        game.play(Play::flag(0, mine)).unwrap();
+        assert_eq!(game.settings(), opts);
+
+        let completed = game.complete();
+        assert_eq!(completed.settings(), opts);
+        let restarted = MinesweeperBuilder::new(completed.settings())
+            .unwrap()
+            .init();
+        assert_eq!(restarted.settings(), opts);
+    }
+
+    #[test]
+    fn snapshots_match_getters() {
+        let mut game = set_up_game();
+        game.play(Play::reveal(0, POINT_3_3)).unwrap();
+        game.play(Play::reveal(1, POINT_0_2)).unwrap();
+        game.play(Play::reveal(1, POINT_0_0)).unwrap();
+
+        let snapshots = game.all_player_snapshots();
+        assert_eq!(snapshots.len(), 2);
+        for (player, snapshot) in snapshots.into_iter().enumerate() {
+            assert_eq!(game.player_snapshot(player), Some(snapshot));
+            assert_eq!(snapshot.score, game.player_score(player).unwrap());
+            assert_eq!(snapshot.dead, game.player_dead(player).unwrap());
+            assert_eq!(
+                snapshot.victory_click,
+                game.player_victory_click(player).unwrap()
+            );
+            assert_eq!(snapshot.top_score, game.player_top_score(player).unwrap());
+        }
+        assert!(game.player_snapshot(0).unwrap().top_score);
+        assert!(game.player_snapshot(1).unwrap().dead);
+        assert_eq!(game.player_snapshot(2), None);
+    }
+
+    #[test]
+    fn first_to_top_score_breaks_ties() {
+        let mut game = empty_game_sized(3, 5, 2);
+        game.plant(&point(1, 2));
+        game.players.iter_mut().for_each(|p| p.played = true);
+        assert_eq!(game.first_to_top_score(), None);
+
+        game.play(Play::reveal(1, point(0, 1))).unwrap();
+        game.play(Play::reveal(0, point(0, 2))).unwrap();
+        assert_eq!(game.first_to_top_score(), Some(1));
+        game.play(Play::reveal(0, point(0, 3))).unwrap();
+        assert_eq!(game.first_to_top_score(), Some(0));
+        game.play(Play::reveal(1, point(2, 1))).unwrap();
+
+        // tied on 2 - player 0 got there first
+        assert!(game.player_top_score(0).unwrap());
+        assert!(game.player_top_score(1).unwrap());
+        assert_eq!(game.first_to_top_score(), Some(0));
+    }
+
+    #[test]
+    fn first_to_top_score_survives_apply_log() {
+        let new_game = || {
+            let mut game = empty_game_sized(3, 5, 2);
+            game.plant(&point(1, 2));
+            game.players.iter_mut().for_each(|p| p.played = true);
+            game.log = Some(Vec::new());
+            game
+        };
+        let mut game = new_game();
+        game.play(Play::reveal(1, point(0, 1))).unwrap();
+        game.play(Play::reveal(0, point(0, 2))).unwrap();
+        game.play(Play::reveal(0, point(0, 3))).unwrap();
+        game.play(Play::reveal(1, point(2, 1))).unwrap();
+        assert_eq!(game.first_to_top_score(), Some(0));
+
+        let mut replayed = new_game();
+        replayed.apply_log(&game.get_log().unwrap()).unwrap();
+        assert_eq!(replayed.first_to_top_score(), Some(0));
+
+        // nothing to tell them apart - lowest player number
+        replayed.players[0].scored_at = 0;
+        replayed.players[1].scored_at = 0;
+        assert_eq!(replayed.first_to_top_score(), Some(0));
+    }
+
+    #[test]
+    fn resigning_last_player_ends_game() {
+        let mut game = set_up_game();
+        game.players[0].played = true;
+        game.play(Play::reveal(0, POINT_0_0)).unwrap();
+        assert!(game.player_dead(0).unwrap());
+        assert!(!game.is_over());
+
+        assert_eq!(game.resign(2), Err(PlayError::UnknownPlayer(2)));
+        assert_eq!(game.resign(0), Err(PlayError::DeadPlayer));
+        game.resign(1).unwrap();
+        assert!(game.player_dead(1).unwrap());
+        assert!(game.is_over());
+        assert_eq!(game.end_reason(), Some(GameEndReason::AllDead));
+        assert_eq!(game.resign(1), Err(PlayError::GameOver));
+    }
+
+    #[test]
+    fn resigning_single_player_loses() {
+        let mut game = set_up_game();
+        game.players.truncate(1);
+        game.players[0].played = true;
+        game.play(Play::reveal(0, POINT_3_3)).unwrap();
+
+        game.resign(0).unwrap();
+        assert!(game.is_over());
+        assert_eq!(game.status(), GameStatus::Lost);
+        assert_eq!(
+            game.play(Play::reveal(0, POINT_0_2)),
+            Err(PlayError::GameOver)
+        );
+    }
+
+    #[test]
+    fn lenient_noop_chord_is_not_a_move() {
+        let center = BoardPoint { row: 2, col: 2 };
+        let mut game = set_up_game_no_superclick();
+        game.lenient_chord = true;
+        game.play(Play::reveal(0, center)).unwrap();
+        let moves = game.moves;
+
+        assert_eq!(
+            game.play(Play::chord(0, center)).unwrap(),
+            PlayOutcome::Success(Vec::new())
+        );
+        assert_eq!(game.moves, moves);
+        game.play(Play::reveal(0, POINT_3_3)).unwrap();
+        assert_eq!(game.players[0].scored_at, moves + 1);
+    }
+
+    #[test]
+    fn ascii_without_mines() {
+        let ascii = "---\n---";
+        let mut game = Minesweeper::from_ascii(ascii).unwrap();
+        assert_eq!(game.to_ascii(), ascii);
+        assert_eq!(game.mine_count(), 0);
+
+        game.play(Play::reveal(0, point(0, 0))).unwrap();
+        let ascii = game.to_ascii();
+        assert_eq!(ascii, "   \n   ");
+        assert!(game.is_over());
+        assert_eq!(Minesweeper::from_ascii(&ascii).unwrap().to_ascii(), ascii);
+    }
 }
@@ -1,7 +1,11 @@
 pub mod analysis;
+pub mod auto;
+mod available;
 pub mod board;
 pub mod cell;
 pub mod client;
 pub mod game;
 pub mod replay;
-mod upair;
+pub mod upair;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -1,4 +1,4 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::HashMap};
 
 use anyhow::{bail, Result};
 
@@ -101,8 +101,10 @@ pub struct SimplePlayer {
 }
 
 impl SimplePlayer {
-    pub fn update_client_player(self, cp: &mut ClientPlayer) {
-        cp.top_score = false;
+    /// Copies this frame's standing onto `cp` - `top_score` is the frame's, from
+    /// [`MinesweeperReplay::current_top_score`]
+    pub fn update_client_player(self, cp: &mut ClientPlayer, top_score: Option<usize>) {
+        cp.top_score = top_score == Some(self.score);
         cp.score = self.score;
         cp.dead = self.dead;
         cp.victory_click = self.victory_click;
@@ -116,6 +118,8 @@ pub struct MinesweeperReplay {
     current_flags: usize,
     current_revealed_mines: usize,
     log: Vec<(Play, PlayOutcome)>,
+    /// Mark each flag step replaces, so stepping either way needn't rescan the log
+    previous_marks: Vec<PlayerCell>,
     current_pos: usize,
 }
 
@@ -125,6 +129,16 @@ impl MinesweeperReplay {
         log: Vec<(Play, PlayOutcome)>,
         players: usize,
     ) -> Self {
+        let mut marks = HashMap::new();
+        let previous_marks = log
+            .iter()
+            .map(|(_, outcome)| match outcome {
+                PlayOutcome::Flag((point, mark)) => marks
+                    .insert(*point, *mark)
+                    .unwrap_or(PlayerCell::Hidden(HiddenCell::Empty)),
+                _ => PlayerCell::Hidden(HiddenCell::Empty),
+            })
+            .collect();
         Self {
             current_board: starting_board,
             current_play: None,
@@ -132,6 +146,7 @@ impl MinesweeperReplay {
             current_flags: 0,
             current_revealed_mines: 0,
             log,
+            previous_marks,
             current_pos: 0,
         }
     }
@@ -177,6 +192,63 @@ impl MinesweeperReplay {
     pub fn current_flags_and_revealed_mines(&self) -> usize {
         self.current_flags + self.current_revealed_mines
     }
+
+    /// Highest score at this point in the replay - `None` in single player or before anyone
+    /// scores, like `Minesweeper::current_top_score`
+    pub fn current_top_score(&self) -> Option<usize> {
+        if self.current_players.len() < 2 {
+            return None;
+        }
+        self.current_players
+            .iter()
+            .map(|p| p.score)
+            .max()
+            .filter(|&score| score > 0)
+    }
+
+    /// Jumps to the move which ended the game
+    pub fn seek_to_end(&mut self) -> &Board<PlayerCell> {
+        let _ = self.to_pos(ReplayPosition::End);
+        &self.current_board
+    }
+
+    /// Jumps to the first move which hit a mine, or `None` if no one hit one
+    pub fn seek_to_first_mine(&mut self) -> Option<&Board<PlayerCell>> {
+        let index = self
+            .log
+            .iter()
+            .position(|(_, outcome)| matches!(outcome, PlayOutcome::Failure(_)))?;
+        // position n is the board once the first n moves are played
+        let pos = ReplayPosition::from_pos(index + 1, self.len());
+        self.to_pos(pos).ok()?;
+        Some(&self.current_board)
+    }
+
+    fn mark(&mut self, point: BoardPoint, mark: PlayerCell) {
+        match mark {
+            PlayerCell::Hidden(HiddenCell::Flag) => {
+                self.current_flags += 1;
+                self.current_board[point] = self.current_board[point].add_flag()
+            }
+            PlayerCell::Hidden(HiddenCell::Question) => {
+                self.current_board[point] = self.current_board[point].add_question()
+            }
+            _ => {}
+        }
+    }
+
+    fn unmark(&mut self, point: BoardPoint, mark: PlayerCell) {
+        match mark {
+            PlayerCell::Hidden(HiddenCell::Flag) => {
+                self.current_flags -= 1;
+                self.current_board[point] = self.current_board[point].remove_flag()
+            }
+            PlayerCell::Hidden(HiddenCell::Question) => {
+                self.current_board[point] = self.current_board[point].remove_question()
+            }
+            _ => {}
+        }
+    }
 }
 
 impl Replayable for MinesweeperReplay {
@@ -205,20 +277,17 @@ impl Replayable for MinesweeperReplay {
                 self.current_board[rc.0] = PlayerCell::Revealed(rc.1);
             }
             PlayOutcome::Victory(results) => {
-                self.current_players[results[0].1.player].victory_click = true;
+                self.current_players[play.0.player].victory_click = true;
                 results.iter().for_each(|rc| {
                     self.current_players[rc.1.player].score += 1;
                     self.current_board[rc.0] = PlayerCell::Revealed(rc.1);
                 });
             }
             PlayOutcome::Flag(res) => {
-                if matches!(res.1, PlayerCell::Hidden(HiddenCell::Flag)) {
-                    self.current_flags += 1;
-                    self.current_board[res.0] = self.current_board[res.0].add_flag()
-                } else {
-                    self.current_flags -= 1;
-                    self.current_board[res.0] = self.current_board[res.0].remove_flag()
-                }
+                let res = *res;
+                let prev = self.previous_marks[self.current_pos];
+                self.unmark(res.0, prev);
+                self.mark(res.0, res.1);
             }
         };
         self.current_pos += 1;
@@ -247,20 +316,17 @@ impl Replayable for MinesweeperReplay {
                 self.current_board[rc.0] = PlayerCell::Hidden(HiddenCell::Mine);
             }
             PlayOutcome::Victory(results) => {
-                self.current_players[results[0].1.player].victory_click = false;
+                self.current_players[play_to_undo.0.player].victory_click = false;
                 results.iter().for_each(|rc| {
                     self.current_players[rc.1.player].score -= 1;
                     self.current_board[rc.0] = PlayerCell::Hidden(HiddenCell::Empty);
                 });
             }
             PlayOutcome::Flag(res) => {
-                if matches!(res.1, PlayerCell::Hidden(HiddenCell::Flag)) {
-                    self.current_flags -= 1;
-                    self.current_board[res.0] = self.current_board[res.0].remove_flag()
-                } else {
-                    self.current_flags += 1;
-                    self.current_board[res.0] = self.current_board[res.0].add_flag()
-                }
+                let res = *res;
+                let prev = self.previous_marks[self.current_pos];
+                self.unmark(res.0, res.1);
+                self.mark(res.0, prev);
             }
         };
         Ok(self.current_pos())
@@ -286,6 +352,10 @@ impl MinesweeperReplayWithAnalysis {
         &self.replay.current_players
     }
 
+    pub fn current_top_score(&self) -> Option<usize> {
+        self.replay.current_top_score()
+    }
+
     pub fn current_flags_and_revealed_mines(&self) -> usize {
         self.replay.current_flags + self.replay.current_revealed_mines
     }
@@ -574,4 +644,153 @@ mod test {
 
         assert!(replay.to_pos(ReplayPosition::Other(5)).is_err());
     }
+
+    #[test]
+    fn scoreboard_follows_frames() {
+        let starting_board = Board::new(4, 4, PlayerCell::Hidden(HiddenCell::Empty));
+        let revealed = |player, row, col| {
+            (
+                BoardPoint { row, col },
+                RevealedCell {
+                    player,
+                    contents: Cell::Empty(1),
+                },
+            )
+        };
+        let reveal = |player, row, col| Play {
+            player,
+            action: Action::Reveal,
+            point: BoardPoint { row, col },
+        };
+        let log = Vec::from([
+            (
+                reveal(1, 0, 0),
+                PlayOutcome::Success(vec![revealed(1, 0, 0)]),
+            ),
+            (
+                reveal(0, 1, 1),
+                PlayOutcome::Success(vec![revealed(0, 1, 1), revealed(0, 1, 2)]),
+            ),
+            (
+                reveal(1, 2, 2),
+                PlayOutcome::Success(vec![revealed(1, 2, 2)]),
+            ),
+        ]);
+        let mut replay = MinesweeperReplay::new(starting_board, log, 2);
+        let scoreboard = |replay: &MinesweeperReplay| {
+            let top_score = replay.current_top_score();
+            replay
+                .current_players()
+                .iter()
+                .enumerate()
+                .map(|(player_id, p)| {
+                    let mut cp = ClientPlayer {
+                        player_id,
+                        ..ClientPlayer::default()
+                    };
+                    p.update_client_player(&mut cp, top_score);
+                    (cp.score, cp.top_score)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(scoreboard(&replay), vec![(0, false), (0, false)]);
+        replay.advance().unwrap();
+        assert_eq!(scoreboard(&replay), vec![(0, false), (1, true)]);
+        replay.advance().unwrap();
+        assert_eq!(scoreboard(&replay), vec![(2, true), (1, false)]);
+        replay.advance().unwrap();
+        assert_eq!(scoreboard(&replay), vec![(2, true), (2, true)]);
+        replay.rewind().unwrap();
+        assert_eq!(scoreboard(&replay), vec![(2, true), (1, false)]);
+    }
+
+    #[test]
+    fn seek_to_decisive_moves() {
+        let starting_board = Board::new(4, 4, PlayerCell::Hidden(HiddenCell::Empty));
+        let reveal = |row, col| Play {
+            player: 0,
+            action: Action::Reveal,
+            point: BoardPoint { row, col },
+        };
+        let log = Vec::from([
+            (reveal(2, 2), PlayOutcome::Success(Vec::from(PLAY_1_RES))),
+            (reveal(3, 3), PlayOutcome::Failure(PLAY_4_RES)),
+            (reveal(2, 3), PlayOutcome::Success(Vec::from([PLAY_3_RES]))),
+        ]);
+        let mut replay = MinesweeperReplay::new(starting_board.clone(), log.clone(), 2);
+
+        let board = replay.seek_to_first_mine().unwrap().clone();
+        assert_eq!(replay.current_pos(), ReplayPosition::Other(2));
+        assert_eq!(replay.current_play().map(|p| p.point), Some(PLAY_4_RES.0));
+        assert_eq!(board[PLAY_4_RES.0], PlayerCell::Revealed(PLAY_4_RES.1));
+        assert_eq!(board[PLAY_3_RES.0], PlayerCell::Hidden(HiddenCell::Empty));
+
+        let board = replay.seek_to_end().clone();
+        assert_eq!(replay.current_pos(), ReplayPosition::End);
+        assert_eq!(board[PLAY_3_RES.0], PlayerCell::Revealed(PLAY_3_RES.1));
+        // back again from the end
+        assert!(replay.seek_to_first_mine().is_some());
+        assert_eq!(replay.current_pos(), ReplayPosition::Other(2));
+
+        let mut no_mines = MinesweeperReplay::new(starting_board, log[..1].to_vec(), 1);
+        assert!(no_mines.seek_to_first_mine().is_none());
+        assert_eq!(no_mines.current_pos(), ReplayPosition::Beginning);
+    }
+
+    #[test]
+    fn empty_victory_replays() {
+        // flag wins used to be logged as a victory revealing nothing
+        let starting_board = Board::new(4, 4, PlayerCell::Hidden(HiddenCell::Empty));
+        let flag = Play {
+            player: 1,
+            action: Action::Flag,
+            point: MINES[0],
+        };
+        let log = Vec::from([(flag, PlayOutcome::Victory(Vec::new()))]);
+        let mut replay = MinesweeperReplay::new(starting_board, log, 2);
+
+        replay.seek_to_end();
+        assert!(replay.current_players()[1].victory_click);
+        assert!(replay.rewind().is_ok());
+        assert!(!replay.current_players()[1].victory_click);
+    }
+
+    #[test]
+    fn marks_step_both_ways() {
+        let starting_board = Board::new(4, 4, PlayerCell::Hidden(HiddenCell::Empty));
+        let point = MINES[0];
+        let flag = Play {
+            player: 0,
+            action: Action::Flag,
+            point,
+        };
+        let marks = [
+            PlayerCell::Hidden(HiddenCell::Flag),
+            PlayerCell::Hidden(HiddenCell::Question),
+            PlayerCell::Hidden(HiddenCell::Empty),
+            PlayerCell::Hidden(HiddenCell::Flag),
+        ];
+        let log = marks
+            .iter()
+            .map(|mark| (flag, PlayOutcome::Flag((point, *mark))))
+            .collect();
+        let mut replay = MinesweeperReplay::new(starting_board, log, 1);
+
+        for mark in marks.iter() {
+            assert!(replay.advance().is_ok());
+            assert_eq!(replay.current_board()[point], *mark);
+        }
+        assert_eq!(replay.current_flags_and_revealed_mines(), 1);
+        for mark in marks.iter().rev().skip(1) {
+            assert!(replay.rewind().is_ok());
+            assert_eq!(replay.current_board()[point], *mark);
+        }
+        assert!(replay.rewind().is_ok());
+        assert_eq!(
+            replay.current_board()[point],
+            PlayerCell::Hidden(HiddenCell::Empty)
+        );
+        assert_eq!(replay.current_flags_and_revealed_mines(), 0);
+    }
 }
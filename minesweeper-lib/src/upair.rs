@@ -1,9 +1,10 @@
 use std::{
     cmp::Ordering,
-    fmt::Debug,
+    fmt::{Debug, Display},
     hash::{Hash, Hasher},
 };
 
+/// Pair of values where order doesn't matter - `a` is always the lesser of the two
 pub struct UnorderedPair<T> {
     a: T,
     b: T,
@@ -44,6 +45,12 @@ impl<T: Debug> Debug for UnorderedPair<T> {
     }
 }
 
+impl<T: Display> Display for UnorderedPair<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} / {}", self.a, self.b)
+    }
+}
+
 impl<T: Default + Ord> Default for UnorderedPair<T> {
     fn default() -> Self {
         Self::new(T::default(), T::default())
@@ -0,0 +1,118 @@
+//! JSON-in/JSON-out bindings for running the engine in a browser without a server.  Everything
+//! crossing the boundary is a JSON string in the same shape the `serde` derives produce, so the
+//! functions are plain Rust and can be exercised natively.
+
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+use crate::game::{Minesweeper, MinesweeperBuilder, MinesweeperOpts, Play};
+
+/// Largest number of rows or columns [`new_game`] accepts
+const MAX_SIDE: usize = 100;
+
+fn default_players() -> usize {
+    1
+}
+
+/// Settings accepted by [`new_game`]
+#[derive(Debug, Deserialize)]
+struct GameConfig {
+    rows: usize,
+    cols: usize,
+    num_mines: usize,
+    #[serde(default = "default_players")]
+    players: usize,
+    #[serde(default)]
+    seed: Option<u64>,
+}
+
+/// A game in progress, held by JavaScript as an opaque handle
+#[wasm_bindgen]
+pub struct WasmGame(Minesweeper);
+
+/// Starts a game from `{"rows", "cols", "num_mines", "players"?, "seed"?}`
+#[wasm_bindgen]
+pub fn new_game(config: &str) -> Result<WasmGame, String> {
+    let config: GameConfig = serde_json::from_str(config).map_err(|e| e.to_string())?;
+    if config.players == 0 {
+        return Err("A game needs at least one player".to_string());
+    }
+    if config.rows > MAX_SIDE || config.cols > MAX_SIDE {
+        return Err(format!(
+            "Board too large ({}x{}) - max is {MAX_SIDE}x{MAX_SIDE}",
+            config.rows, config.cols
+        ));
+    }
+    let opts = MinesweeperOpts {
+        rows: config.rows,
+        cols: config.cols,
+        num_mines: config.num_mines,
+    };
+    let mut builder = MinesweeperBuilder::new(opts)
+        .map_err(|e| e.to_string())?
+        .with_multiplayer(config.players);
+    if let Some(seed) = config.seed {
+        builder = builder.with_seed(seed);
+    }
+    Ok(WasmGame(builder.init()))
+}
+
+/// Applies a JSON `Play`, returning the JSON `PlayOutcome`
+#[wasm_bindgen]
+pub fn play(game: &mut WasmGame, play: &str) -> Result<String, String> {
+    let play: Play = serde_json::from_str(play).map_err(|e| e.to_string())?;
+    let outcome = game.0.play(play).map_err(|e| e.to_string())?;
+    serde_json::to_string(&outcome).map_err(|e| e.to_string())
+}
+
+/// JSON `Board<PlayerCell>` as seen by `player`
+#[wasm_bindgen]
+pub fn player_board(game: &WasmGame, player: usize) -> Result<String, String> {
+    if game.0.player_snapshot(player).is_none() {
+        return Err(format!("Player {player} doesn't exist"));
+    }
+    serde_json::to_string(&game.0.player_board(player)).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        board::{Board, BoardPoint},
+        cell::PlayerCell,
+        game::PlayOutcome,
+    };
+
+    #[test]
+    fn json_boundary() {
+        let mut game =
+            new_game(r#"{"rows":9,"cols":9,"num_mines":10,"seed":7}"#).expect("config is valid");
+
+        let reveal =
+            serde_json::to_string(&Play::reveal(0, BoardPoint { row: 4, col: 4 })).unwrap();
+        let outcome: PlayOutcome =
+            serde_json::from_str(&play(&mut game, &reveal).unwrap()).unwrap();
+        assert!(matches!(
+            outcome,
+            PlayOutcome::Success(_) | PlayOutcome::Victory(_)
+        ));
+
+        let board: Board<PlayerCell> =
+            serde_json::from_str(&player_board(&game, 0).unwrap()).unwrap();
+        assert_eq!(board.rows(), 9);
+        assert_eq!(board.cols(), 9);
+        assert!(matches!(
+            board[BoardPoint { row: 4, col: 4 }],
+            PlayerCell::Revealed(_)
+        ));
+
+        // same cell again is rejected, not a panic
+        assert!(play(&mut game, &reveal).is_err());
+        assert!(play(&mut game, "not json").is_err());
+        assert!(new_game(r#"{"rows":2,"cols":2,"num_mines":10}"#).is_err());
+        assert!(new_game(r#"{"rows":9}"#).is_err());
+        assert!(new_game(r#"{"rows":9,"cols":9,"num_mines":10,"players":0}"#).is_err());
+        assert!(new_game(r#"{"rows":100000,"cols":100000,"num_mines":10}"#).is_err());
+        assert!(player_board(&game, 1).is_err());
+    }
+}
@@ -0,0 +1,18 @@
+--- Create Daily Challenge ----
+create table if not exists daily_games
+(
+    game_id text not null primary key,
+    day     text not null,
+    FOREIGN KEY(game_id) REFERENCES games(game_id)
+);
+
+create table if not exists daily_results
+(
+    user    integer not null, -- users.id
+    day     text    not null,
+    game_id text    not null,
+    seconds integer not null,
+    PRIMARY KEY(user, day),
+    FOREIGN KEY(user) REFERENCES users(id),
+    FOREIGN KEY(game_id) REFERENCES games(game_id)
+);
@@ -14,3 +14,5 @@ pub use root::App;
 
 #[cfg(feature = "ssr")]
 pub use auth::{FrontendUser, OAuthTarget};
+#[cfg(feature = "ssr")]
+pub use minesweeper::{GameMode, GameSettings};
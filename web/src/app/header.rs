@@ -5,7 +5,7 @@ use leptos_router::components::*;
 
 use crate::{
     cell_class,
-    components::{dark_mode::DarkModeToggle, icons::Flag},
+    components::{dark_mode::DarkModeToggle, i18n::LangToggle, icons::Flag},
     number_class,
 };
 
@@ -85,6 +85,7 @@ pub fn Header(user: Resource<Option<FrontendUser>, JsonSerdeCodec>) -> impl Into
                     })}
 
                 </Transition>
+                <LangToggle />
                 <DarkModeToggle />
             </div>
         </header>
@@ -5,10 +5,11 @@ mod game;
 mod games;
 mod players;
 mod replay;
+mod ui_events;
 mod widgets;
 
 use chrono::{DateTime, Utc};
-pub use entry::{GameMode, JoinOrCreateGame};
+pub use entry::{GameMode, JoinOrCreateGame, LinkedGameView};
 pub use game::{GameView, GameWrapper, ReplayView};
 pub use games::{ActiveGames, RecentGames};
 
@@ -41,6 +42,8 @@ pub struct GameInfo {
     end_time: Option<DateTime<Utc>>,
     final_board: Board<PlayerCell>,
     players: Vec<Option<ClientPlayer>>,
+    /// Code for a link to play the same board - see `game_link_code`
+    link_code: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
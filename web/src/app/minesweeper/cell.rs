@@ -1,6 +1,7 @@
 use leptos::either::*;
 use leptos::prelude::*;
 use minesweeper_lib::{analysis::AnalyzedCell, replay::ReplayAnalysisCell};
+use serde::{Deserialize, Serialize};
 use web_sys::{MouseEvent, TouchEvent};
 
 use minesweeper_lib::{
@@ -14,6 +15,71 @@ use crate::{
     number_class, player_class,
 };
 
+/// Cells take their size from variables set on the board by `CellDensity::size_class`
+const BOARD_CELL_SIZE: &str =
+    "h-[var(--cell-size)] w-[var(--cell-size)] text-[length:var(--cell-text)]";
+
+/// Cell size classes - bigger boards get smaller cells so they fit without zooming out
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CellDensity {
+    #[default]
+    Comfortable,
+    Compact,
+    Dense,
+}
+
+impl CellDensity {
+    pub const ALL: [CellDensity; 3] = [Self::Comfortable, Self::Compact, Self::Dense];
+
+    /// Picks a size from the longer side of the board - beginner boards stay full size,
+    /// intermediate and expert step down
+    pub fn for_board(rows: usize, cols: usize) -> Self {
+        match rows.max(cols) {
+            ..=10 => Self::Comfortable,
+            11..=20 => Self::Compact,
+            _ => Self::Dense,
+        }
+    }
+
+    /// Sets the cell size for every cell inside the element it's applied to
+    pub fn size_class(self) -> &'static str {
+        match self {
+            Self::Comfortable => "[--cell-size:2rem] [--cell-text:1.5rem]",
+            Self::Compact => "[--cell-size:1.75rem] [--cell-text:1.25rem]",
+            Self::Dense => "[--cell-size:1.5rem] [--cell-text:1.125rem]",
+        }
+    }
+
+    /// Cell size in px at 100% zoom - matches `size_class`
+    pub fn cell_px(self) -> f64 {
+        match self {
+            Self::Comfortable => 32.0,
+            Self::Compact => 28.0,
+            Self::Dense => 24.0,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Comfortable => "Large",
+            Self::Compact => "Medium",
+            Self::Dense => "Small",
+        }
+    }
+
+    /// Cycles a user override: automatic, then each density from largest to smallest
+    pub fn next_override(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(Self::ALL[0]),
+            Some(density) => Self::ALL
+                .iter()
+                .position(|&d| d == density)
+                .and_then(|i| Self::ALL.get(i + 1))
+                .copied(),
+        }
+    }
+}
+
 fn cell_contents_class(cell: PlayerCell, active: bool) -> &'static str {
     match cell {
         PlayerCell::Hidden(HiddenCell::Flag) if !active => "bg-red-400/40",
@@ -51,36 +117,68 @@ fn cell_player_class(cell: PlayerCell) -> &'static str {
     }
 }
 
+fn cell_aria_label(row: usize, col: usize, cell: PlayerCell) -> String {
+    let state = match cell {
+        PlayerCell::Hidden(HiddenCell::Empty) => "hidden".to_string(),
+        PlayerCell::Hidden(HiddenCell::Flag) => "flagged".to_string(),
+        PlayerCell::Hidden(HiddenCell::Question) => "marked with question".to_string(),
+        PlayerCell::Hidden(HiddenCell::Mine) => "mine".to_string(),
+        PlayerCell::Hidden(HiddenCell::FlagMine) => "flagged mine".to_string(),
+        PlayerCell::Revealed(rc) => match rc.contents {
+            Cell::Mine => "exploded mine".to_string(),
+            Cell::Empty(0) => "revealed, empty".to_string(),
+            Cell::Empty(x) => format!("revealed, {}", x),
+        },
+    };
+    format!("Row {}, column {}: {}", row + 1, col + 1, state)
+}
+
 #[component]
-pub fn ActiveCell<F, F2, F3, F4>(
+pub fn ActiveCell<F, F2, F3, F4, F5>(
     row: usize,
     col: usize,
     cell: ReadSignal<PlayerCell>,
+    active: ReadSignal<BoardPoint>,
     set_active: WriteSignal<BoardPoint>,
     mousedown_handler: F,
     mouseup_handler: F2,
     touchstart_handler: F3,
-    touchend_handler: F4,
+    touchmove_handler: F4,
+    touchend_handler: F5,
 ) -> impl IntoView
 where
     F: Fn(MouseEvent, usize, usize) + Copy + 'static,
     F2: Fn(MouseEvent, usize, usize) + Copy + 'static,
     F3: Fn(TouchEvent, usize, usize) + Copy + 'static,
     F4: Fn(TouchEvent, usize, usize) + Copy + 'static,
+    F5: Fn(TouchEvent, usize, usize) + Copy + 'static,
 {
     let id = format!("{}_{}", row, col);
     let class = move || {
         let item = cell();
-        cell_class!(cell_contents_class(item, true), cell_player_class(item))
+        let focused = if active() == (BoardPoint { row, col }) {
+            "ring-2 ring-inset ring-sky-400"
+        } else {
+            ""
+        };
+        cell_class!(
+            size: BOARD_CELL_SIZE,
+            cell_contents_class(item, true),
+            format!("{} {}", cell_player_class(item), focused)
+        )
     };
+    let aria_label = move || cell_aria_label(row, col, cell());
 
     view! {
         <span
             class=class
             id=id
+            role="gridcell"
+            aria-label=aria_label
             on:mousedown=move |ev| mousedown_handler(ev, row, col)
             on:mouseup=move |ev| mouseup_handler(ev, row, col)
             on:touchstart=move |ev| touchstart_handler(ev, row, col)
+            on:touchmove=move |ev| touchmove_handler(ev, row, col)
             on:touchend=move |ev| touchend_handler(ev, row, col)
             on:touchcancel=move |ev| touchend_handler(ev, row, col)
             on:mouseenter=move |_| set_active(BoardPoint { row, col })
@@ -98,10 +196,21 @@ where
 #[component]
 pub fn InactiveCell(row: usize, col: usize, cell: PlayerCell) -> impl IntoView {
     let id = format!("{}_{}", row, col);
-    let class = cell_class!(cell_contents_class(cell, false), cell_player_class(cell));
+    let class = cell_class!(
+        size: BOARD_CELL_SIZE,
+        cell_contents_class(cell, false),
+        cell_player_class(cell)
+    );
+    let aria_label = cell_aria_label(row, col, cell);
 
     view! {
-        <span class=class id=id oncontextmenu="event.preventDefault();">
+        <span
+            class=class
+            id=id
+            role="gridcell"
+            aria-label=aria_label
+            oncontextmenu="event.preventDefault();"
+        >
             <CellContents cell />
         </span>
     }
@@ -112,7 +221,11 @@ pub fn ReplayCell(row: usize, col: usize, cell: ReadSignal<ReplayAnalysisCell>)
     let id = format!("{}_{}", row, col);
     let class = move || {
         let ReplayAnalysisCell(item, analysis) = cell();
-        cell_class!(cell_replay_class(item, analysis), cell_player_class(item))
+        cell_class!(
+            size: BOARD_CELL_SIZE,
+            cell_replay_class(item, analysis),
+            cell_player_class(item)
+        )
     };
 
     view! {
@@ -129,18 +242,18 @@ pub fn ReplayCell(row: usize, col: usize, cell: ReadSignal<ReplayAnalysisCell>)
 fn CellContents(cell: PlayerCell) -> impl IntoView {
     match cell {
         PlayerCell::Hidden(hc) => match hc {
-            HiddenCell::Empty => EitherOf7::A(view! { <span>""</span> }),
-            HiddenCell::Flag => EitherOf7::B(view! {
+            HiddenCell::Empty => EitherOf8::A(view! { <span>""</span> }),
+            HiddenCell::Flag => EitherOf8::B(view! {
                 <span class="flag">
                     <Flag />
                 </span>
             }),
-            HiddenCell::Mine => EitherOf7::C(view! {
+            HiddenCell::Mine => EitherOf8::C(view! {
                 <span>
                     <Mine />
                 </span>
             }),
-            HiddenCell::FlagMine => EitherOf7::D(view! {
+            HiddenCell::FlagMine => EitherOf8::D(view! {
                 <span class="block w-full h-full relative">
                     <span class="inline-block h-6 w-6 bottom-0 left-0 absolute">
                         <Mine />
@@ -150,15 +263,50 @@ fn CellContents(cell: PlayerCell) -> impl IntoView {
                     </span>
                 </span>
             }),
+            HiddenCell::Question => EitherOf8::E(view! { <span>"?"</span> }),
         },
         PlayerCell::Revealed(rc) => match rc.contents {
-            Cell::Mine => EitherOf7::E(view! {
+            Cell::Mine => EitherOf8::F(view! {
                 <span>
                     <Mine />
                 </span>
             }),
-            Cell::Empty(0) => EitherOf7::F(view! { <span></span> }),
-            Cell::Empty(n) => EitherOf7::G(view! { <span>{n}</span> }),
+            Cell::Empty(0) => EitherOf8::G(view! { <span></span> }),
+            Cell::Empty(n) => EitherOf8::H(view! { <span>{n}</span> }),
         },
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn density_shrinks_with_board() {
+        assert_eq!(CellDensity::for_board(9, 9), CellDensity::Comfortable);
+        assert_eq!(CellDensity::for_board(16, 16), CellDensity::Compact);
+        assert_eq!(CellDensity::for_board(16, 30), CellDensity::Dense);
+        assert_eq!(CellDensity::for_board(30, 16), CellDensity::Dense);
+        assert_eq!(CellDensity::for_board(10, 11), CellDensity::Compact);
+        assert_eq!(CellDensity::for_board(20, 21), CellDensity::Dense);
+    }
+
+    #[test]
+    fn density_override_cycles() {
+        let mut current = None;
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            current = CellDensity::next_override(current);
+            seen.push(current);
+        }
+        assert_eq!(
+            seen,
+            vec![
+                Some(CellDensity::Comfortable),
+                Some(CellDensity::Compact),
+                Some(CellDensity::Dense),
+                None
+            ]
+        );
+    }
+}
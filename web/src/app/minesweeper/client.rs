@@ -6,12 +6,15 @@ use minesweeper_lib::{
     board::{Board, BoardPoint},
     cell::{HiddenCell, PlayerCell},
     client::{ClientPlayer, MinesweeperClient},
-    game::{Action as PlayAction, Play},
+    game::Play,
 };
 
 use crate::messages::{ClientMessage, GameMessage};
 
-use super::GameInfo;
+use super::{
+    ui_events::{ui_event, UiEvent},
+    GameInfo,
+};
 
 #[derive(Clone)]
 pub struct FrontendGame {
@@ -27,6 +30,7 @@ pub struct FrontendGame {
     pub completed: ReadSignal<bool>,
     pub sync_time: ReadSignal<Option<usize>>,
     pub flag_count: ReadSignal<usize>,
+    pub ui_event: ReadSignal<Option<UiEvent>>,
     pub cells: Arc<Vec<Vec<ReadSignal<PlayerCell>>>>,
     cell_signals: Arc<Vec<Vec<WriteSignal<PlayerCell>>>>,
     set_player_id: WriteSignal<Option<usize>>,
@@ -36,6 +40,7 @@ pub struct FrontendGame {
     set_completed: WriteSignal<bool>,
     set_sync_time: WriteSignal<Option<usize>>,
     set_flag_count: WriteSignal<usize>,
+    set_ui_event: WriteSignal<Option<UiEvent>>,
     game: Arc<RwLock<MinesweeperClient>>,
     send: Arc<dyn Fn(&ClientMessage) + Send + Sync>,
 }
@@ -61,6 +66,7 @@ impl FrontendGame {
         let (completed, set_completed) = signal(game_info.is_completed);
         let (sync_time, set_sync_time) = signal::<Option<usize>>(None);
         let (flag_count, set_flag_count) = signal(0);
+        let (ui_event, set_ui_event) = signal::<Option<UiEvent>>(None);
         let rows = game_info.rows;
         let cols = game_info.cols;
         FrontendGame {
@@ -85,6 +91,8 @@ impl FrontendGame {
             set_sync_time,
             flag_count,
             set_flag_count,
+            ui_event,
+            set_ui_event,
             game: Arc::new(RwLock::new(MinesweeperClient::new(rows, cols))),
             send,
         }
@@ -113,11 +121,7 @@ impl FrontendGame {
         if let PlayerCell::Revealed(_) = game.board[&point] {
             bail!("Tried to click revealed cell")
         }
-        let play_message = ClientMessage::Play(Play {
-            player,
-            action: PlayAction::Reveal,
-            point,
-        });
+        let play_message = ClientMessage::Play(Play::reveal(player, point));
         self.send(play_message);
         Ok(())
     }
@@ -129,11 +133,7 @@ impl FrontendGame {
         if let PlayerCell::Revealed(_) = game.board[&point] {
             return Ok(());
         }
-        let play_message = ClientMessage::Play(Play {
-            player,
-            action: PlayAction::Flag,
-            point,
-        });
+        let play_message = ClientMessage::Play(Play::flag(player, point));
         self.send(play_message);
         Ok(())
     }
@@ -149,11 +149,7 @@ impl FrontendGame {
         if !game.neighbors_flagged(&point) {
             bail!("Tried to reveal adjacent with wrong number of flags")
         }
-        let play_message = ClientMessage::Play(Play {
-            player,
-            action: PlayAction::RevealAdjacent,
-            point,
-        });
+        let play_message = ClientMessage::Play(Play::chord(player, point));
         self.send(play_message);
         Ok(())
     }
@@ -161,16 +157,24 @@ impl FrontendGame {
     pub fn handle_message(&self, game_message: GameMessage) -> Result<()> {
         let game: &mut MinesweeperClient = &mut (*self.game).write().unwrap();
         match game_message {
+            GameMessage::GameMeta { .. } => {
+                // the board is already sized from the game info loaded with the page
+                Ok(())
+            }
             GameMessage::PlayerId(player_id) => {
                 (self.set_player_id)(Some(player_id));
                 Ok(())
             }
             GameMessage::PlayOutcome(po) => {
+                let event = ui_event(&po);
                 let plays = game.update(po);
                 plays.iter().for_each(|(point, cell)| {
                     log::debug!("Play outcome: {:?} {:?}", point, cell);
                     self.update_cell(*point, *cell);
                 });
+                if event.is_some() {
+                    (self.set_ui_event)(event);
+                }
                 if game.game_over {
                     (self.set_completed)(true);
                 }
@@ -181,21 +185,28 @@ impl FrontendGame {
                 self.player_signals[pu.player_id](Some(pu));
                 Ok(())
             }
+            GameMessage::ScoreDelta {
+                player_id,
+                new_score,
+                ..
+            } => {
+                game.add_or_update_player(player_id, Some(new_score), None);
+                if let Some(mut cp) = self.players[player_id].get_untracked() {
+                    cp.score = new_score;
+                    self.player_signals[player_id](Some(cp));
+                }
+                Ok(())
+            }
             GameMessage::Error(e) => Err(anyhow!(e)),
+            GameMessage::DoubleClickMismatch { expected, got } => {
+                Err(anyhow!("Needs {expected} flags, has {got}"))
+            }
             GameMessage::GameState(gs) => {
-                let old_board = game.player_board().clone();
+                let changed = game.player_board().diff(&gs);
                 game.set_state(gs);
-                game.player_board()
-                    .rows_iter()
-                    .zip(old_board.rows_iter())
-                    .enumerate()
-                    .for_each(|(row, (new, old))| {
-                        new.iter().enumerate().for_each(|(col, cell)| {
-                            if *cell != old[col] {
-                                (self.cell_signals[row][col])(*cell);
-                            }
-                        })
-                    });
+                changed.into_iter().for_each(|(point, cell)| {
+                    (self.cell_signals[point.row][point.col])(cell);
+                });
                 Ok(())
             }
             GameMessage::PlayersState(ps) => {
@@ -209,6 +220,15 @@ impl FrontendGame {
                 (self.set_players_loaded)(true);
                 Ok(())
             }
+            GameMessage::PlayerJoined(cp) => {
+                game.add_or_update_player(cp.player_id, Some(cp.score), Some(cp.dead));
+                self.player_signals[cp.player_id](Some(cp));
+                Ok(())
+            }
+            GameMessage::PlayerLeft { player_id } => {
+                log::debug!("Player left: {}", player_id);
+                Ok(())
+            }
             GameMessage::GameStarted => {
                 (self.set_started)(true);
                 Ok(())
@@ -217,13 +237,20 @@ impl FrontendGame {
                 (self.set_sync_time)(Some(secs));
                 Ok(())
             }
+            GameMessage::Ping => {
+                self.send(ClientMessage::Pong);
+                Ok(())
+            }
         }
     }
 
     pub fn update_cell(&self, point: BoardPoint, cell: PlayerCell) {
         let curr_cell = self.cells[point.row][point.col].get_untracked();
         match (curr_cell, cell) {
-            (PlayerCell::Hidden(HiddenCell::Flag), PlayerCell::Hidden(HiddenCell::Empty)) => {
+            (
+                PlayerCell::Hidden(HiddenCell::Flag),
+                PlayerCell::Hidden(HiddenCell::Empty | HiddenCell::Question),
+            ) => {
                 self.set_flag_count.update(|nm| *nm -= 1);
                 log::debug!("Removed flag")
             }
@@ -235,9 +262,10 @@ impl FrontendGame {
                 self.set_flag_count.update(|nm| *nm += 1);
                 log::debug!("Added flag")
             }
-            (PlayerCell::Hidden(HiddenCell::Empty), PlayerCell::Revealed(rc))
-                if rc.contents.is_mine() =>
-            {
+            (
+                PlayerCell::Hidden(HiddenCell::Empty | HiddenCell::Question),
+                PlayerCell::Revealed(rc),
+            ) if rc.contents.is_mine() => {
                 self.set_flag_count.update(|nm| *nm += 1);
                 log::debug!("Mine revealed")
             }
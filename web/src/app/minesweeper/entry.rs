@@ -1,10 +1,14 @@
+use chrono::Utc;
 use codee::string::JsonSerdeWasmCodec;
-use leptos::prelude::*;
+use leptos::{either::Either, prelude::*};
+use leptos_router::hooks::use_params_map;
 use leptos_use::storage::{use_local_storage, use_local_storage_with_options, UseStorageOptions};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsValue;
 
-use crate::{button_class, input_class};
+use minesweeper_lib::game::MinesweeperOpts;
+
+use crate::{button_class, components::i18n::t, input_class};
 
 use super::GameSettings;
 
@@ -21,6 +25,9 @@ pub enum GameMode {
     ClassicBeginner,
     ClassicIntermediate,
     ClassicExpert,
+    Tiny,
+    Evil,
+    Daily,
     SmallMultiplayer,
     LargeMultiplayer,
     Custom,
@@ -32,6 +39,9 @@ impl GameMode {
             Self::ClassicBeginner => "Beginner",
             Self::ClassicIntermediate => "Intermediate",
             Self::ClassicExpert => "Expert",
+            Self::Tiny => "Tiny",
+            Self::Evil => "Evil",
+            Self::Daily => "Daily",
             Self::SmallMultiplayer => "Small",
             Self::LargeMultiplayer => "Large",
             Self::Custom => "Custom",
@@ -43,11 +53,53 @@ impl GameMode {
             Self::ClassicBeginner => "Classic Beginner",
             Self::ClassicIntermediate => "Classic Intermediate",
             Self::ClassicExpert => "Classic Expert",
+            Self::Tiny => "Tiny",
+            Self::Evil => "Evil",
+            Self::Daily => "Daily Challenge",
             Self::SmallMultiplayer => "Multiplayer Small",
             Self::LargeMultiplayer => "Multiplayer Large",
             Self::Custom => "Custom",
         }
     }
+
+    /// Mode of a stored game - daily challenges have the same settings as expert games, so
+    /// they can only be told apart by `is_daily`
+    pub fn of_game(settings: GameSettings, is_daily: bool) -> Self {
+        if is_daily {
+            Self::Daily
+        } else {
+            Self::from(settings)
+        }
+    }
+
+    /// Short code used in shareable game links - custom games have none, and daily challenges
+    /// are only started through [`DailyChallenge`] so their seed stays on the server
+    pub fn to_code(self) -> Option<&'static str> {
+        match self {
+            Self::ClassicBeginner => Some("b"),
+            Self::ClassicIntermediate => Some("i"),
+            Self::ClassicExpert => Some("e"),
+            Self::Tiny => Some("t"),
+            Self::Evil => Some("v"),
+            Self::Daily => None,
+            Self::SmallMultiplayer => Some("s"),
+            Self::LargeMultiplayer => Some("l"),
+            Self::Custom => None,
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "b" => Some(Self::ClassicBeginner),
+            "i" => Some(Self::ClassicIntermediate),
+            "e" => Some(Self::ClassicExpert),
+            "t" => Some(Self::Tiny),
+            "v" => Some(Self::Evil),
+            "s" => Some(Self::SmallMultiplayer),
+            "l" => Some(Self::LargeMultiplayer),
+            _ => None,
+        }
+    }
 }
 
 impl Default for GameMode {
@@ -71,12 +123,24 @@ impl From<&GameMode> for GameSettings {
                 num_mines: 40,
                 max_players: 1,
             },
-            GameMode::ClassicExpert => Self {
+            GameMode::ClassicExpert | GameMode::Daily => Self {
                 rows: 16,
                 cols: 30,
                 num_mines: 99,
                 max_players: 1,
             },
+            GameMode::Tiny => Self {
+                rows: 5,
+                cols: 5,
+                num_mines: 4,
+                max_players: 1,
+            },
+            GameMode::Evil => Self {
+                rows: 20,
+                cols: 30,
+                num_mines: 130,
+                max_players: 1,
+            },
             GameMode::SmallMultiplayer => Self {
                 rows: 16,
                 cols: 30,
@@ -116,6 +180,18 @@ impl From<&GameSettings> for GameMode {
                 num_mines: 99,
                 max_players: 1,
             } => Self::ClassicExpert,
+            GameSettings {
+                rows: 5,
+                cols: 5,
+                num_mines: 4,
+                max_players: 1,
+            } => Self::Tiny,
+            GameSettings {
+                rows: 20,
+                cols: 30,
+                num_mines: 130,
+                max_players: 1,
+            } => Self::Evil,
             GameSettings {
                 rows: 16,
                 cols: 30,
@@ -155,6 +231,51 @@ fn validate_num_players(num_players: i64) -> bool {
     num_players > 0 && num_players <= 12
 }
 
+/// Code for a link to a single player game planted from `seed` - `{mode}-{seed}`, or
+/// `{rows}x{cols}x{mines}-{seed}` for boards without a mode code (seed in hex)
+pub fn game_link_code(opts: &MinesweeperOpts, seed: u64) -> String {
+    let settings = GameSettings {
+        rows: opts.rows as i64,
+        cols: opts.cols as i64,
+        num_mines: opts.num_mines as i64,
+        max_players: 1,
+    };
+    match GameMode::from(&settings).to_code() {
+        Some(code) => format!("{}-{:x}", code, seed),
+        None => format!("{}x{}x{}-{:x}", opts.rows, opts.cols, opts.num_mines, seed),
+    }
+}
+
+/// Parses a full `/play/` URL or just its code (see [`game_link_code`])
+pub fn parse_game_link(link: &str) -> Option<(MinesweeperOpts, u64)> {
+    let code = link.trim_end_matches('/').rsplit('/').next()?;
+    let (board, seed) = code.rsplit_once('-')?;
+    let seed = u64::from_str_radix(seed, 16).ok()?;
+    let (rows, cols, num_mines) = match GameMode::from_code(board) {
+        Some(mode) => {
+            let settings = GameSettings::from(&mode);
+            (settings.rows, settings.cols, settings.num_mines)
+        }
+        None => {
+            let mut parts = board.split('x').map(|d| d.parse::<i64>().ok());
+            let dims = (parts.next()??, parts.next()??, parts.next()??);
+            if parts.next().is_some() {
+                return None;
+            }
+            dims
+        }
+    };
+    if !(validate_rows(rows) && validate_cols(cols) && validate_num_mines(rows, cols, num_mines)) {
+        return None;
+    }
+    let opts = MinesweeperOpts {
+        rows: rows as usize,
+        cols: cols as usize,
+        num_mines: num_mines as usize,
+    };
+    Some((opts, seed))
+}
+
 #[server]
 async fn new_game(
     rows: i64,
@@ -185,6 +306,35 @@ async fn new_game(
                 num_mines,
                 max_players: max_players as u8,
             },
+            None,
+        )
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    leptos_axum::redirect(&format!("/game/{}", id));
+    Ok(())
+}
+
+#[server]
+async fn new_linked_game(link: String) -> Result<(), ServerFnError> {
+    let auth_session = use_context::<AuthSession>()
+        .ok_or_else(|| ServerFnError::new("Unable to find auth session".to_string()))?;
+    let game_manager = use_context::<GameManager>()
+        .ok_or_else(|| ServerFnError::new("No game manager".to_string()))?;
+    let (opts, seed) = parse_game_link(&link)
+        .ok_or_else(|| ServerFnError::new("Invalid game link.".to_string()))?;
+
+    let id = nanoid!(12);
+    game_manager
+        .new_game(
+            auth_session.user,
+            &id,
+            GameParameters {
+                rows: opts.rows as i64,
+                cols: opts.cols as i64,
+                num_mines: opts.num_mines as i64,
+                max_players: 1,
+            },
+            Some(seed),
         )
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
@@ -192,6 +342,49 @@ async fn new_game(
     Ok(())
 }
 
+#[server]
+async fn new_daily_game() -> Result<(), ServerFnError> {
+    let auth_session = use_context::<AuthSession>()
+        .ok_or_else(|| ServerFnError::new("Unable to find auth session".to_string()))?;
+    let game_manager = use_context::<GameManager>()
+        .ok_or_else(|| ServerFnError::new("No game manager".to_string()))?;
+    let settings = GameSettings::from(&GameMode::Daily);
+
+    let id = nanoid!(12);
+    game_manager
+        .new_daily_game(
+            auth_session.user,
+            &id,
+            GameParameters {
+                rows: settings.rows,
+                cols: settings.cols,
+                num_mines: settings.num_mines,
+                max_players: settings.max_players as u8,
+            },
+            Utc::now().date_naive(),
+        )
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    leptos_axum::redirect(&format!("/game/{}", id));
+    Ok(())
+}
+
+/// Signed in user's fastest win of today's daily challenge, in seconds
+#[server]
+async fn get_daily_best() -> Result<Option<i64>, ServerFnError> {
+    let auth_session = use_context::<AuthSession>()
+        .ok_or_else(|| ServerFnError::new("Unable to find auth session".to_string()))?;
+    let game_manager = use_context::<GameManager>()
+        .ok_or_else(|| ServerFnError::new("No game manager".to_string()))?;
+    let Some(user) = auth_session.user else {
+        return Ok(None);
+    };
+    game_manager
+        .get_daily_best(user.id, Utc::now().date_naive())
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
 #[server]
 async fn join_game(game_id: String) -> Result<(), ServerFnError> {
     let game_manager = use_context::<GameManager>()
@@ -217,6 +410,7 @@ pub fn PresetButtons(
         GameMode::ClassicIntermediate,
         GameMode::ClassicExpert,
     ];
+    let extra_modes = [GameMode::Tiny, GameMode::Evil];
 
     let class_signal = move |mode: GameMode| {
         let selected = selected.get();
@@ -241,7 +435,7 @@ pub fn PresetButtons(
                     }
                 >
 
-                    {mode.short_name()}
+                    {move || t(mode.short_name())}
                 </button>
             </div>
         }
@@ -253,6 +447,7 @@ pub fn PresetButtons(
                 "Singleplayer Presets"
             </div>
             <div class="flex w-full space-x-2">{classic_modes.map(mode_button).collect_view()}</div>
+            <div class="flex w-full space-x-2">{extra_modes.map(mode_button).collect_view()}</div>
         </div>
         <div class="w-full space-y-2">
             <div class="flex-none w-full text-md font-medium leading-none peer-disabled:cursor-not-allowed peer-disabled:opacity-70 text-neutral-950 dark:text-neutral-50">
@@ -545,10 +740,51 @@ pub fn JoinOrCreateGame() -> impl IntoView {
                     <div class="text-red-600 w-full">"Game does not exist"</div>
                 </Show>
             </ActionForm>
+            <div class="w-full max-w-xs h-6">
+                <span class="w-full h-full inline-flex items-center justify-center text-lg font-medium text-gray-800 dark:text-gray-200">
+                    <span>"-- or --"</span>
+                </span>
+            </div>
+            <DailyChallenge />
         </div>
     }
 }
 
+#[component]
+pub fn DailyChallenge() -> impl IntoView {
+    let new_daily_game = ServerAction::<NewDailyGame>::new();
+    let daily_best = Resource::new(|| (), |_| async { get_daily_best().await });
+    let today = Utc::now().date_naive();
+
+    view! {
+        <ActionForm action=new_daily_game attr:class="w-full max-w-xs space-y-2">
+            <button
+                type="submit"
+                class=button_class!(
+                    "w-full max-w-xs h-12", "text-neutral-50 bg-green-700 hover:bg-green-800/90"
+                )
+                disabled=new_daily_game.pending()
+            >
+                {format!("Daily Challenge {}", today.format("%Y-%m-%d"))}
+            </button>
+            <Transition>
+                {move || {
+                    daily_best
+                        .get()
+                        .and_then(|best| best.ok().flatten())
+                        .map(|seconds| {
+                            view! {
+                                <div class="w-full text-center text-gray-700 dark:text-gray-300">
+                                    {format!("Today's best: {} seconds", seconds)}
+                                </div>
+                            }
+                        })
+                }}
+            </Transition>
+        </ActionForm>
+    }
+}
+
 #[component]
 pub fn ReCreateGame(game_settings: GameSettings) -> impl IntoView {
     let new_game = ServerAction::<NewGame>::new();
@@ -574,3 +810,128 @@ pub fn ReCreateGame(game_settings: GameSettings) -> impl IntoView {
         </div>
     }
 }
+
+#[component]
+pub fn LinkedGameView() -> impl IntoView {
+    let params = use_params_map();
+    let link = move || params.get().get("link").unwrap_or_default();
+    let new_linked_game = ServerAction::<NewLinkedGame>::new();
+
+    let linked_game = move || match parse_game_link(&link()) {
+        Some((opts, seed)) => Either::Left(view! {
+            <div class="text-lg text-gray-900 dark:text-gray-200">
+                {format!(
+                    "{} x {} board with {} mines ({})",
+                    opts.rows,
+                    opts.cols,
+                    opts.num_mines,
+                    game_link_code(&opts, seed),
+                )}
+            </div>
+            <ActionForm action=new_linked_game attr:class="w-full max-w-xs space-y-2">
+                <input type="hidden" name="link" prop:value=link />
+                <button
+                    type="submit"
+                    class=button_class!(
+                        "w-full max-w-xs h-8",
+                        "bg-green-700 hover:bg-green-800/90 text-white"
+                    )
+                    disabled=new_linked_game.pending()
+                >
+                    "Play This Board"
+                </button>
+            </ActionForm>
+        }),
+        None => Either::Right(view! { <div class="text-red-600">"Invalid game link"</div> }),
+    };
+
+    view! {
+        <div class="flex-1 flex flex-col items-center text-center py-8 space-y-4">
+            <h1 class="text-4xl my-4 text-gray-900 dark:text-gray-200">"Shared Game"</h1>
+            {linked_game}
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn game_mode_code_round_trip() {
+        for mode in [
+            GameMode::ClassicBeginner,
+            GameMode::ClassicIntermediate,
+            GameMode::ClassicExpert,
+            GameMode::Tiny,
+            GameMode::Evil,
+            GameMode::SmallMultiplayer,
+            GameMode::LargeMultiplayer,
+        ] {
+            assert_eq!(GameMode::from_code(mode.to_code().unwrap()), Some(mode));
+        }
+        assert_eq!(GameMode::Custom.to_code(), None);
+        assert_eq!(GameMode::Daily.to_code(), None);
+    }
+
+    #[test]
+    fn extra_preset_settings() {
+        let tiny = GameSettings::from(GameMode::Tiny);
+        assert_eq!(
+            (tiny.rows, tiny.cols, tiny.num_mines, tiny.max_players),
+            (5, 5, 4, 1)
+        );
+        let evil = GameSettings::from(GameMode::Evil);
+        assert_eq!(
+            (evil.rows, evil.cols, evil.num_mines, evil.max_players),
+            (20, 30, 130, 1)
+        );
+        for mode in [GameMode::Tiny, GameMode::Evil] {
+            let settings = GameSettings::from(mode);
+            assert_eq!(GameMode::from(&settings), mode);
+            assert!(validate_num_mines(
+                settings.rows,
+                settings.cols,
+                settings.num_mines
+            ));
+        }
+        // classic presets are unchanged
+        let expert = GameSettings::from(GameMode::ClassicExpert);
+        assert_eq!((expert.rows, expert.cols, expert.num_mines), (16, 30, 99));
+    }
+
+    #[test]
+    fn game_link_round_trip() {
+        let expert = MinesweeperOpts {
+            rows: 16,
+            cols: 30,
+            num_mines: 99,
+        };
+        let custom = MinesweeperOpts {
+            rows: 20,
+            cols: 40,
+            num_mines: 150,
+        };
+        assert_eq!(game_link_code(&expert, 255), "e-ff");
+        assert_eq!(game_link_code(&custom, 255), "20x40x150-ff");
+        for (opts, seed) in [(expert, 255), (custom, u64::MAX)] {
+            let code = game_link_code(&opts, seed);
+            assert_eq!(parse_game_link(&code), Some((opts, seed)));
+            let url = format!("https://example.com/play/{}", code);
+            assert_eq!(parse_game_link(&url), Some((opts, seed)));
+        }
+    }
+
+    #[test]
+    fn parse_game_link_rejects_invalid() {
+        assert_eq!(parse_game_link("x-ff"), None);
+        assert_eq!(parse_game_link("e"), None);
+        assert_eq!(parse_game_link("e-zz"), None);
+        assert_eq!(parse_game_link("9x9-ff"), None);
+        assert_eq!(parse_game_link("9x9x10x1-ff"), None);
+        assert_eq!(parse_game_link("9x9x81-ff"), None);
+        assert_eq!(parse_game_link("101x9x10-ff"), None);
+        // daily seeds never leave the server
+        assert_eq!(parse_game_link("d-ff"), None);
+    }
+}
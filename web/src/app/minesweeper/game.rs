@@ -1,12 +1,14 @@
-use chrono::DateTime;
 use codee::string::JsonSerdeWasmCodec;
 use leptos::{either::*, ev, prelude::*};
 use leptos_meta::*;
 use leptos_router::{components::*, hooks::*};
 use leptos_use::{
-    core::ConnectionReadyState, use_document, use_event_listener, use_websocket, UseWebSocketReturn,
+    core::ConnectionReadyState,
+    storage::{use_local_storage_with_options, UseStorageOptions},
+    use_document, use_event_listener, use_websocket, UseWebSocketReturn,
 };
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
+use wasm_bindgen::JsValue;
 use web_sys::{KeyboardEvent, MouseEvent, TouchEvent};
 
 use minesweeper_lib::{
@@ -18,19 +20,25 @@ use minesweeper_lib::{
 };
 
 use super::{
-    cell::{ActiveCell, InactiveCell, ReplayCell},
+    cell::{ActiveCell, CellDensity, InactiveCell, ReplayCell},
     client::FrontendGame,
     entry::ReCreateGame,
     players::{ActivePlayers, InactivePlayers, PlayerButtons},
     replay::{OpenReplay, ReplayControls},
-    widgets::{ActiveMines, ActiveTimer, CopyGameLink, GameWidgets, InactiveMines, InactiveTimer},
+    widgets::{
+        game_time_from_start_end, ActiveMines, ActiveTimer, CopyBoardLink, CopyGameLink,
+        GameWidgets, InactiveMines, InactiveTimer,
+    },
     {GameInfo, GameInfoWithLog, GameSettings},
 };
 
+#[cfg(feature = "ssr")]
+use super::entry::game_link_code;
 #[cfg(feature = "ssr")]
 use crate::backend::{AuthSession, GameManager};
 use crate::{
     button_class,
+    components::i18n::t,
     messages::{ClientMessage, GameMessage},
 };
 #[cfg(feature = "ssr")]
@@ -51,6 +59,10 @@ pub async fn get_game(game_id: String) -> Result<GameInfo, ServerFnError> {
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
     let game_log = game_manager.get_game_log(&game_id).await.ok();
+    let link_code = game_manager
+        .get_board_link(&game)
+        .await
+        .map(|(opts, seed)| game_link_code(&opts, seed));
     // we have all the data we need
 
     let is_owner = if let Some(user) = &auth_session.user {
@@ -114,6 +126,7 @@ pub async fn get_game(game_id: String) -> Result<GameInfo, ServerFnError> {
         end_time: game.end_time,
         final_board,
         players: players_frontend,
+        link_code,
     })
 }
 
@@ -134,6 +147,10 @@ pub async fn get_replay(game_id: String) -> Result<GameInfoWithLog, ServerFnErro
     if game.final_board.is_none() {
         return Err(ServerFnError::new("Game missing board data".to_string()));
     }
+    let link_code = game_manager
+        .get_board_link(&game)
+        .await
+        .map(|(opts, seed)| game_link_code(&opts, seed));
     let game_board = game.final_board.unwrap();
     let players = game_manager
         .get_players(&game_id)
@@ -192,6 +209,7 @@ pub async fn get_replay(game_id: String) -> Result<GameInfoWithLog, ServerFnErro
             end_time: game.end_time,
             final_board,
             players: players_frontend,
+            link_code,
         },
         player_num,
         log,
@@ -280,17 +298,126 @@ pub fn ReplayView() -> impl IntoView {
 }
 
 #[component]
-fn GameBorder<F>(set_active: F, children: Children) -> impl IntoView
+fn GameBorder<F>(set_active: F, rows: usize, cols: usize, children: Children) -> impl IntoView
 where
     F: Fn(bool) + Copy + 'static,
 {
+    let storage_options = UseStorageOptions::<Option<f64>, serde_json::Error, JsValue>::default()
+        .delay_during_hydration(true);
+    let (stored_zoom, set_stored_zoom, _) =
+        use_local_storage_with_options::<Option<f64>, JsonSerdeWasmCodec>(
+            format!("board_zoom_{}x{}", rows, cols),
+            storage_options,
+        );
+    let density_options =
+        UseStorageOptions::<Option<CellDensity>, serde_json::Error, JsValue>::default()
+            .delay_during_hydration(true);
+    let (stored_density, set_stored_density, _) = use_local_storage_with_options::<
+        Option<CellDensity>,
+        JsonSerdeWasmCodec,
+    >("board_density", density_options);
+    let density = Signal::derive(move || {
+        stored_density
+            .get()
+            .unwrap_or_else(|| CellDensity::for_board(rows, cols))
+    });
+    let (fit, set_fit) = signal(1.0);
+    let (pinch, set_pinch) = signal(None::<(f64, f64)>);
+
+    Effect::new(move |_| {
+        let window = window();
+        let width = window.inner_width().ok().and_then(|w| w.as_f64());
+        let height = window.inner_height().ok().and_then(|h| h.as_f64());
+        if let (Some(width), Some(height)) = (width, height) {
+            set_fit(fit_zoom(rows, cols, density.get().cell_px(), width, height));
+        }
+    });
+
+    let zoom = move || stored_zoom.get().unwrap_or_else(|| fit.get());
+    let set_zoom = move |zoom: f64| set_stored_zoom(Some(clamp_zoom(zoom)));
+    let pinch_distance = |ev: &TouchEvent| {
+        let touches = ev.touches();
+        if touches.length() != 2 {
+            return None;
+        }
+        let (a, b) = (touches.get(0)?, touches.get(1)?);
+        Some(f64::from(a.client_x() - b.client_x()).hypot(f64::from(a.client_y() - b.client_y())))
+    };
+
     view! {
+        <div class="flex justify-center gap-1 mb-2">
+            <button
+                type="button"
+                class=button_class!(
+                    "w-10 h-8 rounded-l-md", "bg-neutral-700 hover:bg-neutral-800/90 text-white"
+                )
+                on:click=move |_| set_zoom(zoom() - ZOOM_STEP)
+            >
+                "-"
+            </button>
+            <button
+                type="button"
+                class=button_class!("h-8", "bg-neutral-700 hover:bg-neutral-800/90 text-white")
+                on:click=move |_| set_stored_zoom(None)
+            >
+                {move || format!("{:.0}%", zoom() * 100.0)}
+            </button>
+            <button
+                type="button"
+                class=button_class!(
+                    "w-10 h-8 rounded-r-md", "bg-neutral-700 hover:bg-neutral-800/90 text-white"
+                )
+                on:click=move |_| set_zoom(zoom() + ZOOM_STEP)
+            >
+                "+"
+            </button>
+            <button
+                type="button"
+                class=button_class!(
+                    "h-8 rounded-md", "bg-neutral-700 hover:bg-neutral-800/90 text-white"
+                )
+                title="Cell size"
+                on:click=move |_| {
+                    set_stored_density(CellDensity::next_override(stored_density.get_untracked()))
+                }
+            >
+                {move || stored_density.get().map_or("Auto", CellDensity::label)}
+            </button>
+        </div>
         <div class="select-none overflow-x-auto overflow-y-hidden mb-8">
-            <div class="w-fit border-solid border border-black mx-auto">
+            <div
+                class="w-fit border-solid border border-black mx-auto [zoom:var(--board-zoom)]"
+                style=move || format!("--board-zoom: {}", zoom())
+            >
                 <div
-                    class="w-fit border-groove border-24 bg-gray-900"
+                    class=move || {
+                        format!(
+                            "w-fit border-groove border-24 bg-gray-900 focus:outline-none {}",
+                            density.get().size_class(),
+                        )
+                    }
+                    role="grid"
+                    tabindex="0"
                     on:mouseenter=move |_| set_active(true)
                     on:mouseleave=move |_| set_active(false)
+                    on:focus=move |_| set_active(true)
+                    on:blur=move |_| set_active(false)
+                    on:touchstart=move |ev| {
+                        if let Some(distance) = pinch_distance(&ev) {
+                            set_pinch(Some((distance, zoom())));
+                        }
+                    }
+                    on:touchmove=move |ev| {
+                        let Some((start_distance, start_zoom)) = pinch.get_untracked() else {
+                            return;
+                        };
+                        if let Some(distance) = pinch_distance(&ev) {
+                            ev.prevent_default();
+                            set_zoom(start_zoom * distance / start_distance);
+                        }
+                    }
+                    on:touchend=move |_| set_pinch(None)
+                    on:touchcancel=move |_| set_pinch(None)
                 >
                     {children()}
                 </div>
@@ -322,6 +449,7 @@ where
     let sync_time = game.sync_time;
     let join_trigger = game.join_trigger;
     let players = Arc::clone(&game.players);
+    provide_context(game.ui_event);
 
     let game = StoredValue::new(game);
 
@@ -380,7 +508,7 @@ where
     let (skip_mouseup, set_skip_mouseup) = signal::<usize>(0);
     let (game_is_active, set_game_is_active) = signal(false);
     let (active_cell, set_active_cell) = signal(BoardPoint { row: 0, col: 0 });
-    let (touch_timer, set_touch_timer) = signal(None::<TimeoutHandle>);
+    let (touch_start, set_touch_start) = signal(None::<TouchStart>);
 
     let handle_action = move |pa: PlayAction, row: usize, col: usize| {
         game.with_value(|game| {
@@ -393,23 +521,22 @@ where
         })
     };
 
+    let (rows, cols) = (game_info.rows, game_info.cols);
     let handle_keydown = move |ev: KeyboardEvent| {
         if !game_is_active.get_untracked() {
             return;
         }
-        let BoardPoint { row, col } = active_cell.get_untracked();
-        match ev.key().as_str() {
-            " " => {
+        let cursor = active_cell.get_untracked();
+        match key_command(&ev.key(), cursor, rows, cols) {
+            Some(KeyCommand::Move(point)) => {
                 ev.prevent_default();
-                handle_action(PlayAction::Reveal, row, col);
+                set_active_cell(point);
             }
-            "d" => {
-                handle_action(PlayAction::RevealAdjacent, row, col);
-            }
-            "f" => {
-                handle_action(PlayAction::Flag, row, col);
+            Some(KeyCommand::Play(pa)) => {
+                ev.prevent_default();
+                handle_action(pa, cursor.row, cursor.col);
             }
-            _ => {}
+            None => {}
         }
     };
     let _ = use_event_listener(use_document(), ev::keydown, handle_keydown);
@@ -434,37 +561,66 @@ where
         }
     };
 
-    let handle_touchstart = move |_: TouchEvent, row: usize, col: usize| {
-        let res = set_timeout_with_handle(
-            move || {
-                handle_action(PlayAction::Flag, row, col);
-                set_touch_timer(None);
-            },
-            Duration::from_millis(200),
-        );
-        if let Ok(t) = res {
-            set_touch_timer(Some(t));
+    let handle_touchstart = move |ev: TouchEvent, _: usize, _: usize| {
+        // a second finger means a pinch-zoom, not a press
+        if ev.touches().length() > 1 {
+            set_touch_start(None);
+            return;
         }
+        let Some(touch) = ev.touches().get(0) else {
+            return;
+        };
+        set_touch_start(Some(TouchStart {
+            time: ev.time_stamp(),
+            x: touch.client_x(),
+            y: touch.client_y(),
+        }));
     };
 
-    let handle_touchend = move |_: TouchEvent, _: usize, _: usize| {
-        let timer = touch_timer.get_untracked();
-        if let Some(t) = timer {
-            t.clear();
-            set_touch_timer(None);
+    let handle_touchmove = move |ev: TouchEvent, _: usize, _: usize| {
+        let Some(start) = touch_start.get_untracked() else {
+            return;
+        };
+        let Some(touch) = ev.touches().get(0) else {
+            return;
+        };
+        if start.distance_to(touch.client_x(), touch.client_y()) > TOUCH_MOVE_TOLERANCE {
+            set_touch_start(None);
         }
     };
 
-    let active_cell = move |row: usize, col: usize, cell: ReadSignal<PlayerCell>| {
+    let handle_touchend = move |ev: TouchEvent, row: usize, col: usize| {
+        let Some(start) = touch_start.get_untracked() else {
+            return;
+        };
+        set_touch_start(None);
+        if ev.type_() == "touchcancel" {
+            return;
+        }
+        let Some(touch) = ev.changed_touches().get(0) else {
+            return;
+        };
+        let held = ev.time_stamp() - start.time;
+        let moved = start.distance_to(touch.client_x(), touch.client_y());
+        if let Some(pa) = touch_action(held, moved) {
+            // keep the browser from also sending emulated mouse events
+            ev.prevent_default();
+            handle_action(pa, row, col);
+        }
+    };
+
+    let cell_view = move |row: usize, col: usize, cell: ReadSignal<PlayerCell>| {
         view! {
             <ActiveCell
                 row=row
                 col=col
                 cell=cell
+                active=active_cell
                 set_active=set_active_cell
                 mousedown_handler=handle_mousedown
                 mouseup_handler=handle_mouseup
                 touchstart_handler=handle_touchstart
+                touchmove_handler=handle_touchmove
                 touchend_handler=handle_touchend
             />
         }
@@ -476,7 +632,7 @@ where
                     .iter()
                     .copied()
                     .enumerate()
-                    .map(move |(col, cell)| { active_cell(row, col, cell) })
+                    .map(move |(col, cell)| { cell_view(row, col, cell) })
                     .collect_view()}
             </div>
         }
@@ -492,7 +648,7 @@ where
             <CopyGameLink game_id=game_info.game_id />
             <ActiveTimer sync_time completed />
         </GameWidgets>
-        <GameBorder set_active=set_game_is_active>{cells}</GameBorder>
+        <GameBorder set_active=set_game_is_active rows=game_info.rows cols=game_info.cols>{cells}</GameBorder>
         <div class="text-red-600 h-8">{error}</div>
     }
 }
@@ -503,10 +659,7 @@ fn InactiveGame(game_info: GameInfo) -> impl IntoView {
     let game_time = game_time_from_start_end(game_info.start_time, game_info.end_time);
     let num_mines = game_info
         .final_board
-        .rows_iter()
-        .flatten()
-        .filter(|&c| matches!(c, PlayerCell::Hidden(HiddenCell::Mine)))
-        .count();
+        .count_where(|c| matches!(c, PlayerCell::Hidden(HiddenCell::Mine)));
     let is_victory = game_info
         .players
         .iter()
@@ -533,14 +686,23 @@ fn InactiveGame(game_info: GameInfo) -> impl IntoView {
     view! {
         <InactivePlayers
             players=game_info.players
-            title=if is_victory { "Complete" } else { "Game Over" }
+            title=t(if is_victory { "Complete" } else { "Game Over" })
         />
         <GameWidgets>
             <InactiveMines num_mines=num_mines />
             <CopyGameLink game_id=game_info.game_id />
             <InactiveTimer game_time />
         </GameWidgets>
-        <GameBorder set_active=move |_| {}>{cells}</GameBorder>
+        <GameBorder set_active=move |_| {} rows=game_info.rows cols=game_info.cols>{cells}</GameBorder>
+        {game_info
+            .link_code
+            .map(|link_code| {
+                view! {
+                    <div class="flex justify-center mb-2">
+                        <CopyBoardLink link_code />
+                    </div>
+                }
+            })}
         <ReCreateGame game_settings />
         <OpenReplay />
     }
@@ -606,7 +768,7 @@ fn ReplayGame(replay_data: GameInfoWithLog) -> impl IntoView {
             <CopyGameLink game_id=game_info.game_id />
             <InactiveTimer game_time />
         </GameWidgets>
-        <GameBorder set_active=move |_| ()>{cells}</GameBorder>
+        <GameBorder set_active=move |_| () rows=game_info.rows cols=game_info.cols>{cells}</GameBorder>
         <Show
             when=replay_started
             fallback=move || {
@@ -656,12 +818,161 @@ fn ReplayGame(replay_data: GameInfoWithLog) -> impl IntoView {
     }
 }
 
-fn game_time_from_start_end<T: chrono::TimeZone>(
-    start_time: Option<DateTime<T>>,
-    end_time: Option<DateTime<T>>,
-) -> usize {
-    (match (start_time, end_time) {
-        (Some(st), Some(et)) => et.signed_duration_since(st).num_seconds(),
-        _ => 999,
-    }) as usize
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KeyCommand {
+    Move(BoardPoint),
+    Play(PlayAction),
+}
+
+/// Maps a keyboard key to a cursor move (clamped to the board) or a play on the cursor
+fn key_command(key: &str, cursor: BoardPoint, rows: usize, cols: usize) -> Option<KeyCommand> {
+    let BoardPoint { row, col } = cursor;
+    let move_to = |row, col| Some(KeyCommand::Move(BoardPoint { row, col }));
+    match key {
+        "ArrowUp" => move_to(row.saturating_sub(1), col),
+        "ArrowDown" => move_to((row + 1).min(rows - 1), col),
+        "ArrowLeft" => move_to(row, col.saturating_sub(1)),
+        "ArrowRight" => move_to(row, (col + 1).min(cols - 1)),
+        " " => Some(KeyCommand::Play(PlayAction::Reveal)),
+        "d" => Some(KeyCommand::Play(PlayAction::RevealAdjacent)),
+        "f" => Some(KeyCommand::Play(PlayAction::Flag)),
+        _ => None,
+    }
+}
+
+const ZOOM_STEP: f64 = 0.1;
+const MIN_ZOOM: f64 = 0.3;
+const MAX_ZOOM: f64 = 2.0;
+/// Room taken around the cells by the board border and page padding
+const BOARD_MARGIN_PX: f64 = 64.0;
+
+fn clamp_zoom(zoom: f64) -> f64 {
+    (zoom.clamp(MIN_ZOOM, MAX_ZOOM) * 100.0).round() / 100.0
+}
+
+/// Largest zoom (never above 100%, in `ZOOM_STEP`s) at which the board fits the container
+fn fit_zoom(rows: usize, cols: usize, cell_px: f64, width: f64, height: f64) -> f64 {
+    let fit_width = (width - BOARD_MARGIN_PX) / (cols as f64 * cell_px);
+    let fit_height = (height - BOARD_MARGIN_PX) / (rows as f64 * cell_px);
+    let steps = (fit_width.min(fit_height) / ZOOM_STEP + 1e-9).floor();
+    clamp_zoom((steps * ZOOM_STEP).min(1.0))
+}
+
+/// Touches held at least this long flag instead of reveal
+const LONG_PRESS_MS: f64 = 400.0;
+/// Touches that move further than this (in CSS pixels) are scrolls, not presses
+const TOUCH_MOVE_TOLERANCE: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy)]
+struct TouchStart {
+    time: f64,
+    x: i32,
+    y: i32,
+}
+
+impl TouchStart {
+    fn distance_to(&self, x: i32, y: i32) -> f64 {
+        f64::from(x - self.x).hypot(f64::from(y - self.y))
+    }
+}
+
+/// Decides what a finished touch does from how long it was held (ms) and how far it moved
+fn touch_action(held: f64, moved: f64) -> Option<PlayAction> {
+    if moved > TOUCH_MOVE_TOLERANCE {
+        None
+    } else if held >= LONG_PRESS_MS {
+        Some(PlayAction::Flag)
+    } else {
+        Some(PlayAction::Reveal)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CELL_PX: f64 = 32.0;
+
+    #[test]
+    fn key_command_moves_and_plays() {
+        let cursor = BoardPoint { row: 1, col: 1 };
+        let moved = |row, col| Some(KeyCommand::Move(BoardPoint { row, col }));
+        assert_eq!(key_command("ArrowUp", cursor, 3, 3), moved(0, 1));
+        assert_eq!(key_command("ArrowDown", cursor, 3, 3), moved(2, 1));
+        assert_eq!(key_command("ArrowLeft", cursor, 3, 3), moved(1, 0));
+        assert_eq!(key_command("ArrowRight", cursor, 3, 3), moved(1, 2));
+        assert_eq!(
+            key_command(" ", cursor, 3, 3),
+            Some(KeyCommand::Play(PlayAction::Reveal))
+        );
+        assert_eq!(
+            key_command("d", cursor, 3, 3),
+            Some(KeyCommand::Play(PlayAction::RevealAdjacent))
+        );
+        assert_eq!(
+            key_command("f", cursor, 3, 3),
+            Some(KeyCommand::Play(PlayAction::Flag))
+        );
+        assert_eq!(key_command("x", cursor, 3, 3), None);
+    }
+
+    #[test]
+    fn fit_zoom_small_board_stays_full_size() {
+        assert_eq!(fit_zoom(9, 9, CELL_PX, 1920.0, 1080.0), 1.0);
+    }
+
+    #[test]
+    fn fit_zoom_shrinks_large_boards() {
+        // 50 cols * 32px = 1600px of cells in 1024 - 64 = 960px => 0.6
+        assert_eq!(fit_zoom(16, 50, CELL_PX, 1024.0, 2000.0), 0.6);
+        // height limited: 100 rows * 32px in 1344 - 64 = 1280px => 0.4
+        assert_eq!(fit_zoom(100, 10, CELL_PX, 4000.0, 1344.0), 0.4);
+        // never below the minimum zoom
+        assert_eq!(fit_zoom(100, 100, CELL_PX, 400.0, 400.0), MIN_ZOOM);
+    }
+
+    #[test]
+    fn clamp_zoom_bounds_and_rounds() {
+        assert_eq!(clamp_zoom(0.1), MIN_ZOOM);
+        assert_eq!(clamp_zoom(5.0), MAX_ZOOM);
+        assert_eq!(clamp_zoom(0.7000000001), 0.7);
+    }
+
+    #[test]
+    fn touch_action_thresholds() {
+        assert_eq!(touch_action(0.0, 0.0), Some(PlayAction::Reveal));
+        assert_eq!(touch_action(399.0, 5.0), Some(PlayAction::Reveal));
+        assert_eq!(touch_action(400.0, 0.0), Some(PlayAction::Flag));
+        assert_eq!(touch_action(1500.0, 10.0), Some(PlayAction::Flag));
+        assert_eq!(touch_action(100.0, 10.5), None);
+        assert_eq!(touch_action(1500.0, 40.0), None);
+    }
+
+    #[test]
+    fn touch_start_distance() {
+        let start = TouchStart {
+            time: 0.0,
+            x: 10,
+            y: 10,
+        };
+        assert_eq!(start.distance_to(13, 14), 5.0);
+        assert_eq!(start.distance_to(10, 10), 0.0);
+    }
+
+    #[test]
+    fn key_command_clamps_to_board() {
+        let top_left = BoardPoint { row: 0, col: 0 };
+        let bottom_right = BoardPoint { row: 2, col: 4 };
+        let moved = |p| Some(KeyCommand::Move(p));
+        assert_eq!(key_command("ArrowUp", top_left, 3, 5), moved(top_left));
+        assert_eq!(key_command("ArrowLeft", top_left, 3, 5), moved(top_left));
+        assert_eq!(
+            key_command("ArrowDown", bottom_right, 3, 5),
+            moved(bottom_right)
+        );
+        assert_eq!(
+            key_command("ArrowRight", bottom_right, 3, 5),
+            moved(bottom_right)
+        );
+    }
 }
@@ -5,10 +5,11 @@ use leptos_router::components::*;
 use leptos_use::{use_interval, UseIntervalReturn};
 use serde::{Deserialize, Serialize};
 
-use super::{GameMode, GameSettings};
+use super::{widgets::game_time_from_start_end, GameMode, GameSettings};
+use crate::components::i18n::t;
 
 #[cfg(feature = "ssr")]
-use crate::backend::GameManager;
+use crate::backend::{GameManager, GameQuery};
 #[cfg(feature = "ssr")]
 use crate::models::game::SimpleGameWithPlayers;
 
@@ -27,6 +28,7 @@ pub struct SimpleGameInfo {
     num_players: u8,
     seconds: Option<i64>,
     top_score: Option<i64>,
+    is_daily: bool,
 }
 
 #[cfg(feature = "ssr")]
@@ -46,6 +48,7 @@ impl From<SimpleGameWithPlayers> for SimpleGameInfo {
             num_players: value.num_players,
             seconds: value.seconds,
             top_score: value.top_score,
+            is_daily: value.is_daily,
         }
     }
 }
@@ -54,7 +57,7 @@ impl From<SimpleGameWithPlayers> for SimpleGameInfo {
 pub async fn get_active_games() -> Result<Vec<SimpleGameInfo>, ServerFnError> {
     let game_manager = use_context::<GameManager>()
         .ok_or_else(|| ServerFnError::new("No game manager".to_string()))?;
-    let active_games = game_manager.get_active_games().await;
+    let active_games = game_manager.get_active_games(GameQuery::default()).await;
 
     Ok(active_games.into_iter().map(SimpleGameInfo::from).collect())
 }
@@ -258,16 +261,22 @@ fn GameSummary(game_info: SimpleGameInfo, style: String) -> impl IntoView {
     let section_class =
         "flex justify-center items-center border border-slate-100 dark:border-slate-700 p-1";
 
-    let game_mode = GameMode::from(GameSettings {
-        rows: game_info.rows as i64,
-        cols: game_info.cols as i64,
-        num_mines: game_info.num_mines as i64,
-        max_players: game_info.max_players as i64,
-    });
+    let game_mode = GameMode::of_game(
+        GameSettings {
+            rows: game_info.rows as i64,
+            cols: game_info.cols as i64,
+            num_mines: game_info.num_mines as i64,
+            max_players: game_info.max_players as i64,
+        },
+        game_info.is_daily,
+    );
     let mode = match game_mode {
-        GameMode::ClassicBeginner | GameMode::ClassicIntermediate | GameMode::ClassicExpert => {
-            game_mode.short_name().to_string()
-        }
+        GameMode::ClassicBeginner
+        | GameMode::ClassicIntermediate
+        | GameMode::ClassicExpert
+        | GameMode::Tiny
+        | GameMode::Evil
+        | GameMode::Daily => game_mode.short_name().to_string(),
         GameMode::SmallMultiplayer => "Multi Small".to_string(),
         GameMode::LargeMultiplayer => "Multi Large".to_string(),
         GameMode::Custom => format!("Custom {}x{}", game_info.rows, game_info.cols),
@@ -288,10 +297,7 @@ fn GameSummary(game_info: SimpleGameInfo, style: String) -> impl IntoView {
         }
     } else {
         EitherOf4::C(view! {
-            <span>
-                {Utc::now().signed_duration_since(game_info.start_time.unwrap()).num_seconds()}
-                " seconds"
-            </span>
+            <span>{game_time_from_start_end(game_info.start_time, None)}" seconds"</span>
         })
     };
 
@@ -302,9 +308,12 @@ fn GameSummary(game_info: SimpleGameInfo, style: String) -> impl IntoView {
     };
     let top_score = if let Some(score) = game_info.top_score {
         match (game_mode, score) {
-            (GameMode::ClassicBeginner, 71) => "Victory".to_string(),
-            (GameMode::ClassicIntermediate, 216) => "Victory".to_string(),
-            (GameMode::ClassicExpert, 381) => "Victory".to_string(),
+            (GameMode::ClassicBeginner, 71) => t("Victory").to_string(),
+            (GameMode::ClassicIntermediate, 216) => t("Victory").to_string(),
+            (GameMode::ClassicExpert, 381) => t("Victory").to_string(),
+            (GameMode::Daily, 381) => t("Victory").to_string(),
+            (GameMode::Tiny, 21) => t("Victory").to_string(),
+            (GameMode::Evil, 470) => t("Victory").to_string(),
             _ => {
                 if game_info.start_time.is_some() {
                     format!("{}", score)
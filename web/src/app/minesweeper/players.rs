@@ -9,7 +9,10 @@ use minesweeper_lib::client::ClientPlayer;
 use crate::backend::{AuthSession, GameManager};
 use crate::{
     button_class,
-    components::icons::{IconTooltip, Mine, Star, Trophy},
+    components::{
+        i18n::t,
+        icons::{IconTooltip, Mine, Star, Trophy},
+    },
     player_class, player_icon_holder,
 };
 
@@ -173,7 +176,7 @@ fn PlayerRow(player_num: usize, player: Option<ClientPlayer>) -> impl IntoView {
                         view! {
                             <span class=player_icon_holder!("bg-red-600", true)>
                                 <Mine />
-                                <IconTooltip>"Dead"</IconTooltip>
+                                <IconTooltip>{move || t("Dead")}</IconTooltip>
                             </span>
                         },
                     )
@@ -185,7 +188,7 @@ fn PlayerRow(player_num: usize, player: Option<ClientPlayer>) -> impl IntoView {
                         view! {
                             <span class=player_icon_holder!("bg-green-800", true)>
                                 <Trophy />
-                                <IconTooltip>"Top Score"</IconTooltip>
+                                <IconTooltip>{move || t("Top Score")}</IconTooltip>
                             </span>
                         },
                     )
@@ -197,7 +200,7 @@ fn PlayerRow(player_num: usize, player: Option<ClientPlayer>) -> impl IntoView {
                         view! {
                             <span class=player_icon_holder!("bg-black", true)>
                                 <Star />
-                                <IconTooltip>"Victory Click"</IconTooltip>
+                                <IconTooltip>{move || t("Victory Click")}</IconTooltip>
                             </span>
                         },
                     )
@@ -236,7 +239,7 @@ fn PlayForm(join_trigger: Trigger) -> impl IntoView {
                 class="w-full max-w-xs h-8"
             >
                 <button type="submit" class=button_class!("w-full max-w-xs h-8")>
-                    "Play Game"
+                    {move || t("Play Game")}
                 </button>
             </form>
         </Show>
@@ -271,7 +274,7 @@ fn StartForm(start_game: ServerAction<StartGame>, game_id: String) -> impl IntoV
 
                 disabled=start_game.pending()
             >
-                "Start Game"
+                {move || t("Start Game")}
             </button>
         </ActionForm>
     }
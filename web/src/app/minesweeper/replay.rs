@@ -5,6 +5,7 @@ use std::sync::{Arc, RwLock};
 
 use crate::button_class;
 use minesweeper_lib::{
+    analysis::AnalyzedCell,
     board::Board,
     cell::{HiddenCell, PlayerCell},
     client::ClientPlayer,
@@ -28,9 +29,10 @@ impl ReplayStore {
         f(replay.current_board())
     }
 
-    fn with_current_players(&self, f: impl FnOnce(&Vec<SimplePlayer>)) {
+    /// Players and top score for the current frame
+    fn with_current_players(&self, f: impl FnOnce(&Vec<SimplePlayer>, Option<usize>)) {
         let replay: &MinesweeperReplayWithAnalysis = &mut (*self.replay).read().unwrap();
-        f(replay.current_players())
+        f(replay.current_players(), replay.current_top_score())
     }
 
     fn next(&self) -> Result<ReplayPosition> {
@@ -60,6 +62,21 @@ impl ReplayStore {
     }
 }
 
+/// Analysis to show for a cell - `shown` is kept while analysis is paused, so the overlay only
+/// changes again once it's live
+fn cell_analysis(
+    show_analysis: bool,
+    live_analysis: bool,
+    shown: Option<AnalyzedCell>,
+    current: Option<AnalyzedCell>,
+) -> Option<AnalyzedCell> {
+    match (show_analysis, live_analysis) {
+        (false, _) => None,
+        (true, false) => shown,
+        (true, true) => current,
+    }
+}
+
 #[component]
 pub fn ReplayControls(
     replay: MinesweeperReplayWithAnalysis,
@@ -75,6 +92,7 @@ pub fn ReplayControls(
 
     let (show_mines, set_show_mines) = signal(true);
     let (show_analysis, set_show_analysis) = signal(false);
+    let (live_analysis, set_live_analysis) = signal(true);
     let (is_beginning, set_beginning) = signal(true);
     let (is_end, set_end) = signal(false);
     let (current_play, set_current_play) = signal::<Option<Play>>(None);
@@ -100,11 +118,13 @@ pub fn ReplayControls(
         } else {
             *pc
         };
-        let ac = if !show_analysis.get_untracked() {
-            None
-        } else {
-            *ac
-        };
+        let ReplayAnalysisCell(_, shown) = replay.cell_read_signals[row][col].get_untracked();
+        let ac = cell_analysis(
+            show_analysis.get_untracked(),
+            live_analysis.get_untracked(),
+            shown,
+            *ac,
+        );
         let cell = ReplayAnalysisCell(pc, ac);
         if replay.cell_read_signals[row][col].get_untracked() != cell {
             replay.cell_write_signals[row][col](cell);
@@ -122,11 +142,11 @@ pub fn ReplayControls(
                             .for_each(|(col, cell)| render_cell(replay, row, col, cell))
                     })
             });
-            replay.with_current_players(|current_players| {
+            replay.with_current_players(|current_players, top_score| {
                 current_players.iter().enumerate().for_each(|(i, p)| {
                     replay.player_write_signals[i].update(|cp| {
                         if let Some(cp) = cp.as_mut() {
-                            p.update_client_player(cp);
+                            p.update_client_player(cp, top_score);
                         }
                     });
                 })
@@ -158,6 +178,17 @@ pub fn ReplayControls(
         false,
     );
 
+    Effect::watch(
+        live_analysis,
+        move |live_analysis, _, prev| {
+            if prev != Some(*live_analysis) && *live_analysis {
+                render_current();
+            }
+            *live_analysis
+        },
+        false,
+    );
+
     let next = move || {
         replay.with_value(|replay| {
             let res = replay.next();
@@ -251,7 +282,12 @@ pub fn ReplayControls(
                         value=""
                         class="table-cell sr-only peer"
                         on:change=move |ev| {
-                            set_show_analysis(event_target_checked(&ev));
+                            let checked = event_target_checked(&ev);
+                            if checked {
+                                // nothing was kept while hidden, so start live
+                                set_live_analysis(true);
+                            }
+                            set_show_analysis(checked);
                         }
                     />
                     <div class="table-cell relative w-11 h-6 bg-gray-200 dark:bg-gray-700 rounded-full peer peer-checked:after:translate-x-full rtl:peer-checked:after:-translate-x-full peer-checked:after:border-gray-600 after:content-[''] after:absolute after:top-0.5 after:start-[2px] after:bg-cyan-200 after:border-gray-300 after:border after:rounded-full after:h-5 after:w-5 after:transition-all dark:border-gray-600 peer-checked:bg-gray-400 peer-checked:dark:bg-gray-500"></div>
@@ -259,6 +295,22 @@ pub fn ReplayControls(
                         "Toggle Analysis"
                     </span>
                 </label>
+                <label class="table-row cursor-pointer">
+                    <input
+                        type="checkbox"
+                        value=""
+                        class="table-cell sr-only peer"
+                        prop:checked=live_analysis
+                        disabled=move || !show_analysis()
+                        on:change=move |ev| {
+                            set_live_analysis(event_target_checked(&ev));
+                        }
+                    />
+                    <div class="table-cell relative w-11 h-6 bg-gray-200 dark:bg-gray-700 rounded-full peer peer-checked:after:translate-x-full rtl:peer-checked:after:-translate-x-full peer-checked:after:border-gray-600 after:content-[''] after:absolute after:top-0.5 after:start-[2px] after:bg-cyan-200 after:border-gray-300 after:border after:rounded-full after:h-5 after:w-5 after:transition-all dark:border-gray-600 peer-checked:bg-gray-400 peer-checked:dark:bg-gray-500 peer-disabled:opacity-50"></div>
+                    <span class="table-cell text-left ms-3 text-sm font-medium text-gray-900 dark:text-gray-300 select-none">
+                        {move || if live_analysis() { "Analysis Live" } else { "Analysis Paused" }}
+                    </span>
+                </label>
             </div>
             <div class="w-full max-w-xs flex justify-between items-center">
                 <button
@@ -335,3 +387,18 @@ pub fn OpenReplay() -> impl IntoView {
         </div>
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn paused_analysis_keeps_shown() {
+        let shown = Some(AnalyzedCell::Mine);
+        let current = Some(AnalyzedCell::Empty);
+        assert_eq!(cell_analysis(true, true, shown, current), current);
+        assert_eq!(cell_analysis(true, false, shown, current), shown);
+        assert_eq!(cell_analysis(false, true, shown, current), None);
+        assert_eq!(cell_analysis(false, false, shown, current), None);
+    }
+}
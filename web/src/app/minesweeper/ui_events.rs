@@ -0,0 +1,73 @@
+use minesweeper_lib::{
+    board::BoardPoint,
+    cell::{HiddenCell, PlayerCell},
+    game::PlayOutcome,
+};
+
+/// Things worth reacting to in an active game (sounds, animations) - the game itself doesn't
+/// care whether anything is listening.  `ActiveGame` provides the latest as a
+/// `ReadSignal<Option<UiEvent>>` context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiEvent {
+    CellRevealed { player: usize, point: BoardPoint },
+    Flagged(BoardPoint),
+    Exploded { player: usize, point: BoardPoint },
+    Victory { player: usize },
+}
+
+/// Event for a play outcome - unflagging and question marks have none
+pub fn ui_event(outcome: &PlayOutcome) -> Option<UiEvent> {
+    match outcome {
+        PlayOutcome::Success(cells) => cells.first().map(|(point, rc)| UiEvent::CellRevealed {
+            player: rc.player,
+            point: *point,
+        }),
+        PlayOutcome::Victory(cells) => cells
+            .first()
+            .map(|(_, rc)| UiEvent::Victory { player: rc.player }),
+        PlayOutcome::Failure((point, rc)) => Some(UiEvent::Exploded {
+            player: rc.player,
+            point: *point,
+        }),
+        PlayOutcome::Flag((point, PlayerCell::Hidden(HiddenCell::Flag))) => {
+            Some(UiEvent::Flagged(*point))
+        }
+        PlayOutcome::Flag(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use minesweeper_lib::cell::{Cell, RevealedCell};
+
+    use super::*;
+
+    #[test]
+    fn outcomes_map_to_ui_events() {
+        let point = BoardPoint { row: 1, col: 2 };
+        let revealed = |contents| RevealedCell {
+            player: 1,
+            contents,
+        };
+
+        let reveal = PlayOutcome::Success(vec![(point, revealed(Cell::Empty(1)))]);
+        assert_eq!(
+            ui_event(&reveal),
+            Some(UiEvent::CellRevealed { player: 1, point })
+        );
+
+        let flag = PlayOutcome::Flag((point, PlayerCell::Hidden(HiddenCell::Flag)));
+        assert_eq!(ui_event(&flag), Some(UiEvent::Flagged(point)));
+        let unflag = PlayOutcome::Flag((point, PlayerCell::Hidden(HiddenCell::Empty)));
+        assert_eq!(ui_event(&unflag), None);
+
+        let death = PlayOutcome::Failure((point, revealed(Cell::Mine)));
+        assert_eq!(
+            ui_event(&death),
+            Some(UiEvent::Exploded { player: 1, point })
+        );
+
+        let victory = PlayOutcome::Victory(vec![(point, revealed(Cell::Empty(0)))]);
+        assert_eq!(ui_event(&victory), Some(UiEvent::Victory { player: 1 }));
+    }
+}
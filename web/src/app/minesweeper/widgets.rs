@@ -1,7 +1,8 @@
-use leptos::prelude::*;
+use chrono::{DateTime, TimeDelta, Utc};
+use leptos::{ev, prelude::*};
 use leptos_use::{
-    use_clipboard, use_interval_fn_with_options, use_timeout_fn, utils::Pausable,
-    UseClipboardReturn, UseIntervalFnOptions, UseTimeoutFnReturn,
+    use_clipboard, use_document, use_event_listener, use_interval_fn_with_options, use_timeout_fn,
+    utils::Pausable, UseClipboardReturn, UseIntervalFnOptions, UseTimeoutFnReturn,
 };
 
 use crate::{
@@ -23,30 +24,33 @@ pub fn ActiveTimer(
     sync_time: ReadSignal<Option<usize>>,
     completed: ReadSignal<bool>,
 ) -> impl IntoView {
-    let (start_time, set_start_time) = signal::<Option<f64>>(None);
+    let (start_time, set_start_time) = signal::<Option<DateTime<Utc>>>(None);
     let (display_time, set_display_time) = signal::<usize>(0);
 
+    // recompute from the start time rather than counting ticks - background tabs throttle timers
+    let update_display = move || {
+        if let Some(st) = start_time.get_untracked() {
+            set_display_time(elapsed_seconds(st, Utc::now()));
+        }
+    };
+
     let Pausable {
         is_active,
         pause,
         resume,
     } = use_interval_fn_with_options(
-        move || {
-            if let Some(st) = start_time.get() {
-                if let Some(p) = window().performance() {
-                    let base = sync_time.get().unwrap_or(0);
-                    let time_since_sync = (p.now() - st).floor() as usize / 1000;
-                    let display_time = 999.min(base + time_since_sync);
-                    set_display_time(display_time);
-                };
-            }
-        },
+        update_display,
         100,
         UseIntervalFnOptions {
             immediate: false,
             immediate_callback: false,
         },
     );
+    let _ = use_event_listener(use_document(), ev::visibilitychange, move |_| {
+        if !completed.get_untracked() {
+            update_display();
+        }
+    });
 
     Effect::watch(
         move || (completed.get(), sync_time.get()),
@@ -57,9 +61,7 @@ pub fn ActiveTimer(
             if sync_time.is_some() && sync_time != prev.flatten() {
                 if let Some(st) = sync_time {
                     set_display_time(st);
-                    if let Some(p) = window().performance() {
-                        set_start_time(Some(p.now()));
-                    };
+                    set_start_time(Some(Utc::now() - TimeDelta::seconds(st as i64)));
                 };
             }
             if !is_active.get_untracked() && !completed && sync_time.is_some() {
@@ -84,6 +86,31 @@ pub fn ActiveTimer(
     }
 }
 
+/// Whole seconds from `start` to `now`, clamped to what the timer can display
+fn elapsed_seconds(start: DateTime<Utc>, now: DateTime<Utc>) -> usize {
+    now.signed_duration_since(start).num_seconds().clamp(0, 999) as usize
+}
+
+/// Seconds a game took, or has taken so far if it has no `end` yet
+pub(super) fn game_time_from_start_end(
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> usize {
+    game_time_at(start, end, Utc::now())
+}
+
+fn game_time_at(
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> usize {
+    match (start, end) {
+        (Some(st), et) => elapsed_seconds(st, et.unwrap_or(now)),
+        (None, Some(_)) => 999,
+        (None, None) => 0,
+    }
+}
+
 #[component]
 pub fn InactiveMines(num_mines: usize) -> impl IntoView {
     view! {
@@ -130,6 +157,17 @@ pub fn InactiveTimer(game_time: usize) -> impl IntoView {
 
 #[component]
 pub fn CopyGameLink(game_id: String) -> impl IntoView {
+    view! { <CopyLink path=format!("/game/{}", game_id) label="Copy Link" /> }
+}
+
+/// Copies a link for anyone to play the same board - see `game_link_code`
+#[component]
+pub fn CopyBoardLink(link_code: String) -> impl IntoView {
+    view! { <CopyLink path=format!("/play/{}", link_code) label="Copy Board" /> }
+}
+
+#[component]
+fn CopyLink(path: String, label: &'static str) -> impl IntoView {
     let (show_tooltip, set_show_tooltip) = signal(false);
     let UseClipboardReturn { copy, .. } = use_clipboard();
     let UseTimeoutFnReturn { start, .. } = use_timeout_fn(
@@ -150,7 +188,7 @@ pub fn CopyGameLink(game_id: String) -> impl IntoView {
     let origin = { window().location().origin().unwrap_or_default() };
     #[cfg(feature = "ssr")]
     let origin = String::new();
-    let url = format!("{}/game/{}", origin, game_id);
+    let url = format!("{}{}", origin, path);
     view! {
         <div class="flex flex-col items-center justify-center border-2 rounded-full border-slate-400 bg-neutral-200 text-neutral-800 font-medium px-2">
             <button
@@ -161,7 +199,7 @@ pub fn CopyGameLink(game_id: String) -> impl IntoView {
                     start(());
                 }
             >
-                <span>Copy Link</span>
+                <span>{label}</span>
                 <span class=widget_icon_holder!("", true)>
                     <Copy />
                     <IconTooltip>Copied</IconTooltip>
@@ -170,3 +208,46 @@ pub fn CopyGameLink(game_id: String) -> impl IntoView {
         </div>
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn elapsed_seconds_from_start() {
+        let start = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let now = start + TimeDelta::milliseconds(83_900);
+        assert_eq!(elapsed_seconds(start, now), 83);
+        assert_eq!(elapsed_seconds(start, start), 0);
+        assert_eq!(elapsed_seconds(now, start), 0);
+        assert_eq!(elapsed_seconds(start, start + TimeDelta::hours(2)), 999);
+    }
+
+    #[test]
+    fn game_time_completed() {
+        let start = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let end = start + TimeDelta::seconds(42);
+        let now = start + TimeDelta::seconds(500);
+        assert_eq!(game_time_at(Some(start), Some(end), now), 42);
+    }
+
+    #[test]
+    fn game_time_in_progress() {
+        let start = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let now = start + TimeDelta::seconds(75);
+        assert_eq!(game_time_at(Some(start), None, now), 75);
+        assert!(game_time_from_start_end(Some(Utc::now() - TimeDelta::seconds(10)), None) >= 10);
+    }
+
+    #[test]
+    fn game_time_not_started() {
+        assert_eq!(game_time_at(None, None, Utc::now()), 0);
+        assert_eq!(game_time_from_start_end(None, None), 0);
+    }
+}
@@ -2,7 +2,10 @@ use leptos::prelude::*;
 use leptos_meta::*;
 use leptos_router::{components::*, path};
 
-use crate::components::info::{use_controls_info_keybinds, ControlsInfoButton, ControlsInfoModal};
+use crate::components::{
+    i18n::provide_lang,
+    info::{use_controls_info_keybinds, ControlsInfoButton, ControlsInfoModal},
+};
 
 use super::{
     auth::{get_frontend_user, Login, Logout},
@@ -11,7 +14,7 @@ use super::{
     header::Header,
     home::HomeView,
     login::LoginView,
-    minesweeper::{ActiveGames, GameView, GameWrapper, RecentGames, ReplayView},
+    minesweeper::{ActiveGames, GameView, GameWrapper, LinkedGameView, RecentGames, ReplayView},
     profile::ProfileView,
 };
 
@@ -65,6 +68,7 @@ pub fn App() -> impl IntoView {
 
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context();
+    provide_lang();
 
     view! {
         <Title formatter=|title| format!("Minesweeper - {title}") />
@@ -88,6 +92,7 @@ pub fn App() -> impl IntoView {
                         <Route path=path!("/replay") view=ReplayView />
                         <Route path=path!("/") view=GameView />
                     </ParentRoute>
+                    <Route path=path!("/play/:link") view=LinkedGameView />
                     <Route path=path!("/active") view=ActiveGames />
                     <Route path=path!("/recent") view=RecentGames />
                 </Routes>
@@ -1,12 +1,15 @@
+mod admin;
 mod app;
 mod auth;
 mod cache;
 mod fileserv;
 mod game_manager;
+mod heartbeat;
+mod replay_stream;
 mod users;
 mod websocket;
 
 pub use app::App;
 pub use auth::{CSRF_STATE_KEY, NEXT_URL_KEY, OAUTH_TARGET};
-pub use game_manager::GameManager;
+pub use game_manager::{GameLimits, GameManager, GameQuery, GameSort};
 pub use users::AuthSession;
@@ -0,0 +1,45 @@
+use std::env;
+
+use axum::{extract::State, response::IntoResponse, routing::get, Json, Router};
+use http::{header::AUTHORIZATION, HeaderMap, StatusCode};
+
+use super::app::AppState;
+
+/// Token for the admin endpoints, read from `ADMIN_TOKEN` - the endpoints don't exist without it
+#[derive(Clone, Debug, Default)]
+pub struct AdminConfig {
+    token: Option<String>,
+}
+
+impl AdminConfig {
+    pub fn from_env() -> Self {
+        Self {
+            token: env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()),
+        }
+    }
+
+    /// Whether `headers` carry `Authorization: Bearer <token>`
+    fn authorized(&self, headers: &HeaderMap) -> Option<bool> {
+        let token = self.token.as_ref()?;
+        let bearer = headers
+            .get(AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "));
+        Some(bearer == Some(token.as_str()))
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::<AppState>::new().route("/api/admin/metrics", get(metrics_handler))
+}
+
+pub async fn metrics_handler(
+    headers: HeaderMap,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    match app_state.admin.authorized(&headers) {
+        None => StatusCode::NOT_FOUND.into_response(),
+        Some(false) => StatusCode::UNAUTHORIZED.into_response(),
+        Some(true) => Json(app_state.game_manager.metrics().await).into_response(),
+    }
+}
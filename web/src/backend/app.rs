@@ -19,14 +19,21 @@ use tower_sessions::{
 };
 use tower_sessions_sqlx_store::SqliteStore;
 
-use crate::{
-    app::{shell, App as FrontendApp, OAuthTarget},
-    models::game::Game,
-};
+use crate::app::{shell, App as FrontendApp, OAuthTarget};
 
 use super::{
-    auth, auth::REDIRECT_URL, fileserv::file_and_error_handler, game_manager::GameManager, users,
-    users::AuthSession, websocket,
+    admin,
+    admin::AdminConfig,
+    auth,
+    auth::REDIRECT_URL,
+    fileserv::file_and_error_handler,
+    game_manager::{
+        DailySecret, GameManager, SpectatorLimits, SpectatorOverflow, DEFAULT_CHANNEL_CAPACITY,
+    },
+    heartbeat::HeartbeatConfig,
+    users,
+    users::AuthSession,
+    websocket,
 };
 
 /// This takes advantage of Axum's SubStates feature by deriving FromRef. This is the only way to have more than one
@@ -36,6 +43,8 @@ pub struct AppState {
     pub leptos_options: LeptosOptions,
     pub routes: Vec<AxumRouteListing>,
     pub game_manager: GameManager,
+    pub heartbeat: HeartbeatConfig,
+    pub admin: AdminConfig,
 }
 
 pub struct App {
@@ -137,8 +146,6 @@ impl App {
 
         let db = SqlitePool::connect(&db_url).await?;
         sqlx::migrate!().run(&db).await?;
-        // Close out any dangling games from before restart
-        Game::set_all_games_completed(&db).await?;
 
         let session_store = SqliteStore::new(db.clone());
         session_store
@@ -166,7 +173,7 @@ impl App {
         deletion_task
     }
 
-    pub async fn router(self) -> (Router, SocketAddr) {
+    pub async fn router(self) -> (Router, SocketAddr, GameManager) {
         // Setting get_configuration(None) means we'll be using cargo-leptos's env values
         // For deployment these variables are:
         // <https://github.com/leptos-rs/start-axum#executing-a-server-on-a-remote-machine-without-the-toolchain>
@@ -176,12 +183,45 @@ impl App {
         let leptos_options = conf.leptos_options;
         let addr = leptos_options.site_addr;
         let routes = generate_route_list(FrontendApp);
-        let game_manager = GameManager::new(self.db.clone());
+        let channel_capacity = env::var("GAME_CHANNEL_CAPACITY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&capacity| capacity > 0)
+            .unwrap_or(DEFAULT_CHANNEL_CAPACITY);
+        let mut spectator_limits = SpectatorLimits::default();
+        if let Some(max_spectators) = env::var("MAX_SPECTATORS").ok().and_then(|s| s.parse().ok()) {
+            spectator_limits.max_spectators = max_spectators;
+        }
+        // spectators over the cap wait this long for a spot instead of being turned away
+        if let Some(secs) = env::var("SPECTATOR_QUEUE_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+        {
+            spectator_limits.overflow = SpectatorOverflow::Queue {
+                max_wait: std::time::Duration::from_secs(secs),
+            };
+        }
+        let mut game_manager = GameManager::new(self.db.clone())
+            .with_channel_capacity(channel_capacity)
+            .with_spectator_limits(spectator_limits);
+        // daily boards come from this, so it has to survive restarts
+        if let Some(secret) = env::var("DAILY_SECRET").ok().filter(|s| !s.is_empty()) {
+            game_manager = game_manager.with_daily_secret(DailySecret::new(&secret));
+        } else {
+            log::warn!("DAILY_SECRET not set - daily challenge boards come from the date alone");
+        }
+        // Pick up any games left running before restart
+        if let Err(e) = game_manager.resume_incomplete_games().await {
+            log::error!("Error resuming games: {e}");
+        }
+        game_manager.spawn_sweeper();
 
         let app_state = AppState {
             leptos_options,
             routes: routes.clone(),
-            game_manager,
+            game_manager: game_manager.clone(),
+            heartbeat: HeartbeatConfig::from_env(),
+            admin: AdminConfig::from_env(),
         };
 
         // Session layer.
@@ -214,8 +254,9 @@ impl App {
             .fallback(file_and_error_handler)
             .merge(auth::router())
             .merge(websocket::router())
+            .merge(admin::router())
             .layer(auth_service)
             .with_state(app_state);
-        (app, addr)
+        (app, addr, game_manager)
     }
 }
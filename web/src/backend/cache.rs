@@ -40,6 +40,12 @@ impl<T: Clone> CachedValue<T> {
         *current = Some((Instant::now(), value));
     }
 
+    /// Forces the next `get_or_set` to recompute regardless of TTL
+    pub async fn invalidate(&self) {
+        let mut current = self.current.lock().await;
+        *current = None;
+    }
+
     pub async fn get_or_set<F, Fut>(&self, f: F) -> T
     where
         F: FnOnce() -> Fut,
@@ -63,3 +69,20 @@ impl<T: Clone> CachedValue<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn invalidate_recomputes_before_ttl() {
+        let cache = CachedValue::new(Duration::from_secs(60));
+        assert_eq!(cache.get_or_set(|| async { 1 }).await, 1);
+        // still cached
+        assert_eq!(cache.get_or_set(|| async { 2 }).await, 1);
+
+        cache.invalidate().await;
+        assert_eq!(cache.get().await, None);
+        assert_eq!(cache.get_or_set(|| async { 3 }).await, 3);
+    }
+}
@@ -1,4 +1,4 @@
-use ::chrono::{DateTime, Utc};
+use ::chrono::{DateTime, NaiveDate, Utc};
 use anyhow::{anyhow, bail, Result};
 use axum::extract::ws::{Message, WebSocket};
 use chrono::TimeDelta;
@@ -7,28 +7,50 @@ use minesweeper_lib::{
     board::Board,
     cell::PlayerCell,
     client::ClientPlayer,
-    game::{Minesweeper, MinesweeperBuilder, MinesweeperOpts, Play, PlayOutcome},
+    game::{
+        GameStatus, Minesweeper, MinesweeperBuilder, MinesweeperOpts, Play, PlayError, PlayOutcome,
+    },
 };
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
-use std::{collections::HashMap, sync::Arc};
+use std::{cmp::Reverse, collections::HashMap, sync::Arc};
+use thiserror::Error;
 use tokio::{
-    sync::{broadcast, mpsc, Mutex, RwLock},
-    time::{interval, Duration},
+    sync::{broadcast, broadcast::error::RecvError, mpsc, oneshot, watch, Mutex, RwLock},
+    time::{interval, timeout, Duration},
 };
+use tracing::Instrument;
 
 use crate::{
-    app::FrontendUser,
-    messages::{ClientMessage, GameMessage},
+    app::{FrontendUser, GameMode, GameSettings},
+    messages::{ClientMessage, GameMessage, TaggedGameMessage},
     models::{
+        daily::{DailyGame, DailyResult},
         game::{
-            AggregateStats, Game, GameLog, GameParameters, Player, PlayerGame, PlayerUser,
-            SimpleGameWithPlayers, TimelineStats,
+            AggregateStats, Game, GameLog, GameParameters, GameParametersError, Player, PlayerGame,
+            PlayerUser, SimpleGameWithPlayers, TimelineStats,
         },
         user::User,
     },
 };
 
-use super::cache::CachedValue;
+use super::{
+    cache::CachedValue,
+    replay_stream::{move_delay, ReplayStream, ReplayStreams, MAX_REPLAY_SPEED},
+};
+
+/// Multiplayer boards are re-rolled until they have an opening at least this big so every
+/// player has somewhere to start
+const MULTIPLAYER_MIN_OPENING: usize = 12;
+/// Updates a game buffers for each socket before slow ones start missing them
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 100;
+/// How often games whose handler died are looked for
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+/// How often a queued spectator checks for a free spot
+const SPECTATOR_QUEUE_CHECK: Duration = Duration::from_millis(250);
+/// Seconds a multiplayer player can be disconnected from a started game before they resign
+const ABSENT_PLAYER_RESIGN_SECS: i64 = 60;
 
 #[derive(Clone, Debug)]
 struct PlayerHandle {
@@ -38,15 +60,57 @@ struct PlayerHandle {
     ws_sender: Arc<Mutex<SplitSink<WebSocket, Message>>>,
 }
 
+/// A player's seat in a running game - `ws_sender` is `None` while the player isn't
+/// connected, e.g. after leaving or when restored by [`GameManager::resume_game`]
+#[derive(Clone, Debug)]
+struct PlayerSlot {
+    user_id: Option<i64>,
+    player_id: usize,
+    display_name: String,
+    ws_sender: Option<Arc<Mutex<SplitSink<WebSocket, Message>>>>,
+}
+
+impl PlayerSlot {
+    fn is_socket(&self, ws_sender: &Arc<Mutex<SplitSink<WebSocket, Message>>>) -> bool {
+        self.ws_sender
+            .as_ref()
+            .is_some_and(|s| Arc::ptr_eq(s, ws_sender))
+    }
+}
+
 #[derive(Clone, Debug)]
 struct ViewerHandle {
     ws_sender: Arc<Mutex<SplitSink<WebSocket, Message>>>,
+    /// Set for multiplexed sockets - direct messages are wrapped in a `TaggedGameMessage`
+    tag: Option<String>,
+}
+
+impl ViewerHandle {
+    fn tagged(&self, msg: String) -> String {
+        match &self.tag {
+            Some(game_id) => TaggedGameMessage::tag_json(game_id, &msg),
+            None => msg,
+        }
+    }
+}
+
+struct ViewerChannels {
+    start_time: Option<DateTime<Utc>>,
+    game_events: mpsc::Sender<GameEvent>,
+    to_client: broadcast::Receiver<String>,
 }
 
 #[derive(Debug)]
 enum GameEvent {
     Player(PlayerHandle),
     Viewer(ViewerHandle),
+    Leave(PlayerHandle),
+    /// Current board and players for a socket which missed updates - `player_id` gets that
+    /// player's board, otherwise the viewer board
+    Resync {
+        player_id: Option<usize>,
+        reply: oneshot::Sender<Vec<String>>,
+    },
     Start,
 }
 
@@ -55,12 +119,183 @@ struct GameHandle {
     to_client: broadcast::Sender<String>,
     from_client: mpsc::Sender<String>,
     game_events: mpsc::Sender<GameEvent>,
-    players: Vec<PlayerHandle>,
+    players: Vec<PlayerSlot>,
     max_players: u8,
     owner: Option<i64>,
+    is_started: bool,
     start_time: Option<DateTime<Utc>>,
 }
 
+impl GameHandle {
+    /// Subscribed sockets without a connected seat
+    fn spectator_count(&self) -> usize {
+        // every socket subscribes as a viewer, including the ones seated as players
+        let connected_players = self
+            .players
+            .iter()
+            .filter(|p| p.ws_sender.is_some())
+            .count();
+        self.to_client
+            .receiver_count()
+            .saturating_sub(connected_players)
+    }
+}
+
+/// Largest games the server will create - checked before anything is allocated
+#[derive(Clone, Copy, Debug)]
+pub struct GameLimits {
+    pub max_rows: i64,
+    pub max_cols: i64,
+    pub max_players: u8,
+}
+
+impl Default for GameLimits {
+    fn default() -> Self {
+        Self {
+            max_rows: 100,
+            max_cols: 100,
+            max_players: 12,
+        }
+    }
+}
+
+impl GameLimits {
+    pub fn check(&self, params: &GameParameters) -> Result<(), GameParametersError> {
+        if params.rows > self.max_rows || params.cols > self.max_cols {
+            return Err(GameParametersError::TooLarge {
+                rows: params.rows,
+                cols: params.cols,
+                max_rows: self.max_rows,
+                max_cols: self.max_cols,
+            });
+        }
+        if params.max_players > self.max_players {
+            return Err(GameParametersError::TooManyPlayers {
+                max_players: params.max_players,
+                limit: self.max_players,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// What a spectator arriving at a game with `max_spectators` gets
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpectatorOverflow {
+    /// Turned away with `SpectateError::Full`
+    #[default]
+    Reject,
+    /// Waits up to `max_wait` for a spectator to leave before being turned away
+    Queue { max_wait: Duration },
+}
+
+/// Cap on each game's spectators, so a popular game can't pile up sockets
+#[derive(Clone, Copy, Debug)]
+pub struct SpectatorLimits {
+    pub max_spectators: usize,
+    pub overflow: SpectatorOverflow,
+}
+
+impl Default for SpectatorLimits {
+    fn default() -> Self {
+        Self {
+            max_spectators: 500,
+            overflow: SpectatorOverflow::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SpectateError {
+    #[error("Game with id {0} doesn't exist")]
+    NotFound(String),
+    #[error("Game already has max spectators ({0})")]
+    Full(usize),
+    #[error("Game with id {0} is no longer running")]
+    Closed(String),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GameSort {
+    #[default]
+    Unsorted,
+    MostPlayers,
+    FewestPlayers,
+    Newest,
+    Oldest,
+}
+
+/// Filters and ordering applied to the cached active games list
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GameQuery {
+    pub mode: Option<GameMode>,
+    pub max_players: Option<u8>,
+    pub sort: GameSort,
+}
+
+impl GameQuery {
+    fn apply(&self, games: Vec<SimpleGameWithPlayers>) -> Vec<SimpleGameWithPlayers> {
+        let mut games = games
+            .into_iter()
+            .filter(|g| {
+                self.mode.is_none_or(|mode| {
+                    let settings =
+                        GameSettings::new(g.rows, g.cols, g.num_mines, g.max_players as i64);
+                    GameMode::of_game(settings, g.is_daily) == mode
+                })
+            })
+            .filter(|g| self.max_players.is_none_or(|max| g.max_players == max))
+            .collect::<Vec<_>>();
+        // games which haven't started yet are the newest
+        let age_key = |g: &SimpleGameWithPlayers| g.start_time.unwrap_or(DateTime::<Utc>::MAX_UTC);
+        match self.sort {
+            GameSort::Unsorted => {}
+            GameSort::MostPlayers => games.sort_by_key(|g| Reverse(g.num_players)),
+            GameSort::FewestPlayers => games.sort_by_key(|g| g.num_players),
+            GameSort::Newest => games.sort_by_key(|g| Reverse(age_key(g))),
+            GameSort::Oldest => games.sort_by_key(age_key),
+        }
+        games
+    }
+}
+
+/// Snapshot of what's running, for spotting games which never get cleaned up
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GameManagerMetrics {
+    pub active_games: usize,
+    /// Seated players, connected or not
+    pub total_players: usize,
+    /// Connected sockets without a seat
+    pub total_spectators: usize,
+}
+
+/// Key mixed into daily challenge seeds so upcoming boards can't be worked out from the date.
+/// The default is empty, which seeds boards from the date alone.
+#[derive(Clone, Default)]
+pub struct DailySecret(Arc<str>);
+
+impl DailySecret {
+    pub fn new(secret: &str) -> Self {
+        Self(secret.into())
+    }
+}
+
+// Debug is implemented manually to avoid accidentally logging the secret
+impl std::fmt::Debug for DailySecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DailySecret([redacted])")
+    }
+}
+
+/// Seed shared by everyone playing the daily challenge on `day`
+pub fn daily_seed(secret: &DailySecret, day: NaiveDate) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(secret.0.as_bytes())
+        .chain_update(day.format("%Y-%m-%d").to_string())
+        .finalize();
+    u64::from_be_bytes(digest[..8].try_into().expect("digest is 32 bytes"))
+}
+
 #[derive(Clone, Debug)]
 pub struct GameManager {
     db: SqlitePool,
@@ -68,6 +303,12 @@ pub struct GameManager {
     // use active cache to avoid frequent read locks on games
     active_cache: Arc<CachedValue<Vec<SimpleGameWithPlayers>>>,
     recent_cache: Arc<CachedValue<Vec<SimpleGameWithPlayers>>>,
+    shutdown: Arc<watch::Sender<bool>>,
+    limits: GameLimits,
+    spectator_limits: SpectatorLimits,
+    channel_capacity: usize,
+    replays: ReplayStreams,
+    daily_secret: DailySecret,
 }
 
 impl GameManager {
@@ -78,7 +319,98 @@ impl GameManager {
             // 1.5 second active cache
             active_cache: CachedValue::new(Duration::from_millis(1500)).into(),
             recent_cache: CachedValue::new(Duration::from_secs(4)).into(),
+            shutdown: watch::Sender::new(false).into(),
+            limits: GameLimits::default(),
+            spectator_limits: SpectatorLimits::default(),
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            replays: ReplayStreams::default(),
+            daily_secret: DailySecret::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: GameLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_spectator_limits(mut self, spectator_limits: SpectatorLimits) -> Self {
+        self.spectator_limits = spectator_limits;
+        self
+    }
+
+    /// Size of each game's broadcast buffer - sockets further behind than this are resynced
+    pub fn with_channel_capacity(mut self, capacity: usize) -> Self {
+        self.channel_capacity = capacity;
+        self
+    }
+
+    /// Secret for daily challenge seeds - must stay the same across restarts for the daily
+    /// board to stay the same all day
+    pub fn with_daily_secret(mut self, daily_secret: DailySecret) -> Self {
+        self.daily_secret = daily_secret;
+        self
+    }
+
+    /// Tells every running game to save and stop, then waits (up to `max_wait`) for them to
+    /// finish.  The games are left incomplete so `resume_incomplete_games` can pick them up.
+    pub async fn shutdown(&self, max_wait: Duration) {
+        log::info!("Shutting down games");
+        let _ = self.shutdown.send(true);
+        let wait_for_games = async {
+            let mut check_interval = interval(Duration::from_millis(100));
+            loop {
+                check_interval.tick().await;
+                if self.games.read().await.is_empty() {
+                    break;
+                }
+            }
+        };
+        if timeout(max_wait, wait_for_games).await.is_err() {
+            log::error!("Timed out waiting for games to complete on shutdown");
+        }
+    }
+
+    /// Removes games whose handler stopped without completing them (e.g. it panicked) - they'd
+    /// otherwise stay "active" forever.  The handler owns the receiving end of `from_client`, so
+    /// the sender closes once it's gone.
+    pub async fn sweep_orphaned_games(&self) -> Vec<String> {
+        let mut orphaned = Vec::new();
+        self.games.write().await.retain(|game_id, handle| {
+            let closed = handle.from_client.is_closed();
+            if closed {
+                orphaned.push(game_id.clone());
+            }
+            !closed
+        });
+        if orphaned.is_empty() {
+            return orphaned;
+        }
+        for game_id in orphaned.iter() {
+            log::error!("Removing orphaned game {game_id}");
+            if let Err(e) = Game::set_completed(&self.db, game_id).await {
+                log::error!("Error completing orphaned game {game_id}: {e}");
+            }
         }
+        self.active_cache.invalidate().await;
+        self.recent_cache.invalidate().await;
+        orphaned
+    }
+
+    /// Sweeps orphaned games periodically until shutdown
+    pub fn spawn_sweeper(&self) {
+        let game_manager = self.clone();
+        let mut shutdown = self.shutdown.subscribe();
+        tokio::spawn(async move {
+            let mut sweep_interval = interval(SWEEP_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = sweep_interval.tick() => {
+                        game_manager.sweep_orphaned_games().await;
+                    }
+                    _ = shutdown.changed() => break,
+                }
+            }
+        });
     }
 
     pub async fn new_game(
@@ -86,32 +418,196 @@ impl GameManager {
         user: Option<User>,
         game_id: &str,
         game_parameters: GameParameters,
+        seed: Option<u64>,
     ) -> Result<()> {
+        game_parameters.validate()?;
+        self.limits.check(&game_parameters)?;
         let max_players = game_parameters.max_players;
-        let mut game = Game::create_game(&self.db, game_id, &user, game_parameters).await?;
+        // saved with the game so it can be rebuilt exactly - see `resume_game`
+        let seed = match seed {
+            Some(seed) => seed,
+            None => random_seed()?,
+        };
+        let mut game = Game::create_game(&self.db, game_id, &user, game_parameters, seed).await?;
         if max_players == 1 {
             Game::start_game(&self.db, game_id).await?;
             game.is_started = true;
         }
-        let (bc_tx, _bc_rx) = broadcast::channel(100);
+        let (bc_tx, _bc_rx) = broadcast::channel(self.channel_capacity);
         let (mp_tx, mp_rx) = mpsc::channel(100);
         let (ch_tx, ch_rx) = mpsc::channel(100);
+        let is_started = game.is_started;
+        let minesweeper = match new_minesweeper(&game) {
+            Ok(minesweeper) => minesweeper,
+            Err(e) => {
+                // don't leave a game row behind which nothing will ever complete
+                log::error!("Error creating game handler for {game_id}: {e}");
+                Game::delete_game(&self.db, game_id).await?;
+                return Err(e);
+            }
+        };
+        let self_clone = self.clone();
+        let shutdown = self.shutdown.subscribe();
+        let game_handler = GameHandler::new(
+            game,
+            minesweeper,
+            self_clone,
+            bc_tx.clone(),
+            mp_rx,
+            ch_rx,
+            shutdown,
+        );
         let handle = GameHandle {
-            to_client: bc_tx.clone(),
+            to_client: bc_tx,
             from_client: mp_tx,
             game_events: ch_tx,
             players: Vec::with_capacity(max_players as usize),
             max_players,
             owner: user.map(|u| u.id),
+            is_started,
             start_time: None,
         };
         {
             let mut games = self.games.write().await;
             games.insert(game_id.to_string(), handle);
         }
-        let self_clone = self.clone();
-        let game_handler = GameHandler::new(game, self_clone, bc_tx, mp_rx, ch_rx);
-        tokio::spawn(async move { game_handler.handle_game().await });
+        self.active_cache.invalidate().await;
+        let span = tracing::info_span!("game", %game_id);
+        tokio::spawn(game_handler.handle_game().instrument(span));
+        Ok(())
+    }
+
+    /// Starts the daily challenge for `day` - everyone gets the same board, seeded from `day`
+    /// and the daily secret
+    pub async fn new_daily_game(
+        &self,
+        user: Option<User>,
+        game_id: &str,
+        game_parameters: GameParameters,
+        day: NaiveDate,
+    ) -> Result<()> {
+        let seed = daily_seed(&self.daily_secret, day);
+        self.new_game(user, game_id, game_parameters, Some(seed))
+            .await?;
+        DailyGame::create(&self.db, game_id, day).await?;
+        Ok(())
+    }
+
+    /// Options and seed for anyone to play the board of a finished single player game -
+    /// `None` for daily challenges, which are played fresh each day, and games saved without a
+    /// seed
+    pub async fn get_board_link(&self, game: &Game) -> Option<(MinesweeperOpts, u64)> {
+        if !game.is_completed || game.max_players != 1 {
+            return None;
+        }
+        let seed = game.seed?;
+        if DailyGame::get_day(&self.db, &game.game_id)
+            .await
+            .ok()?
+            .is_some()
+        {
+            return None;
+        }
+        let opts = MinesweeperOpts {
+            rows: game.rows as usize,
+            cols: game.cols as usize,
+            num_mines: game.num_mines as usize,
+        };
+        Some((opts, seed as u64))
+    }
+
+    /// Fastest win of the daily challenge for `day` by `user`, in seconds
+    pub async fn get_daily_best(&self, user: i64, day: NaiveDate) -> Result<Option<i64>> {
+        let result = DailyResult::get_result(&self.db, user, day).await?;
+        Ok(result.map(|r| r.seconds))
+    }
+
+    /// Restarts a game which was still running when the server stopped.
+    ///
+    /// The board is rebuilt from the game's seed by replaying its log, so it's exactly as it
+    /// was left - mines, flags and scores included - and players keep their seats and can
+    /// rejoin.  Games which can't be rebuilt that way are an error.
+    pub async fn resume_game(&self, game_id: &str) -> Result<()> {
+        if self.game_is_active(game_id).await {
+            bail!("Game with id {game_id} is already running")
+        }
+        let game = Game::get_game(&self.db, game_id)
+            .await?
+            .ok_or(anyhow!("Game does not exist"))?;
+        if game.is_completed {
+            bail!("Game with id {game_id} is already completed")
+        }
+        let players = Player::get_players(&self.db, game_id).await?;
+        let log = match GameLog::get_log(&self.db, game_id).await? {
+            Some(game_log) => game_log.log,
+            // the saved board can't be trusted without the plays which made it
+            None if game.start_time.is_some() => bail!("Game with id {game_id} has no saved log"),
+            None => Vec::new(),
+        };
+        let minesweeper = resumed_minesweeper(&game, &players, &log)?;
+
+        let mut slots = players
+            .iter()
+            .map(|p| PlayerSlot {
+                user_id: p.user,
+                player_id: p.player as usize,
+                display_name: FrontendUser::display_name_or_anon(
+                    p.display_name.as_ref(),
+                    p.user.is_some(),
+                ),
+                ws_sender: None,
+            })
+            .collect::<Vec<_>>();
+        slots.sort_by_key(|slot| slot.player_id);
+        if slots
+            .iter()
+            .enumerate()
+            .any(|(i, slot)| slot.player_id != i)
+        {
+            bail!("Players for game {game_id} aren't numbered in order")
+        }
+
+        let (bc_tx, _bc_rx) = broadcast::channel(self.channel_capacity);
+        let (mp_tx, mp_rx) = mpsc::channel(100);
+        let (ch_tx, ch_rx) = mpsc::channel(100);
+        let handle = GameHandle {
+            to_client: bc_tx.clone(),
+            from_client: mp_tx,
+            game_events: ch_tx,
+            players: slots,
+            max_players: game.max_players,
+            owner: game.owner,
+            is_started: game.is_started,
+            start_time: game.start_time,
+        };
+        let game_handler = GameHandler::new(
+            game,
+            minesweeper,
+            self.clone(),
+            bc_tx,
+            mp_rx,
+            ch_rx,
+            self.shutdown.subscribe(),
+        );
+        {
+            let mut games = self.games.write().await;
+            games.insert(game_id.to_string(), handle);
+        }
+        self.active_cache.invalidate().await;
+        let span = tracing::info_span!("game", %game_id);
+        tokio::spawn(game_handler.handle_game().instrument(span));
+        Ok(())
+    }
+
+    /// Resumes every game left running by the last shutdown, completing any which can't be
+    /// resumed
+    pub async fn resume_incomplete_games(&self) -> Result<()> {
+        for game_id in Game::get_incomplete_game_ids(&self.db).await? {
+            if let Err(e) = self.resume_game(&game_id).await {
+                log::error!("Error resuming game {game_id}: {e}");
+                Game::set_completed(&self.db, &game_id).await?;
+            }
+        }
         Ok(())
     }
 
@@ -123,8 +619,9 @@ impl GameManager {
             .is_some()
     }
 
-    pub async fn get_active_games(&self) -> Vec<SimpleGameWithPlayers> {
-        self.active_cache
+    pub async fn get_active_games(&self, query: GameQuery) -> Vec<SimpleGameWithPlayers> {
+        let games = self
+            .active_cache
             .get_or_set(|| async {
                 let game_ids = {
                     let games = self.games.read().await;
@@ -141,7 +638,8 @@ impl GameManager {
                     .await
                     .unwrap_or_default()
             })
-            .await
+            .await;
+        query.apply(games)
     }
 
     pub async fn get_recent_games(&self) -> Vec<SimpleGameWithPlayers> {
@@ -208,39 +706,193 @@ impl GameManager {
             })
     }
 
+    pub async fn metrics(&self) -> GameManagerMetrics {
+        let games = self.games.read().await;
+        games
+            .values()
+            .fold(GameManagerMetrics::default(), |mut metrics, handle| {
+                metrics.active_games += 1;
+                metrics.total_players += handle.players.len();
+                metrics.total_spectators += handle.spectator_count();
+                metrics
+            })
+    }
+
     pub async fn game_is_active(&self, game_id: &str) -> bool {
         let games = self.games.read().await;
         games.contains_key(game_id)
     }
 
-    pub async fn join_game(
+    /// Watches a game without taking a player slot - no `Player` row is created.
+    ///
+    /// The websocket is sent the current viewer board and players, and the returned receiver
+    /// gets every broadcast game update. Use [`GameManager::play_game`] to take a slot.
+    ///
+    /// When `tagged`, messages sent directly to the websocket are wrapped in a
+    /// `TaggedGameMessage` so one socket can follow several games.  Broadcast updates from the
+    /// receiver are left for the caller to tag.
+    ///
+    /// Games with `max_spectators` already watching are handled by the `SpectatorOverflow`
+    /// policy set with [`GameManager::with_spectator_limits`].
+    pub async fn spectate_game(
         &self,
         game_id: &str,
         ws_sender: Arc<Mutex<SplitSink<WebSocket, Message>>>,
-    ) -> Result<broadcast::Receiver<String>> {
-        let (start_time, game_events, to_client) = {
-            let games = self.games.read().await;
-            if !games.contains_key(game_id) {
-                bail!("Game with id {game_id} doesn't exist")
-            }
-            let handle = games.get(game_id).unwrap();
-            (
-                handle.start_time,
-                handle.game_events.clone(),
-                handle.to_client.clone(),
-            )
+        tagged: bool,
+    ) -> Result<broadcast::Receiver<String>, SpectateError> {
+        let ViewerChannels {
+            start_time,
+            game_events,
+            to_client,
+        } = self.viewer_channels(game_id).await?;
+        let viewer = ViewerHandle {
+            ws_sender,
+            tag: tagged.then(|| game_id.to_string()),
         };
         if let Some(dt) = start_time {
-            let mut sender = ws_sender.lock().await;
-            let start_time_msg =
+            let mut sender = viewer.ws_sender.lock().await;
+            let start_time_msg = viewer.tagged(
                 GameMessage::SyncTimer(Utc::now().signed_duration_since(dt).num_seconds() as usize)
-                    .into_json();
+                    .into_json(),
+            );
             let _ = sender.send(Message::Text(start_time_msg)).await;
         };
         game_events
-            .send(GameEvent::Viewer(ViewerHandle { ws_sender }))
+            .send(GameEvent::Viewer(viewer))
+            .await
+            .map_err(|_| SpectateError::Closed(game_id.to_string()))?;
+        Ok(to_client)
+    }
+
+    /// Plays a finished game back at `speed` times its original pace.
+    ///
+    /// Spectators asking for the same game and speed share one playback - the returned board
+    /// is the playback so far, and the receiver gets each `PlayOutcome` after it.  The receiver
+    /// closes once the playback ends.  The stream is returned too, to resync receivers which
+    /// fall behind.
+    pub async fn watch_replay(
+        &self,
+        game_id: &str,
+        speed: f64,
+    ) -> Result<(ReplayStream, Board<PlayerCell>, broadcast::Receiver<String>)> {
+        if !(speed > 0.0 && speed <= MAX_REPLAY_SPEED) {
+            bail!("Replay speed must be above 0 and at most {MAX_REPLAY_SPEED}")
+        }
+        let key = (game_id.to_string(), speed.to_bits());
+        let mut replays = self.replays.write().await;
+        if let Some(stream) = replays.get(&key) {
+            let (board, rx) = stream.subscribe().await;
+            return Ok((stream.clone(), board, rx));
+        }
+        let game = Game::get_game(&self.db, game_id)
+            .await?
+            .ok_or(anyhow!("Game does not exist"))?;
+        if !game.is_completed {
+            bail!("Game with id {game_id} isn't finished")
+        }
+        let log = GameLog::get_log(&self.db, game_id)
+            .await?
+            .ok_or(anyhow!("Game with id {game_id} has no log"))?
+            .log;
+        let moves = log
+            .iter()
+            .filter(|(_, outcome)| !matches!(outcome, PlayOutcome::Flag(_)))
+            .count();
+        let delay = move_delay(game.seconds, moves, speed);
+
+        let stream = ReplayStream::new(game.rows as usize, game.cols as usize);
+        let (board, rx) = stream.subscribe().await;
+        replays.insert(key.clone(), stream.clone());
+        tokio::spawn(
+            stream
+                .clone()
+                .play(log, delay, Arc::clone(&self.replays), key),
+        );
+        Ok((stream, board, rx))
+    }
+
+    /// Next updates for a socket following `game_id`, or `None` once the game is gone.
+    ///
+    /// A socket which fell more than the channel capacity behind has missed updates, so it's
+    /// sent the current board and players instead - the player's own board if `ws_sender` is
+    /// seated in the game.
+    pub async fn next_updates(
+        &self,
+        game_id: &str,
+        rx: &mut broadcast::Receiver<String>,
+        ws_sender: Option<&Arc<Mutex<SplitSink<WebSocket, Message>>>>,
+    ) -> Option<Vec<String>> {
+        match rx.recv().await {
+            Ok(msg) => Some(vec![msg]),
+            Err(RecvError::Lagged(missed)) => {
+                log::debug!("Socket missed {missed} updates for game {game_id}, resyncing");
+                self.resync(game_id, ws_sender)
+                    .await
+                    .map_err(|e| log::error!("Error resyncing game {game_id}: {e}"))
+                    .ok()
+            }
+            Err(RecvError::Closed) => None,
+        }
+    }
+
+    async fn resync(
+        &self,
+        game_id: &str,
+        ws_sender: Option<&Arc<Mutex<SplitSink<WebSocket, Message>>>>,
+    ) -> Result<Vec<String>> {
+        let (player_id, game_events) = {
+            let games = self.games.read().await;
+            let Some(handle) = games.get(game_id) else {
+                bail!("Game with id {game_id} doesn't exist")
+            };
+            let player_id = ws_sender
+                .and_then(|s| handle.players.iter().find(|p| p.is_socket(s)))
+                .map(|p| p.player_id);
+            (player_id, handle.game_events.clone())
+        };
+        let (reply, resynced) = oneshot::channel();
+        game_events
+            .send(GameEvent::Resync { player_id, reply })
             .await?;
-        Ok(to_client.subscribe())
+        Ok(resynced.await?)
+    }
+
+    async fn viewer_channels(&self, game_id: &str) -> Result<ViewerChannels, SpectateError> {
+        let channels = self.try_viewer_channels(game_id).await;
+        let SpectatorOverflow::Queue { max_wait } = self.spectator_limits.overflow else {
+            return channels;
+        };
+        if !matches!(channels, Err(SpectateError::Full(_))) {
+            return channels;
+        }
+        let wait_for_spot = async {
+            let mut check_interval = interval(SPECTATOR_QUEUE_CHECK);
+            loop {
+                check_interval.tick().await;
+                match self.try_viewer_channels(game_id).await {
+                    Err(SpectateError::Full(_)) => continue,
+                    res => return res,
+                }
+            }
+        };
+        timeout(max_wait, wait_for_spot).await.unwrap_or(channels)
+    }
+
+    async fn try_viewer_channels(&self, game_id: &str) -> Result<ViewerChannels, SpectateError> {
+        // write lock so two spectators can't both take the last spot
+        let games = self.games.write().await;
+        let Some(handle) = games.get(game_id) else {
+            return Err(SpectateError::NotFound(game_id.to_string()));
+        };
+        let max_spectators = self.spectator_limits.max_spectators;
+        if handle.spectator_count() >= max_spectators {
+            return Err(SpectateError::Full(max_spectators));
+        }
+        Ok(ViewerChannels {
+            start_time: handle.start_time,
+            game_events: handle.game_events.clone(),
+            to_client: handle.to_client.subscribe(),
+        })
     }
 
     pub async fn play_game(
@@ -248,7 +900,7 @@ impl GameManager {
         game_id: &str,
         user: &Option<User>,
         ws_sender: Arc<Mutex<SplitSink<WebSocket, Message>>>,
-    ) -> Result<mpsc::Sender<String>> {
+    ) -> Result<(usize, mpsc::Sender<String>)> {
         let user_id = user.as_ref().map(|u| u.id);
         let display_name = user.as_ref().and_then(|u| u.display_name.as_ref());
 
@@ -272,19 +924,19 @@ impl GameManager {
                         bail!("Game already has max players")
                     }
                     save_player = true;
-                    handle.players.push(PlayerHandle {
+                    handle.players.push(PlayerSlot {
                         user_id,
                         player_id,
                         display_name: FrontendUser::display_name_or_anon(
                             display_name,
                             user.is_some(),
                         ),
-                        ws_sender: Arc::clone(&ws_sender),
+                        ws_sender: Some(Arc::clone(&ws_sender)),
                     });
                     player_id
                 }
                 Some(p) => {
-                    p.ws_sender = Arc::clone(&ws_sender);
+                    p.ws_sender = Some(Arc::clone(&ws_sender));
                     p.player_id
                 }
             };
@@ -312,7 +964,69 @@ impl GameManager {
                 ws_sender: Arc::clone(&ws_sender),
             }))
             .await?;
-        Ok(from_client)
+        Ok((player_id, from_client))
+    }
+
+    /// Called when a player's websocket closes - the player keeps their slot and can rejoin
+    pub async fn leave_game(
+        &self,
+        game_id: &str,
+        player_id: usize,
+        ws_sender: Arc<Mutex<SplitSink<WebSocket, Message>>>,
+    ) -> Result<()> {
+        let (player, game_events, new_owner) = {
+            let mut games = self.games.write().await;
+            let Some(handle) = games.get_mut(game_id) else {
+                // game already completed
+                return Ok(());
+            };
+            let Some(slot) = handle.players.get(player_id) else {
+                bail!("Player {player_id} doesn't exist in game {game_id}")
+            };
+            let player = PlayerHandle {
+                user_id: slot.user_id,
+                player_id,
+                display_name: slot.display_name.clone(),
+                ws_sender,
+            };
+            let new_owner = if Self::owner_left(handle, &player) {
+                // next logged in player, otherwise anyone who joined can start
+                let next = handle
+                    .players
+                    .iter()
+                    .find(|p| p.user_id.is_some() && p.user_id != handle.owner)
+                    .and_then(|p| p.user_id);
+                handle.owner = next;
+                Some(next)
+            } else {
+                None
+            };
+            if let Some(slot) = handle.players.get_mut(player_id) {
+                if slot.is_socket(&player.ws_sender) {
+                    slot.ws_sender = None;
+                }
+            }
+            (player, handle.game_events.clone(), new_owner)
+        };
+        if let Some(owner) = new_owner {
+            log::debug!("Owner left game {game_id} before start, new owner {owner:?}");
+            Game::set_owner(&self.db, game_id, owner).await?;
+        }
+        game_events.send(GameEvent::Leave(player)).await?;
+        Ok(())
+    }
+
+    /// Whether `player` leaving strands an unstarted game - ignores sockets already replaced
+    /// by a rejoin
+    fn owner_left(handle: &GameHandle, player: &PlayerHandle) -> bool {
+        let is_current = handle
+            .players
+            .get(player.player_id)
+            .is_some_and(|p| p.is_socket(&player.ws_sender));
+        !handle.is_started
+            && is_current
+            && player.user_id.is_some()
+            && player.user_id == handle.owner
     }
 
     pub async fn start_game(&self, game_id: &str, user: &Option<User>) -> Result<()> {
@@ -334,6 +1048,7 @@ impl GameManager {
                     }
                 }
             }
+            handle.is_started = true;
             handle.game_events.clone()
         };
         sender.send(GameEvent::Start).await?;
@@ -341,6 +1056,37 @@ impl GameManager {
         Ok(())
     }
 
+    /// Hands an unstarted game to another player who joined it - only the owner can do this
+    pub async fn transfer_ownership(
+        &self,
+        game_id: &str,
+        from_user: &User,
+        to_user: i64,
+    ) -> Result<()> {
+        let joined = Player::get_players(&self.db, game_id)
+            .await?
+            .iter()
+            .any(|p| p.user == Some(to_user));
+        if !joined {
+            bail!("New owner hasn't joined game {game_id}")
+        }
+        {
+            let mut games = self.games.write().await;
+            let Some(handle) = games.get_mut(game_id) else {
+                bail!("Game with id {game_id} doesn't exist")
+            };
+            if handle.is_started {
+                bail!("Can't transfer ownership of a started game")
+            }
+            if handle.owner != Some(from_user.id) {
+                bail!("Ownership transfer attempted by non-owner")
+            }
+            handle.owner = Some(to_user);
+        }
+        Game::set_owner(&self.db, game_id, Some(to_user)).await?;
+        Ok(())
+    }
+
     pub async fn set_start_time(&self, game_id: &str) -> Result<DateTime<Utc>> {
         let now = Utc::now();
         {
@@ -381,6 +1127,8 @@ impl GameManager {
             let mut games = self.games.write().await;
             games.remove(game_id);
         }
+        self.active_cache.invalidate().await;
+        self.recent_cache.invalidate().await;
         Ok(())
     }
 
@@ -389,12 +1137,36 @@ impl GameManager {
         Ok(())
     }
 
-    async fn update_players(&self, game_id: &str, players: Vec<ClientPlayer>) -> Result<()> {
-        Player::update_players(&self.db, game_id, players).await?;
+    /// Keeps the owner's time if `game` is a daily challenge they've won faster than before
+    async fn save_daily_result(&self, game: &Game, seconds: i64) -> Result<()> {
+        let Some(user) = game.owner else {
+            return Ok(());
+        };
+        if let Some(day) = DailyGame::get_day(&self.db, &game.game_id).await? {
+            // only the day's shared board counts, e.g. not one from before a secret change
+            if game.seed != Some(daily_seed(&self.daily_secret, day) as i64) {
+                return Ok(());
+            }
+            DailyResult::save_best(&self.db, user, day, &game.game_id, seconds).await?;
+        }
         Ok(())
     }
 
-    pub async fn was_playing(&self, game_id: &str, user: &Option<User>) -> bool {
+    /// Drops the handle of a game stopped by shutdown, leaving it incomplete in the db
+    async fn release_game(&self, game_id: &str) {
+        {
+            let mut games = self.games.write().await;
+            games.remove(game_id);
+        }
+        self.active_cache.invalidate().await;
+    }
+
+    async fn update_players(&self, game_id: &str, players: Vec<ClientPlayer>) -> Result<()> {
+        Player::update_players(&self.db, game_id, players).await?;
+        Ok(())
+    }
+
+    pub async fn was_playing(&self, game_id: &str, user: &Option<User>) -> bool {
         if user.is_none() {
             return false;
         }
@@ -414,38 +1186,84 @@ struct GameHandler {
     broadcaster: broadcast::Sender<String>,
     receiver: mpsc::Receiver<String>,
     game_events: mpsc::Receiver<GameEvent>,
+    shutdown: watch::Receiver<bool>,
     player_handles: Vec<Option<PlayerHandle>>,
+    /// When each player's socket closed, if they haven't come back
+    left_at: Vec<Option<DateTime<Utc>>>,
     minesweeper: Minesweeper,
 }
 
+/// Seed for a game which wasn't given one
+fn random_seed() -> Result<u64> {
+    let mut bytes = [0; 8];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow!("Error generating seed: {e}"))?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// Fresh board for `game` from its saved seed - fails instead of panicking on impossible
+/// stored dimensions
+fn new_minesweeper(game: &Game) -> Result<Minesweeper> {
+    let mut minesweeper = MinesweeperBuilder::new(MinesweeperOpts {
+        rows: game.rows as usize,
+        cols: game.cols as usize,
+        num_mines: game.num_mines as usize,
+    })?
+    .with_superclick()
+    .with_log();
+    if game.max_players > 1 {
+        minesweeper = minesweeper
+            .with_multiplayer(game.max_players as usize)
+            .with_min_opening(MULTIPLAYER_MIN_OPENING);
+    }
+    if let Some(seed) = game.seed {
+        minesweeper = minesweeper.with_seed(seed as u64);
+    }
+    Ok(minesweeper.init())
+}
+
+/// Rebuilds a running game by replaying its log on the board its seed generates.  Resigning
+/// isn't logged, so resigned players are taken from the saved players.
+fn resumed_minesweeper(
+    game: &Game,
+    players: &[PlayerUser],
+    log: &[(Play, PlayOutcome)],
+) -> Result<Minesweeper> {
+    if game.seed.is_none() && !log.is_empty() {
+        bail!("Game with id {} has no saved seed", game.game_id)
+    }
+    let mut minesweeper = new_minesweeper(game)?;
+    minesweeper.replay_log(log)?;
+    for player in players.iter().filter(|p| p.dead) {
+        // players who hit a mine are dead already
+        let _ = minesweeper.resign(player.player as usize);
+    }
+    if minesweeper.is_over() {
+        bail!("Game with id {} is already over", game.game_id)
+    }
+    Ok(minesweeper)
+}
+
 impl GameHandler {
     fn new(
         game: Game,
+        minesweeper: Minesweeper,
         game_manager: GameManager,
         broadcaster: broadcast::Sender<String>,
         receiver: mpsc::Receiver<String>,
         game_events: mpsc::Receiver<GameEvent>,
+        shutdown: watch::Receiver<bool>,
     ) -> Self {
         let player_handles = vec![None; game.max_players as usize];
-        let mut minesweeper = MinesweeperBuilder::new(MinesweeperOpts {
-            rows: game.rows as usize,
-            cols: game.cols as usize,
-            num_mines: game.num_mines as usize,
-        })
-        .unwrap()
-        .with_superclick()
-        .with_log();
-        if game.max_players > 1 {
-            minesweeper = minesweeper.with_multiplayer(game.max_players as usize);
-        }
-        let minesweeper = minesweeper.init();
+        let left_at = vec![None; game.max_players as usize];
         Self {
             game,
             game_manager,
             broadcaster,
             receiver,
             game_events,
+            shutdown,
             player_handles,
+            left_at,
             minesweeper,
         }
     }
@@ -453,27 +1271,29 @@ impl GameHandler {
     async fn handle_game(mut self) {
         let mut checks_interval = interval(Duration::from_secs(5));
 
-        let mut first_play = false;
+        // resumed games may already be under way
+        let mut start_time = self.game.start_time;
+        let mut first_play = start_time.is_some();
         let mut needs_save = false;
         let mut timed_out = false;
-        let mut start_time = None;
+        let mut shutting_down = false;
         let mut last_action = Utc::now();
 
         loop {
             tokio::select! {
                 Some(msg) = self.receiver.recv() => {
-                    log::debug!("Message received {}: {}", self.game.game_id, msg);
+                    tracing::debug!("Message received: {}", msg);
                     let played = self.handle_message(&msg).await.is_some();
                     if played {
                         needs_save = true;
                     }
                     if played && !first_play {
                         first_play = true;
-                        if let Ok(st) = self.game_manager.set_start_time(&self.game.game_id).await.map_err(|e| log::error!("Error setting start time: {e}")) {
+                        if let Ok(st) = self.game_manager.set_start_time(&self.game.game_id).await.map_err(|e| tracing::error!("Error setting start time: {e}")) {
                             start_time = Some(st)
                         }
                         let sync_msg = GameMessage::SyncTimer(0).into_json();
-                        log::debug!("Sending sync_msg {:?}", sync_msg);
+                        tracing::debug!("Sending sync_msg {:?}", sync_msg);
                         let _ = self.broadcaster.send(sync_msg);
                     }
                     last_action = Utc::now();
@@ -482,24 +1302,35 @@ impl GameHandler {
                     }
                 },
                 Some(event) = self.game_events.recv() => {
-                    log::debug!("Game update received {}: {:?}", self.game.game_id, event);
+                    tracing::debug!("Game update received: {:?}", event);
                     self.handle_game_event(event).await;
                     last_action = Utc::now();
                 }
+                Ok(()) = self.shutdown.changed() => {
+                    tracing::debug!("Server shutting down");
+                    shutting_down = true;
+                    break;
+                }
                 _ = checks_interval.tick() => {
-                    log::debug!("Checking for game {}", self.game.game_id);
+                    tracing::debug!("Checking for game");
                     let now = Utc::now();
                     if let Some(st) = start_time {
                         if now.signed_duration_since(st).num_seconds() >= 999 {
-                            log::debug!("Game over time {}", self.game.game_id);
+                            tracing::debug!("Game over time");
                             break;
                         }
                     }
                     if now.signed_duration_since(last_action).num_seconds() >= 120 {
-                        log::debug!("Game timed out {}", self.game.game_id);
+                        tracing::debug!("Game timed out");
                         timed_out = true;
                         break;
                     }
+                    if self.resign_absent_players(now) {
+                        needs_save = true;
+                        if self.minesweeper.is_over() {
+                            break;
+                        }
+                    }
                     if needs_save {
                         self.save_game_state_nonblocking();
                         needs_save = false;
@@ -508,9 +1339,16 @@ impl GameHandler {
             }
         }
 
+        if shutting_down {
+            // saved with its log, so it can be resumed on restart
+            self.save_game_state().await;
+            self.game_manager.release_game(&self.game.game_id).await;
+            return;
+        }
         if needs_save {
             self.save_game_state().await;
         }
+        let won = self.minesweeper.status() == GameStatus::Won;
         let minesweeper = self.minesweeper.complete();
         let (end_time, seconds) = if let Some(st) = start_time {
             if !timed_out {
@@ -533,38 +1371,37 @@ impl GameHandler {
                 timed_out,
             )
             .await
-            .map_err(|e| log::error!("Error completing game: {e}"));
+            .map_err(|e| tracing::error!("Error completing game: {e}"));
+        if let (true, Some(seconds)) = (won, seconds) {
+            let _ = self
+                .game_manager
+                .save_daily_result(&self.game, seconds)
+                .await
+                .map_err(|e| tracing::error!("Error saving daily result: {e}"));
+        }
         if let Some(game_log) = minesweeper.get_log() {
             let _ = self
                 .game_manager
                 .save_game_log(&self.game.game_id, game_log)
                 .await
-                .map_err(|e| log::error!("Error saving game log: {e}"));
+                .map_err(|e| tracing::error!("Error saving game log: {e}"));
         }
     }
 
     fn handles_to_client_players(&self) -> Vec<Option<ClientPlayer>> {
-        let current_top_score = self.minesweeper.current_top_score();
+        let snapshots = self.minesweeper.all_player_snapshots();
         self.player_handles
             .iter()
             .map(|item| {
                 item.as_ref().map(|player| {
-                    let player_score = self.minesweeper.player_score(player.player_id).unwrap_or(0);
+                    let snapshot = snapshots.get(player.player_id).copied().unwrap_or_default();
                     ClientPlayer {
                         player_id: player.player_id,
                         username: player.display_name.to_owned(),
-                        dead: self
-                            .minesweeper
-                            .player_dead(player.player_id)
-                            .unwrap_or(false),
-                        victory_click: self
-                            .minesweeper
-                            .player_victory_click(player.player_id)
-                            .unwrap_or(false),
-                        top_score: current_top_score
-                            .map(|s| s == player_score)
-                            .unwrap_or(false),
-                        score: player_score,
+                        dead: snapshot.dead,
+                        victory_click: snapshot.victory_click,
+                        top_score: snapshot.top_score,
+                        score: snapshot.score,
                     }
                 })
             })
@@ -577,17 +1414,24 @@ impl GameHandler {
             .into_iter()
             .flatten()
             .collect();
-        log::debug!("Saving game - players: {:?}", &players);
+        tracing::debug!("Saving game - players: {:?}", &players);
         let _ = self
             .game_manager
             .update_players(&self.game.game_id, players)
             .await
-            .map_err(|e| log::error!("Error updating players: {e}"));
+            .map_err(|e| tracing::error!("Error updating players: {e}"));
         let _ = self
             .game_manager
             .save_game(&self.game.game_id, self.minesweeper.viewer_board())
             .await
-            .map_err(|e| log::error!("Error saving game: {e}"));
+            .map_err(|e| tracing::error!("Error saving game: {e}"));
+        if let Some(game_log) = self.minesweeper.get_log() {
+            let _ = self
+                .game_manager
+                .save_game_log(&self.game.game_id, game_log)
+                .await
+                .map_err(|e| tracing::error!("Error saving game log: {e}"));
+        }
     }
 
     fn save_game_state_nonblocking(&self) {
@@ -598,19 +1442,27 @@ impl GameHandler {
             .collect();
         let game_id = self.game.game_id.clone();
         let board = self.minesweeper.viewer_board();
+        let game_log = self.minesweeper.get_log();
         let game_manager = self.game_manager.clone();
-        log::debug!("Saving game - players: {:?}", &players);
-        tokio::spawn(async move {
+        tracing::debug!("Saving game - players: {:?}", &players);
+        let save = async move {
             let game_id = game_id;
             let _ = game_manager
                 .update_players(&game_id, players)
                 .await
-                .map_err(|e| log::error!("Error updating players: {e}"));
+                .map_err(|e| tracing::error!("Error updating players: {e}"));
             let _ = game_manager
                 .save_game(&game_id, board)
                 .await
-                .map_err(|e| log::error!("Error saving game: {e}"));
-        });
+                .map_err(|e| tracing::error!("Error saving game: {e}"));
+            if let Some(game_log) = game_log {
+                let _ = game_manager
+                    .save_game_log(&game_id, game_log)
+                    .await
+                    .map_err(|e| tracing::error!("Error saving game log: {e}"));
+            }
+        };
+        tokio::spawn(save.in_current_span());
     }
 
     async fn handle_game_event(&mut self, event: GameEvent) {
@@ -619,31 +1471,63 @@ impl GameHandler {
                 let player_sender = Arc::clone(&player.ws_sender);
                 let player_id = player.player_id;
                 let player_board = self.minesweeper.player_board(player_id);
+                Minesweeper::assert_no_mine_leak(&player_board);
                 self.player_handles[player_id] = Some(player);
+                self.left_at[player_id] = None;
                 {
                     let mut player_sender = player_sender.lock().await;
                     let player_msg = GameMessage::GameState(player_board).into_json();
-                    log::debug!("Sending player_msg {:?}", player_msg);
+                    tracing::debug!("Sending player_msg {:?}", player_msg);
                     let _ = player_sender.send(Message::Text(player_msg)).await;
                 }
 
                 let players = self.handles_to_client_players();
+                if let Some(Some(joined)) = players.get(player_id) {
+                    let joined_msg = GameMessage::PlayerJoined(joined.clone()).into_json();
+                    let _ = self.broadcaster.send(joined_msg);
+                }
                 let players_msg = GameMessage::PlayersState(players).into_json();
-                log::debug!("Sending players_msg {:?}", players_msg);
+                tracing::debug!("Sending players_msg {:?}", players_msg);
                 let _ = self.broadcaster.send(players_msg);
             }
+            GameEvent::Leave(player) => {
+                let player_id = player.player_id;
+                // ignore sockets which were already replaced by a rejoin
+                let current = self.player_handles.get(player_id).and_then(|p| p.as_ref());
+                if current.is_some_and(|p| Arc::ptr_eq(&p.ws_sender, &player.ws_sender)) {
+                    self.left_at[player_id] = Some(Utc::now());
+                    let left_msg = GameMessage::PlayerLeft { player_id }.into_json();
+                    let _ = self.broadcaster.send(left_msg);
+                }
+            }
             GameEvent::Viewer(viewer) => {
                 let viewer_board = self.minesweeper.viewer_board();
+                Minesweeper::assert_no_mine_leak(&viewer_board);
                 {
                     let mut viewer_sender = viewer.ws_sender.lock().await;
-                    let viewer_msg = GameMessage::GameState(viewer_board).into_json();
-                    log::debug!("Sending viewer_msg {:?}", viewer_msg);
+                    let meta_msg = viewer.tagged(self.game_meta().into_json());
+                    let _ = viewer_sender.send(Message::Text(meta_msg)).await;
+                    let viewer_msg =
+                        viewer.tagged(GameMessage::GameState(viewer_board).into_json());
+                    tracing::debug!("Sending viewer_msg {:?}", viewer_msg);
                     let _ = viewer_sender.send(Message::Text(viewer_msg)).await;
                     let players = self.handles_to_client_players();
-                    let players_msg = GameMessage::PlayersState(players).into_json();
+                    let players_msg = viewer.tagged(GameMessage::PlayersState(players).into_json());
                     let _ = viewer_sender.send(Message::Text(players_msg)).await;
                 }
             }
+            GameEvent::Resync { player_id, reply } => {
+                let board = match player_id {
+                    Some(player_id) => self.minesweeper.player_board(player_id),
+                    None => self.minesweeper.viewer_board(),
+                };
+                Minesweeper::assert_no_mine_leak(&board);
+                let players = self.handles_to_client_players();
+                let _ = reply.send(vec![
+                    GameMessage::GameState(board).into_json(),
+                    GameMessage::PlayersState(players).into_json(),
+                ]);
+            }
             GameEvent::Start => {
                 self.game.is_started = true;
                 let start_msg = GameMessage::GameStarted.into_json();
@@ -652,6 +1536,56 @@ impl GameHandler {
         }
     }
 
+    /// Mercy rule for multiplayer - players gone longer than `ABSENT_PLAYER_RESIGN_SECS` from a
+    /// started game resign, so the others aren't left waiting on them.  Returns whether anyone
+    /// resigned.
+    fn resign_absent_players(&mut self, now: DateTime<Utc>) -> bool {
+        if self.game.max_players < 2 || !self.game.is_started {
+            return false;
+        }
+        let mut resigned = false;
+        for player_id in 0..self.left_at.len() {
+            let Some(left_at) = self.left_at[player_id] else {
+                continue;
+            };
+            if now.signed_duration_since(left_at).num_seconds() < ABSENT_PLAYER_RESIGN_SECS {
+                continue;
+            }
+            self.left_at[player_id] = None;
+            // already dead players have nothing to resign
+            if self.minesweeper.resign(player_id).is_err() {
+                continue;
+            }
+            tracing::debug!("Player {player_id} resigned after leaving");
+            resigned = true;
+            if let Some(player) = &self.player_handles[player_id] {
+                let update =
+                    client_player(&self.minesweeper, player_id, &player.display_name, false);
+                let _ = self
+                    .broadcaster
+                    .send(GameMessage::PlayerUpdate(update).into_json());
+            }
+        }
+        resigned
+    }
+
+    fn game_meta(&self) -> GameMessage {
+        let game = &self.game;
+        GameMessage::GameMeta {
+            rows: game.rows as usize,
+            cols: game.cols as usize,
+            num_mines: game.num_mines as usize,
+            max_players: game.max_players as usize,
+            mode: GameSettings::new(
+                game.rows,
+                game.cols,
+                game.num_mines,
+                game.max_players as i64,
+            )
+            .into(),
+        }
+    }
+
     async fn handle_message(&mut self, msg: &str) -> Option<()> {
         if !self.game.is_started {
             return None;
@@ -661,6 +1595,12 @@ impl GameHandler {
             ClientMessage::Play(p) => p,
             _ => return None,
         };
+        let span = tracing::debug_span!("play", player_id = play.player);
+        self.handle_play(play).instrument(span).await
+    }
+
+    async fn handle_play(&mut self, play: Play) -> Option<()> {
+        tracing::debug!("Playing {:?} at {:?}", play.action, play.point);
         if play.player > self.player_handles.len() {
             return None;
         }
@@ -669,11 +1609,23 @@ impl GameHandler {
         } else {
             return None;
         };
+        let before = client_player(
+            &self.minesweeper,
+            player.player_id,
+            &player.display_name,
+            false,
+        );
         let outcome = self.minesweeper.play(play);
         let res = match outcome {
             Ok(res) => res,
             Err(e) => {
-                let err_msg = GameMessage::Error(format!("{:?}", e)).into_json();
+                let err_msg = match e {
+                    PlayError::DoubleClickMismatch { expected, got } => {
+                        GameMessage::DoubleClickMismatch { expected, got }
+                    }
+                    e => GameMessage::Error(e.to_string()),
+                }
+                .into_json();
                 {
                     let mut player_sender = player.ws_sender.lock().await;
                     let _ = player_sender.send(Message::Text(err_msg)).await;
@@ -682,29 +1634,41 @@ impl GameHandler {
             }
         };
         match res {
+            // flags are private - only the placing player is told, never the broadcast
             PlayOutcome::Flag(flag) => {
                 let flag_msg = GameMessage::PlayOutcome(PlayOutcome::Flag(flag)).into_json();
                 {
                     let mut player_sender = player.ws_sender.lock().await;
                     let _ = player_sender.send(Message::Text(flag_msg)).await;
                 }
-                None
+                if !self.minesweeper.is_over() {
+                    return None;
+                }
+                // a flag won the game - everyone learns of the win, but not where the flag is
+                let victory_msg =
+                    GameMessage::PlayOutcome(PlayOutcome::Victory(Vec::new())).into_json();
+                let after = client_player(
+                    &self.minesweeper,
+                    player.player_id,
+                    &player.display_name,
+                    true,
+                );
+                let _ = self.broadcaster.send(victory_msg);
+                let _ = self
+                    .broadcaster
+                    .send(GameMessage::PlayerUpdate(after).into_json());
+                Some(())
             }
             default => {
                 let victory_click = matches!(default, PlayOutcome::Victory(_));
                 let outcome_msg = GameMessage::PlayOutcome(default).into_json();
-                let score = self.minesweeper.player_score(player.player_id).unwrap();
-                let dead = self.minesweeper.player_dead(player.player_id).unwrap();
-                let top_score = self.minesweeper.player_top_score(player.player_id).unwrap();
-                let player_state = ClientPlayer {
-                    player_id: player.player_id,
-                    username: player.display_name.to_owned(),
-                    dead,
+                let after = client_player(
+                    &self.minesweeper,
+                    player.player_id,
+                    &player.display_name,
                     victory_click,
-                    top_score,
-                    score,
-                };
-                let player_state_message = GameMessage::PlayerUpdate(player_state).into_json();
+                );
+                let player_state_message = player_state_message(&before, after).into_json();
                 let _ = self.broadcaster.send(outcome_msg);
                 let _ = self.broadcaster.send(player_state_message);
                 Some(())
@@ -712,3 +1676,861 @@ impl GameHandler {
         }
     }
 }
+
+fn client_player(
+    minesweeper: &Minesweeper,
+    player_id: usize,
+    username: &str,
+    victory_click: bool,
+) -> ClientPlayer {
+    ClientPlayer {
+        player_id,
+        username: username.to_owned(),
+        dead: minesweeper.player_dead(player_id).unwrap(),
+        victory_click,
+        top_score: minesweeper.player_top_score(player_id).unwrap(),
+        score: minesweeper.player_score(player_id).unwrap(),
+    }
+}
+
+/// Broadcast after a play - just the score change unless the player died, won, or took or
+/// lost the top score
+fn player_state_message(before: &ClientPlayer, after: ClientPlayer) -> GameMessage {
+    if after.dead != before.dead || after.victory_click || after.top_score != before.top_score {
+        GameMessage::PlayerUpdate(after)
+    } else {
+        GameMessage::ScoreDelta {
+            player_id: after.player_id,
+            delta: after.score - before.score,
+            new_score: after.score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use minesweeper_lib::board::BoardPoint;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    fn game(
+        game_id: &str,
+        settings: (i64, i64, i64, u8),
+        num_players: u8,
+    ) -> SimpleGameWithPlayers {
+        let (rows, cols, num_mines, max_players) = settings;
+        SimpleGameWithPlayers {
+            game_id: game_id.to_string(),
+            owner: None,
+            rows,
+            cols,
+            num_mines,
+            max_players,
+            is_completed: false,
+            is_started: false,
+            start_time: None,
+            end_time: None,
+            timed_out: None,
+            seconds: None,
+            num_players,
+            top_score: None,
+            is_daily: false,
+        }
+    }
+
+    const BEGINNER: (i64, i64, i64, u8) = (9, 9, 10, 1);
+    const EXPERT: (i64, i64, i64, u8) = (16, 30, 99, 1);
+    const SMALL_MULTIPLAYER: (i64, i64, i64, u8) = (16, 30, 80, 2);
+    const LARGE_MULTIPLAYER: (i64, i64, i64, u8) = (50, 50, 500, 8);
+
+    fn games() -> Vec<SimpleGameWithPlayers> {
+        vec![
+            game("beginner", BEGINNER, 1),
+            game("expert", EXPERT, 1),
+            game("small", SMALL_MULTIPLAYER, 1),
+            game("large_1", LARGE_MULTIPLAYER, 2),
+            game("large_2", LARGE_MULTIPLAYER, 5),
+            game("custom", (20, 20, 50, 8), 3),
+        ]
+    }
+
+    fn ids(games: Vec<SimpleGameWithPlayers>) -> Vec<String> {
+        games.into_iter().map(|g| g.game_id).collect()
+    }
+
+    /// Fresh in-memory database with every migration run
+    async fn test_db() -> SqlitePool {
+        let db = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&db).await.unwrap();
+        db
+    }
+
+    /// Single player 9x9 game which was never saved, for handlers built without a database
+    fn test_game(num_mines: i64, seed: Option<i64>) -> Game {
+        Game {
+            game_id: "game".to_string(),
+            owner: None,
+            rows: 9,
+            cols: 9,
+            num_mines,
+            max_players: 1,
+            is_completed: false,
+            is_started: false,
+            start_time: None,
+            end_time: None,
+            timed_out: None,
+            seconds: None,
+            final_board: None,
+            seed,
+        }
+    }
+
+    #[tokio::test]
+    async fn shutdown_saves_running_games() {
+        let db = test_db().await;
+        let game_manager = GameManager::new(db.clone());
+        game_manager
+            .new_game(
+                None,
+                "game",
+                GameParameters {
+                    rows: 9,
+                    cols: 9,
+                    num_mines: 10,
+                    max_players: 1,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(game_manager.game_is_active("game").await);
+
+        game_manager.shutdown(Duration::from_secs(5)).await;
+
+        assert!(!game_manager.game_is_active("game").await);
+        let game = Game::get_game(&db, "game").await.unwrap().unwrap();
+        assert!(!game.is_completed);
+        assert!(game.final_board.is_some());
+        assert!(GameLog::get_log(&db, "game").await.unwrap().is_some());
+
+        let restarted = GameManager::new(db.clone());
+        restarted.resume_incomplete_games().await.unwrap();
+        assert!(restarted.game_is_active("game").await);
+        restarted.shutdown(Duration::from_secs(5)).await;
+    }
+
+    #[tokio::test]
+    async fn metrics_track_running_games() {
+        let db = test_db().await;
+        let game_manager = GameManager::new(db);
+        assert_eq!(game_manager.metrics().await, GameManagerMetrics::default());
+
+        for game_id in ["one", "two"] {
+            game_manager
+                .new_game(
+                    None,
+                    game_id,
+                    GameParameters {
+                        rows: 9,
+                        cols: 9,
+                        num_mines: 10,
+                        max_players: 2,
+                    },
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        let _spectator = game_manager.viewer_channels("one").await.unwrap();
+        game_manager
+            .games
+            .write()
+            .await
+            .get_mut("two")
+            .unwrap()
+            .players
+            .push(PlayerSlot {
+                user_id: None,
+                player_id: 0,
+                display_name: "Anonymous".to_string(),
+                ws_sender: None,
+            });
+        assert_eq!(
+            game_manager.metrics().await,
+            GameManagerMetrics {
+                active_games: 2,
+                total_players: 1,
+                total_spectators: 1,
+            }
+        );
+
+        game_manager.shutdown(Duration::from_secs(5)).await;
+        assert_eq!(game_manager.metrics().await, GameManagerMetrics::default());
+    }
+
+    #[tokio::test]
+    async fn sweeper_removes_dead_handles() {
+        let db = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let game_manager = GameManager::new(db);
+        let (to_client, _) = broadcast::channel(100);
+        let (game_events, _game_events_rx) = mpsc::channel(100);
+        let handle = |from_client| GameHandle {
+            to_client: to_client.clone(),
+            from_client,
+            game_events: game_events.clone(),
+            players: Vec::new(),
+            max_players: 1,
+            owner: None,
+            is_started: true,
+            start_time: None,
+        };
+        let (alive, _alive_rx) = mpsc::channel(100);
+        // handler task dropped its receiver without completing the game
+        let (dead, _) = mpsc::channel(100);
+        {
+            let mut games = game_manager.games.write().await;
+            games.insert("alive".to_string(), handle(alive));
+            games.insert("dead".to_string(), handle(dead));
+        }
+
+        assert_eq!(game_manager.sweep_orphaned_games().await, vec!["dead"]);
+        assert!(game_manager.game_is_active("alive").await);
+        assert!(!game_manager.game_is_active("dead").await);
+        assert!(game_manager.sweep_orphaned_games().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn lagged_receiver_is_resynced() {
+        let db = test_db().await;
+        let game_manager = GameManager::new(db).with_channel_capacity(2);
+        game_manager
+            .new_game(
+                None,
+                "game",
+                GameParameters {
+                    rows: 9,
+                    cols: 9,
+                    num_mines: 10,
+                    max_players: 1,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let mut channels = game_manager.viewer_channels("game").await.unwrap();
+        let to_client = game_manager.games.read().await["game"].to_client.clone();
+        for i in 0..5 {
+            to_client.send(i.to_string()).unwrap();
+        }
+
+        let resync = game_manager
+            .next_updates("game", &mut channels.to_client, None)
+            .await
+            .unwrap();
+        assert_eq!(resync.len(), 2);
+        assert!(matches!(
+            serde_json::from_str::<GameMessage>(&resync[0]).unwrap(),
+            GameMessage::GameState(board) if board.rows() == 9
+        ));
+        assert!(matches!(
+            serde_json::from_str::<GameMessage>(&resync[1]).unwrap(),
+            GameMessage::PlayersState(_)
+        ));
+        // then carries on from the oldest update still buffered
+        let next = game_manager
+            .next_updates("game", &mut channels.to_client, None)
+            .await;
+        assert_eq!(next, Some(vec!["3".to_string()]));
+
+        game_manager.shutdown(Duration::from_secs(5)).await;
+    }
+
+    /// Formatted log output, for checking span fields
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl CapturedLogs {
+        fn contents(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+    }
+
+    #[tokio::test]
+    async fn plays_are_logged_in_game_span() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .with_max_level(tracing::Level::DEBUG)
+            .finish();
+        // tokio tests run every task on this thread, so the handler logs here too
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let db = test_db().await;
+        let game_manager = GameManager::new(db);
+        game_manager
+            .new_game(
+                None,
+                "game",
+                GameParameters {
+                    rows: 9,
+                    cols: 9,
+                    num_mines: 10,
+                    max_players: 1,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let from_client = game_manager.games.read().await["game"].from_client.clone();
+        let play = ClientMessage::Play(Play::reveal(0, BoardPoint { row: 0, col: 0 }));
+        from_client
+            .send(serde_json::to_string(&play).unwrap())
+            .await
+            .unwrap();
+
+        let played = timeout(Duration::from_secs(5), async {
+            while !logs.contents().contains("Playing") {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        assert!(played.is_ok());
+        let contents = logs.contents();
+        let play_line = contents.lines().find(|l| l.contains("Playing")).unwrap();
+        assert!(play_line.contains("game{game_id=game}:play{player_id=0}"));
+
+        game_manager.shutdown(Duration::from_secs(5)).await;
+    }
+
+    #[tokio::test]
+    async fn spectating_takes_no_player_slot() {
+        let db = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let game_manager = GameManager::new(db);
+        let (to_client, _) = broadcast::channel(100);
+        let (from_client, _from_client_rx) = mpsc::channel(100);
+        let (game_events, _game_events_rx) = mpsc::channel(100);
+        game_manager.games.write().await.insert(
+            "game".to_string(),
+            GameHandle {
+                to_client: to_client.clone(),
+                from_client,
+                game_events,
+                players: Vec::new(),
+                max_players: 2,
+                owner: None,
+                is_started: false,
+                start_time: None,
+            },
+        );
+
+        let mut channels = game_manager.viewer_channels("game").await.unwrap();
+        assert_eq!(game_manager.games.read().await["game"].players.len(), 0);
+        to_client.send("update".to_string()).unwrap();
+        assert_eq!(channels.to_client.recv().await.unwrap(), "update");
+
+        assert!(game_manager.viewer_channels("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn spectators_over_limit_are_rejected() {
+        let db = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let game_manager = GameManager::new(db).with_spectator_limits(SpectatorLimits {
+            max_spectators: 2,
+            overflow: SpectatorOverflow::Reject,
+        });
+        let (to_client, _) = broadcast::channel(100);
+        let (from_client, _from_client_rx) = mpsc::channel(100);
+        let (game_events, _game_events_rx) = mpsc::channel(100);
+        game_manager.games.write().await.insert(
+            "game".to_string(),
+            GameHandle {
+                to_client,
+                from_client,
+                game_events,
+                players: Vec::new(),
+                max_players: 2,
+                owner: None,
+                is_started: false,
+                start_time: None,
+            },
+        );
+
+        let first = game_manager.viewer_channels("game").await.unwrap();
+        let _second = game_manager.viewer_channels("game").await.unwrap();
+        assert_eq!(
+            game_manager.viewer_channels("game").await.err(),
+            Some(SpectateError::Full(2))
+        );
+        assert_eq!(
+            game_manager.viewer_channels("missing").await.err(),
+            Some(SpectateError::NotFound("missing".to_string()))
+        );
+
+        // a queued spectator gets the spot once someone leaves
+        let game_manager = game_manager.with_spectator_limits(SpectatorLimits {
+            max_spectators: 2,
+            overflow: SpectatorOverflow::Queue {
+                max_wait: Duration::from_secs(5),
+            },
+        });
+        let queued = tokio::spawn({
+            let game_manager = game_manager.clone();
+            async move { game_manager.viewer_channels("game").await.is_ok() }
+        });
+        drop(first);
+        assert!(queued.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn transferred_owner_can_start() {
+        let db = test_db().await;
+        let owner = User::add_user(&db, "owner", "").await.unwrap();
+        let joiner = User::add_user(&db, "joiner", "").await.unwrap();
+        let game_manager = GameManager::new(db.clone());
+        game_manager
+            .new_game(
+                Some(owner.clone()),
+                "game",
+                GameParameters {
+                    rows: 16,
+                    cols: 30,
+                    num_mines: 80,
+                    max_players: 2,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        let (as_owner, as_joiner) = (Some(owner.clone()), Some(joiner.clone()));
+        Player::add_player(&db, "game", &as_owner, &None, 0)
+            .await
+            .unwrap();
+        Player::add_player(&db, "game", &as_joiner, &None, 1)
+            .await
+            .unwrap();
+
+        assert!(game_manager
+            .transfer_ownership("game", &joiner, owner.id)
+            .await
+            .is_err());
+        game_manager
+            .transfer_ownership("game", &owner, joiner.id)
+            .await
+            .unwrap();
+        let game = Game::get_game(&db, "game").await.unwrap().unwrap();
+        assert_eq!(game.owner, Some(joiner.id));
+
+        assert!(game_manager.start_game("game", &as_owner).await.is_err());
+        game_manager.start_game("game", &as_joiner).await.unwrap();
+        assert!(game_manager
+            .transfer_ownership("game", &joiner, owner.id)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn oversized_game_rejected() {
+        let db = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let game_manager = GameManager::new(db);
+        let huge = GameParameters {
+            rows: 10000,
+            cols: 10000,
+            num_mines: 10,
+            max_players: 1,
+        };
+        let err = game_manager
+            .new_game(None, "huge", huge, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<GameParametersError>(),
+            Some(GameParametersError::TooLarge { .. })
+        ));
+        assert!(!game_manager.game_is_active("huge").await);
+
+        let crowded = GameParameters {
+            rows: 16,
+            cols: 30,
+            num_mines: 80,
+            max_players: 200,
+        };
+        assert_eq!(
+            GameLimits::default().check(&crowded),
+            Err(GameParametersError::TooManyPlayers {
+                max_players: 200,
+                limit: 12
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_game_does_not_panic_handler() {
+        let db = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let game_manager = GameManager::new(db);
+        let too_many_mines = GameParameters {
+            rows: 9,
+            cols: 9,
+            num_mines: 81,
+            max_players: 1,
+        };
+        assert!(game_manager
+            .new_game(None, "bad", too_many_mines, None)
+            .await
+            .is_err());
+        assert!(!game_manager.game_is_active("bad").await);
+
+        // games loaded from elsewhere skip `validate` - the handler must still not panic
+        let game = test_game(81, None);
+        assert!(new_minesweeper(&game).is_err());
+    }
+
+    #[tokio::test]
+    async fn impossible_game_fails_cleanly() {
+        let db = test_db().await;
+        let game_manager = GameManager::new(db.clone());
+        let impossible = GameParameters {
+            rows: 2,
+            cols: 2,
+            num_mines: 10,
+            max_players: 1,
+        };
+        assert!(game_manager
+            .new_game(None, "impossible", impossible, None)
+            .await
+            .is_err());
+        assert!(!game_manager.game_is_active("impossible").await);
+        assert!(Game::get_game(&db, "impossible").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn resumed_game_continues() {
+        let db = test_db().await;
+        let params = GameParameters {
+            rows: 9,
+            cols: 9,
+            num_mines: 10,
+            max_players: 1,
+        };
+        let game = Game::create_game(&db, "game", &None, params, 1)
+            .await
+            .unwrap();
+        Player::add_player(&db, "game", &None, &None, 0)
+            .await
+            .unwrap();
+
+        // state left behind by a server which stopped mid-game
+        let mut before = new_minesweeper(&game).unwrap();
+        before
+            .play(Play::reveal(0, BoardPoint { row: 4, col: 4 }))
+            .unwrap();
+        let flagged = before.mine_positions()[0];
+        before.play(Play::flag(0, flagged)).unwrap();
+        let players = vec![ClientPlayer {
+            player_id: 0,
+            score: before.player_score(0).unwrap(),
+            ..ClientPlayer::default()
+        }];
+        Game::start_game(&db, "game").await.unwrap();
+        Game::set_start_time(&db, "game", Utc::now()).await.unwrap();
+        Game::save_board(&db, "game", before.viewer_board().into())
+            .await
+            .unwrap();
+        Player::update_players(&db, "game", players).await.unwrap();
+        GameLog::save_log(&db, "game", before.get_log().unwrap())
+            .await
+            .unwrap();
+
+        let game_manager = GameManager::new(db.clone());
+        game_manager.resume_game("game").await.unwrap();
+        assert!(game_manager.game_is_active("game").await);
+        assert!(game_manager.resume_game("game").await.is_err());
+        {
+            let games = game_manager.games.read().await;
+            assert_eq!(games["game"].players.len(), 1);
+            assert!(games["game"].players[0].ws_sender.is_none());
+            assert!(games["game"].is_started);
+        }
+
+        let game = Game::get_game(&db, "game").await.unwrap().unwrap();
+        let players = Player::get_players(&db, "game").await.unwrap();
+        let log = GameLog::get_log(&db, "game").await.unwrap().unwrap().log;
+        let mut resumed = resumed_minesweeper(&game, &players, &log).unwrap();
+        // same mines, and the flag is still there
+        assert_eq!(resumed.mine_positions(), before.mine_positions());
+        assert_eq!(resumed.player_board(0), before.player_board(0));
+        let next = Play::reveal(0, BoardPoint { row: 0, col: 0 });
+        assert_eq!(resumed.play(next).ok(), before.play(next).ok());
+        assert_eq!(
+            resumed.player_score(0).unwrap(),
+            before.player_score(0).unwrap()
+        );
+
+        // without the seed the log can't be replayed
+        let unseeded = Game { seed: None, ..game };
+        assert!(resumed_minesweeper(&unseeded, &players, &log).is_err());
+    }
+
+    #[tokio::test]
+    async fn replay_streams_log_in_order() {
+        let db = test_db().await;
+        let params = GameParameters {
+            rows: 9,
+            cols: 9,
+            num_mines: 10,
+            max_players: 1,
+        };
+        let game = Game::create_game(&db, "game", &None, params, 1)
+            .await
+            .unwrap();
+        let mut minesweeper = new_minesweeper(&game).unwrap();
+        minesweeper
+            .play(Play::reveal(0, BoardPoint { row: 4, col: 4 }))
+            .unwrap();
+        let mines = minesweeper.mine_positions();
+        minesweeper.play(Play::flag(0, mines[0])).unwrap();
+        minesweeper.play(Play::reveal(0, mines[1])).unwrap();
+        let completed = minesweeper.complete();
+        let log = completed.recover_log().unwrap();
+        Game::complete_game(&db, "game", Vec::new(), None, Some(1), false)
+            .await
+            .unwrap();
+        GameLog::save_log(&db, "game", log.clone()).await.unwrap();
+
+        let game_manager = GameManager::new(db);
+        assert!(game_manager.watch_replay("game", 0.0).await.is_err());
+        let (_, board, mut rx) = game_manager
+            .watch_replay("game", MAX_REPLAY_SPEED)
+            .await
+            .unwrap();
+        assert!(board.iter().all(|cell| *cell == PlayerCell::default()));
+        let mut streamed = Vec::new();
+        while let Ok(msg) = rx.recv().await {
+            streamed.push(msg);
+        }
+        let expected = log
+            .into_iter()
+            .filter(|(_, outcome)| !matches!(outcome, PlayOutcome::Flag(_)))
+            .map(|(_, outcome)| GameMessage::PlayOutcome(outcome).into_json())
+            .collect::<Vec<_>>();
+        assert_eq!(streamed.len(), 2);
+        assert_eq!(streamed, expected);
+        assert!(game_manager.replays.read().await.is_empty());
+    }
+
+    #[test]
+    fn reveal_sends_score_delta() {
+        let game = test_game(10, Some(1));
+        let mut minesweeper = new_minesweeper(&game).unwrap();
+
+        let before = client_player(&minesweeper, 0, "Player", false);
+        let outcome = minesweeper
+            .play(Play::reveal(0, BoardPoint { row: 4, col: 4 }))
+            .unwrap();
+        let after = client_player(&minesweeper, 0, "Player", false);
+        assert!(matches!(
+            player_state_message(&before, after),
+            GameMessage::ScoreDelta { player_id: 0, delta, new_score }
+                if delta == outcome.len() && new_score == outcome.len()
+        ));
+
+        let before = client_player(&minesweeper, 0, "Player", false);
+        let mine = minesweeper.mine_positions()[0];
+        minesweeper.play(Play::reveal(0, mine)).unwrap();
+        let after = client_player(&minesweeper, 0, "Player", false);
+        assert!(matches!(
+            player_state_message(&before, after),
+            GameMessage::PlayerUpdate(ClientPlayer { dead: true, .. })
+        ));
+    }
+
+    #[test]
+    fn default_query_keeps_everything() {
+        assert_eq!(ids(GameQuery::default().apply(games())), ids(games()));
+    }
+
+    #[test]
+    fn filter_by_mode() {
+        let query = GameQuery {
+            mode: Some(GameMode::LargeMultiplayer),
+            ..Default::default()
+        };
+        assert_eq!(ids(query.apply(games())), vec!["large_1", "large_2"]);
+
+        let query = GameQuery {
+            mode: Some(GameMode::Custom),
+            ..Default::default()
+        };
+        assert_eq!(ids(query.apply(games())), vec!["custom"]);
+    }
+
+    #[test]
+    fn filter_by_max_players_and_sort() {
+        let query = GameQuery {
+            max_players: Some(8),
+            sort: GameSort::MostPlayers,
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(query.apply(games())),
+            vec!["large_2", "custom", "large_1"]
+        );
+    }
+
+    #[test]
+    fn daily_seed_is_stable_per_day() {
+        let secret = DailySecret::new("secret");
+        let day = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        assert_eq!(daily_seed(&secret, day), daily_seed(&secret, day));
+        assert_ne!(
+            daily_seed(&secret, day),
+            daily_seed(&secret, day.succ_opt().unwrap())
+        );
+        assert_ne!(
+            daily_seed(&secret, day),
+            daily_seed(&DailySecret::new("other"), day)
+        );
+        assert_eq!(format!("{:?}", secret), "DailySecret([redacted])");
+
+        let settings = GameSettings::from(&GameMode::Daily);
+        let game = |seed| {
+            MinesweeperBuilder::new(MinesweeperOpts {
+                rows: settings.rows as usize,
+                cols: settings.cols as usize,
+                num_mines: settings.num_mines as usize,
+            })
+            .unwrap()
+            .with_seed(seed)
+            .init()
+            .mine_positions()
+        };
+        assert_eq!(
+            game(daily_seed(&secret, day)),
+            game(daily_seed(&secret, day))
+        );
+    }
+
+    #[tokio::test]
+    async fn daily_games_are_listed_as_daily() {
+        let db = test_db().await;
+        let game_manager = GameManager::new(db.clone());
+        let settings = GameSettings::from(&GameMode::Daily);
+        let params = GameParameters {
+            rows: settings.rows,
+            cols: settings.cols,
+            num_mines: settings.num_mines,
+            max_players: 1,
+        };
+        let day = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        game_manager
+            .new_daily_game(None, "daily", params, day)
+            .await
+            .unwrap();
+        game_manager
+            .new_game(None, "expert", params, None)
+            .await
+            .unwrap();
+
+        let daily = GameQuery {
+            mode: Some(GameMode::Daily),
+            ..GameQuery::default()
+        };
+        assert_eq!(ids(game_manager.get_active_games(daily).await), ["daily"]);
+        let expert = GameQuery {
+            mode: Some(GameMode::ClassicExpert),
+            ..GameQuery::default()
+        };
+        assert_eq!(ids(game_manager.get_active_games(expert).await), ["expert"]);
+
+        game_manager.shutdown(Duration::from_secs(5)).await;
+    }
+
+    #[tokio::test]
+    async fn daily_results_need_the_daily_board() {
+        let db = test_db().await;
+        let game_manager = GameManager::new(db.clone());
+        let user = User::add_user(&db, "user", "token").await.unwrap();
+        let settings = GameSettings::from(&GameMode::Daily);
+        let params = GameParameters {
+            rows: settings.rows,
+            cols: settings.cols,
+            num_mines: settings.num_mines,
+            max_players: 1,
+        };
+        let day = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        // no secret configured - the seed still only depends on the day
+        let seed = daily_seed(&DailySecret::default(), day);
+        let owner = Some(user.clone());
+        for (game_id, seed) in [("daily", seed), ("other", seed ^ 1)] {
+            Game::create_game(&db, game_id, &owner, params, seed)
+                .await
+                .unwrap();
+            DailyGame::create(&db, game_id, day).await.unwrap();
+        }
+
+        let other = Game::get_game(&db, "other").await.unwrap().unwrap();
+        game_manager.save_daily_result(&other, 60).await.unwrap();
+        assert_eq!(
+            game_manager.get_daily_best(user.id, day).await.unwrap(),
+            None
+        );
+        let daily = Game::get_game(&db, "daily").await.unwrap().unwrap();
+        game_manager.save_daily_result(&daily, 90).await.unwrap();
+        assert_eq!(
+            game_manager.get_daily_best(user.id, day).await.unwrap(),
+            Some(90)
+        );
+    }
+
+    #[tokio::test]
+    async fn board_links_only_for_finished_games() {
+        let db = test_db().await;
+        let game_manager = GameManager::new(db.clone());
+        let params = GameParameters {
+            rows: 9,
+            cols: 9,
+            num_mines: 10,
+            max_players: 1,
+        };
+        let day = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        for game_id in ["game", "daily"] {
+            Game::create_game(&db, game_id, &None, params, 255)
+                .await
+                .unwrap();
+        }
+        DailyGame::create(&db, "daily", day).await.unwrap();
+
+        let game = Game::get_game(&db, "game").await.unwrap().unwrap();
+        assert!(game_manager.get_board_link(&game).await.is_none());
+        for game_id in ["game", "daily"] {
+            Game::complete_game(&db, game_id, Vec::new(), None, Some(1), false)
+                .await
+                .unwrap();
+        }
+        let game = Game::get_game(&db, "game").await.unwrap().unwrap();
+        let (opts, seed) = game_manager.get_board_link(&game).await.unwrap();
+        assert_eq!(
+            (opts.rows, opts.cols, opts.num_mines, seed),
+            (9, 9, 10, 255)
+        );
+        let daily = Game::get_game(&db, "daily").await.unwrap().unwrap();
+        assert!(game_manager.get_board_link(&daily).await.is_none());
+    }
+}
@@ -0,0 +1,106 @@
+use std::{env, time::Duration};
+
+const DEFAULT_INTERVAL_SECS: u64 = 15;
+const DEFAULT_MAX_MISSED: u32 = 2;
+
+/// How often player sockets are pinged, and how many pings can go unanswered before the
+/// socket is treated as dead
+#[derive(Clone, Copy, Debug)]
+pub struct HeartbeatConfig {
+    pub interval: Duration,
+    pub max_missed: u32,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(DEFAULT_INTERVAL_SECS),
+            max_missed: DEFAULT_MAX_MISSED,
+        }
+    }
+}
+
+impl HeartbeatConfig {
+    /// Reads `HEARTBEAT_INTERVAL_SECS` and `HEARTBEAT_MAX_MISSED`, falling back to defaults
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let interval = env::var("HEARTBEAT_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(default.interval);
+        let max_missed = env::var("HEARTBEAT_MAX_MISSED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(default.max_missed);
+        Self {
+            interval,
+            max_missed,
+        }
+    }
+}
+
+/// Tracks unanswered pings for a single socket
+#[derive(Debug)]
+pub struct Heartbeat {
+    unanswered: u32,
+    max_missed: u32,
+}
+
+impl Heartbeat {
+    pub fn new(config: &HeartbeatConfig) -> Self {
+        Self {
+            unanswered: 0,
+            max_missed: config.max_missed,
+        }
+    }
+
+    /// Call on every interval tick - returns whether to send a ping, or `false` once too many
+    /// pings went unanswered and the socket should be dropped
+    pub fn tick(&mut self) -> bool {
+        if self.unanswered >= self.max_missed {
+            return false;
+        }
+        self.unanswered += 1;
+        true
+    }
+
+    pub fn pong(&mut self) {
+        self.unanswered = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn heartbeat(max_missed: u32) -> Heartbeat {
+        Heartbeat::new(&HeartbeatConfig {
+            max_missed,
+            ..HeartbeatConfig::default()
+        })
+    }
+
+    #[test]
+    fn drops_after_missed_pongs() {
+        let mut heartbeat = heartbeat(2);
+        assert!(heartbeat.tick());
+        assert!(heartbeat.tick());
+        assert!(!heartbeat.tick());
+        assert!(!heartbeat.tick());
+    }
+
+    #[test]
+    fn pong_resets_missed() {
+        let mut heartbeat = heartbeat(2);
+        for _ in 0..10 {
+            assert!(heartbeat.tick());
+            heartbeat.pong();
+        }
+        assert!(heartbeat.tick());
+        assert!(heartbeat.tick());
+        heartbeat.pong();
+        assert!(heartbeat.tick());
+    }
+}
@@ -0,0 +1,152 @@
+use std::{collections::HashMap, sync::Arc};
+
+use minesweeper_lib::{
+    board::Board,
+    cell::PlayerCell,
+    game::{Play, PlayOutcome},
+};
+use tokio::{
+    sync::{broadcast, RwLock},
+    time::{sleep, Duration},
+};
+
+use crate::messages::GameMessage;
+
+/// Fastest playback spectators can ask for
+pub const MAX_REPLAY_SPEED: f64 = 16.0;
+/// Pace for games without a recorded duration
+const DEFAULT_MOVE_DELAY: Duration = Duration::from_secs(1);
+
+/// Running playbacks keyed by game id and speed, so spectators asking for the same one share it
+pub type ReplayStreams = Arc<RwLock<HashMap<(String, u64), ReplayStream>>>;
+
+/// A finished game being played back to spectators
+#[derive(Clone, Debug)]
+pub struct ReplayStream {
+    to_client: broadcast::Sender<String>,
+    board: Arc<RwLock<Board<PlayerCell>>>,
+}
+
+impl ReplayStream {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let (to_client, _) = broadcast::channel(100);
+        Self {
+            to_client,
+            board: Arc::new(RwLock::new(Board::new(rows, cols, PlayerCell::default()))),
+        }
+    }
+
+    /// Board played back so far, and a receiver for every `PlayOutcome` after it
+    pub async fn subscribe(&self) -> (Board<PlayerCell>, broadcast::Receiver<String>) {
+        // outcomes are sent under the board lock, so none are missed or repeated
+        let board = self.board.read().await;
+        (board.clone(), self.to_client.subscribe())
+    }
+
+    /// Board played back so far for a receiver which fell too far behind - `rx` is moved on
+    /// to the outcomes after it
+    pub async fn resync(&self, rx: &mut broadcast::Receiver<String>) -> Board<PlayerCell> {
+        let board = self.board.read().await;
+        *rx = rx.resubscribe();
+        board.clone()
+    }
+
+    /// Sends each outcome in `log` after `delay`, then removes itself from `streams`.  Flags are
+    /// private to the player who placed them, so they're skipped.
+    pub async fn play(
+        self,
+        log: Vec<(Play, PlayOutcome)>,
+        delay: Duration,
+        streams: ReplayStreams,
+        key: (String, u64),
+    ) {
+        for (_, outcome) in log {
+            let revealed = match &outcome {
+                PlayOutcome::Success(cells) | PlayOutcome::Victory(cells) => cells.clone(),
+                PlayOutcome::Failure(cell) => vec![*cell],
+                PlayOutcome::Flag(_) => continue,
+            };
+            sleep(delay).await;
+            if self.to_client.receiver_count() == 0 {
+                // everyone stopped watching
+                break;
+            }
+            let mut board = self.board.write().await;
+            board.apply_diff(
+                revealed
+                    .into_iter()
+                    .map(|(point, rc)| (point, PlayerCell::Revealed(rc))),
+            );
+            let _ = self
+                .to_client
+                .send(GameMessage::PlayOutcome(outcome).into_json());
+        }
+        streams.write().await.remove(&key);
+    }
+}
+
+/// Delay between moves so playback at `speed` takes `seconds / speed` - the log has no per-move
+/// timestamps, so moves are spread evenly over the game
+pub fn move_delay(seconds: Option<i64>, moves: usize, speed: f64) -> Duration {
+    let delay = match seconds {
+        Some(seconds) if seconds > 0 && moves > 0 => {
+            Duration::from_secs_f64(seconds as f64 / moves as f64)
+        }
+        _ => DEFAULT_MOVE_DELAY,
+    };
+    delay.div_f64(speed)
+}
+
+#[cfg(test)]
+mod test {
+    use minesweeper_lib::{
+        board::BoardPoint,
+        cell::{Cell, RevealedCell},
+    };
+    use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+    use super::*;
+
+    #[test]
+    fn delay_spreads_moves_over_game() {
+        assert_eq!(move_delay(Some(10), 5, 1.0), Duration::from_secs(2));
+        assert_eq!(move_delay(Some(10), 5, 4.0), Duration::from_millis(500));
+        assert_eq!(move_delay(None, 5, 2.0), Duration::from_millis(500));
+        assert_eq!(move_delay(Some(10), 0, 1.0), DEFAULT_MOVE_DELAY);
+    }
+
+    #[tokio::test]
+    async fn lagged_receiver_is_resynced() {
+        let stream = ReplayStream::new(1, 150);
+        let (_, mut rx) = stream.subscribe().await;
+        let log = (0..150)
+            .map(|col| {
+                let point = BoardPoint { row: 0, col };
+                let cell = RevealedCell {
+                    player: 0,
+                    contents: Cell::Empty(0),
+                };
+                (
+                    Play::reveal(0, point),
+                    PlayOutcome::Success(vec![(point, cell)]),
+                )
+            })
+            .collect();
+        stream
+            .clone()
+            .play(
+                log,
+                Duration::ZERO,
+                ReplayStreams::default(),
+                ("game".to_string(), 0),
+            )
+            .await;
+
+        assert!(matches!(rx.recv().await, Err(RecvError::Lagged(_))));
+        let board = stream.resync(&mut rx).await;
+        assert!(board
+            .iter()
+            .all(|cell| matches!(cell, PlayerCell::Revealed(_))));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+    }
+}
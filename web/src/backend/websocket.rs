@@ -1,7 +1,7 @@
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        Path, State,
+        Path, Query, State,
     },
     response::IntoResponse,
     routing::get,
@@ -9,15 +9,36 @@ use axum::{
 };
 use futures::{sink::SinkExt, StreamExt};
 use http::StatusCode;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use minesweeper_lib::{board::Board, cell::PlayerCell};
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Arc};
+use tokio::{
+    sync::{broadcast, broadcast::error::RecvError, Mutex},
+    task::JoinHandle,
+    time::interval,
+};
+
+use crate::{
+    messages::{ClientMessage, GameMessage, TaggedGameMessage},
+    models::user::User,
+};
 
-use crate::{messages::ClientMessage, models::user::User};
+use super::{
+    app::AppState,
+    game_manager::GameManager,
+    heartbeat::{Heartbeat, HeartbeatConfig},
+    replay_stream::ReplayStream,
+    users::AuthSession,
+};
 
-use super::{app::AppState, game_manager::GameManager, users::AuthSession};
+/// Most games one multiplexed connection may follow at once
+const MAX_SUBSCRIPTIONS: usize = 20;
 
 pub fn router() -> Router<AppState> {
-    Router::<AppState>::new().route("/api/websocket/game/:id", get(websocket_handler))
+    Router::<AppState>::new()
+        .route("/api/websocket/game/:id", get(websocket_handler))
+        .route("/api/websocket/games", get(multiplexed_websocket_handler))
+        .route("/api/websocket/replay/:id", get(replay_websocket_handler))
 }
 
 pub async fn websocket_handler(
@@ -31,7 +52,15 @@ pub async fn websocket_handler(
     {
         return StatusCode::BAD_REQUEST.into_response();
     }
-    ws.on_upgrade(|socket| websocket(socket, auth_session.user, game_id, app_state.game_manager))
+    ws.on_upgrade(move |socket| {
+        websocket(
+            socket,
+            auth_session.user,
+            game_id,
+            app_state.game_manager,
+            app_state.heartbeat,
+        )
+    })
 }
 
 // This function deals with a single websocket connection, i.e., a single
@@ -42,6 +71,7 @@ pub async fn websocket(
     user: Option<User>,
     game_id: String,
     game_manager: GameManager,
+    heartbeat_config: HeartbeatConfig,
 ) {
     log::debug!("Websocket upgraded");
     // By splitting, we can send and receive at the same time.
@@ -51,25 +81,35 @@ pub async fn websocket(
     let game_id = game_id.as_str();
 
     let sender_clone = Arc::clone(&sender);
-    let mut rx = game_manager
-        .join_game(game_id, sender_clone)
+    let mut rx = match game_manager
+        .spectate_game(game_id, sender_clone, false)
         .await
-        .unwrap_or_else(|_| panic!("Failed to join game ({}) from websocket", game_id));
+    {
+        Ok(rx) => rx,
+        Err(e) => {
+            log::debug!("Error spectating game ({}): {}", game_id, e);
+            let error_msg = GameMessage::Error(e.to_string()).into_json();
+            let _ = sender.lock().await.send(Message::Text(error_msg)).await;
+            return;
+        }
+    };
 
     let sender_clone = Arc::clone(&sender);
     // Spawn the first task that will receive broadcast messages and send text
     // messages over the websocket to our client.
+    let game_manager_clone = game_manager.clone();
+    let game_id_clone = game_id.to_string();
     let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
-            // In any websocket error, break loop.
-            if sender_clone
-                .lock()
-                .await
-                .send(Message::Text(msg))
-                .await
-                .is_err()
-            {
-                break;
+        while let Some(msgs) = game_manager_clone
+            .next_updates(&game_id_clone, &mut rx, Some(&sender_clone))
+            .await
+        {
+            let mut sender = sender_clone.lock().await;
+            for msg in msgs {
+                // In any websocket error, stop sending.
+                if sender.send(Message::Text(msg)).await.is_err() {
+                    return;
+                }
             }
         }
     });
@@ -80,8 +120,8 @@ pub async fn websocket(
             .play_game(game_id, &user, Arc::clone(&sender))
             .await;
         match resp {
-            Ok(tx) => {
-                game_sender = Some(tx);
+            Ok(player_and_tx) => {
+                game_sender = Some(player_and_tx);
             }
             Err(e) => {
                 log::error!("Error playing game: {}", e)
@@ -100,8 +140,8 @@ pub async fn websocket(
                                     log::debug!("Trying to Play");
                                     let resp = game_manager.play_game(game_id, &user, Arc::clone(&sender)).await;
                                     match resp {
-                                        Ok(tx) => {
-                                            game_sender = Some(tx);
+                                        Ok(player_and_tx) => {
+                                            game_sender = Some(player_and_tx);
                                             break;
                                         },
                                         Err(e) => {log::error!("Error playing game: {}", e)},
@@ -117,18 +157,46 @@ pub async fn websocket(
         }
     }
 
-    let game_sender = if let Some(game_sender) = game_sender {
+    let (player_id, game_sender) = if let Some(game_sender) = game_sender {
         game_sender
     } else {
         let _ = send_task.await;
         return;
     };
 
-    // Spawn a task that takes messages from the websocket and sends them to the game handler
+    // Spawn a task that takes messages from the websocket and sends them to the game handler.
+    // It also pings the player, so a socket which silently went away is dropped well before
+    // the game's idle timeout.
+    let ping_sender = Arc::clone(&sender);
     let mut recv_task = tokio::spawn(async move {
-        while let Some(Ok(Message::Text(text))) = receiver.next().await {
-            if game_sender.send(text).await.is_err() {
-                return;
+        let mut heartbeat = Heartbeat::new(&heartbeat_config);
+        let mut ping_interval = interval(heartbeat_config.interval);
+        loop {
+            tokio::select! {
+                _ = ping_interval.tick() => {
+                    let mut sink = ping_sender.lock().await;
+                    if !heartbeat.tick() {
+                        log::debug!("Player missed too many pongs, dropping socket");
+                        let _ = sink.close().await;
+                        return;
+                    }
+                    let ping_msg = GameMessage::Ping.into_json();
+                    if sink.send(Message::Text(ping_msg)).await.is_err() {
+                        return;
+                    }
+                }
+                recvd = receiver.next() => {
+                    let Some(Ok(Message::Text(text))) = recvd else {
+                        return;
+                    };
+                    if matches!(serde_json::from_str::<ClientMessage>(&text), Ok(ClientMessage::Pong)) {
+                        heartbeat.pong();
+                        continue;
+                    }
+                    if game_sender.send(text).await.is_err() {
+                        return;
+                    }
+                }
             }
         }
     });
@@ -138,4 +206,161 @@ pub async fn websocket(
         _ = (&mut send_task) => recv_task.abort(),
         _ = (&mut recv_task) => send_task.abort(),
     };
+
+    let _ = game_manager
+        .leave_game(game_id, player_id, sender)
+        .await
+        .map_err(|e| log::error!("Error leaving game: {e}"));
+}
+
+pub async fn multiplexed_websocket_handler(
+    ws: WebSocketUpgrade,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    ws.on_upgrade(|socket| multiplexed_websocket(socket, app_state.game_manager))
+}
+
+// Spectates any number of games over one connection.  The client sends
+// `ClientMessage::Subscribe`/`Unsubscribe` and every message sent back is a
+// `TaggedGameMessage`, so it can tell which game an update belongs to.
+// Playing still goes through the per-game websocket.
+pub async fn multiplexed_websocket(stream: WebSocket, game_manager: GameManager) {
+    log::debug!("Multiplexed websocket upgraded");
+    let (sender, mut receiver) = stream.split();
+    let sender = Arc::new(Mutex::new(sender));
+    let mut subscriptions = HashMap::<String, JoinHandle<()>>::new();
+
+    while let Some(Ok(Message::Text(msg))) = receiver.next().await {
+        match serde_json::from_str::<ClientMessage>(&msg) {
+            Ok(ClientMessage::Subscribe(game_id)) => {
+                if subscriptions
+                    .get(&game_id)
+                    .is_some_and(|task| !task.is_finished())
+                {
+                    continue;
+                }
+                subscriptions.retain(|_, task| !task.is_finished());
+                if subscriptions.len() >= MAX_SUBSCRIPTIONS {
+                    let error_msg = TaggedGameMessage::tag_json(
+                        &game_id,
+                        &GameMessage::Error(format!(
+                            "Can't follow more than {MAX_SUBSCRIPTIONS} games at once"
+                        ))
+                        .into_json(),
+                    );
+                    if sender
+                        .lock()
+                        .await
+                        .send(Message::Text(error_msg))
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                    continue;
+                }
+                let rx = game_manager
+                    .spectate_game(&game_id, Arc::clone(&sender), true)
+                    .await;
+                let mut rx = match rx {
+                    Ok(rx) => rx,
+                    Err(e) => {
+                        log::debug!("Error subscribing to game ({}): {}", game_id, e);
+                        let error_msg = TaggedGameMessage::tag_json(
+                            &game_id,
+                            &GameMessage::Error(format!("Can't follow game: {e}")).into_json(),
+                        );
+                        if sender
+                            .lock()
+                            .await
+                            .send(Message::Text(error_msg))
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
+                        continue;
+                    }
+                };
+                let sender_clone = Arc::clone(&sender);
+                let game_manager_clone = game_manager.clone();
+                let tag = game_id.clone();
+                let task = tokio::spawn(async move {
+                    while let Some(msgs) =
+                        game_manager_clone.next_updates(&tag, &mut rx, None).await
+                    {
+                        let mut sender = sender_clone.lock().await;
+                        for msg in msgs {
+                            let msg = TaggedGameMessage::tag_json(&tag, &msg);
+                            if sender.send(Message::Text(msg)).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+                subscriptions.insert(game_id, task);
+            }
+            Ok(ClientMessage::Unsubscribe(game_id)) => {
+                if let Some(task) = subscriptions.remove(&game_id) {
+                    task.abort();
+                }
+            }
+            _ => log::debug!("Unexpected multiplexed message: {:?}", msg),
+        }
+    }
+
+    subscriptions.values().for_each(|task| task.abort());
+}
+
+#[derive(Deserialize)]
+pub struct ReplayParams {
+    speed: Option<f64>,
+}
+
+pub async fn replay_websocket_handler(
+    ws: WebSocketUpgrade,
+    Path(game_id): Path<String>,
+    Query(params): Query<ReplayParams>,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    let speed = params.speed.unwrap_or(1.0);
+    let replay = app_state.game_manager.watch_replay(&game_id, speed).await;
+    let (replay, board, rx) = match replay {
+        Ok(replay) => replay,
+        Err(e) => {
+            log::debug!("Error watching replay ({}): {}", game_id, e);
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+    ws.on_upgrade(move |socket| replay_websocket(socket, replay, board, rx))
+}
+
+// Streams a finished game's plays as they happened - the client is sent the board played
+// back so far, then each `GameMessage::PlayOutcome` as it's replayed.  The socket closes
+// when the playback ends.  A socket which falls too far behind is sent the board again.
+pub async fn replay_websocket(
+    mut stream: WebSocket,
+    replay: ReplayStream,
+    board: Board<PlayerCell>,
+    mut rx: broadcast::Receiver<String>,
+) {
+    log::debug!("Replay websocket upgraded");
+    let state_msg = GameMessage::GameState(board).into_json();
+    if stream.send(Message::Text(state_msg)).await.is_err() {
+        return;
+    }
+    loop {
+        let msg = match rx.recv().await {
+            Ok(msg) => msg,
+            Err(RecvError::Lagged(missed)) => {
+                log::debug!("Replay socket missed {missed} updates, resyncing");
+                GameMessage::GameState(replay.resync(&mut rx).await).into_json()
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if stream.send(Message::Text(msg)).await.is_err() {
+            return;
+        }
+    }
+    let _ = stream.close().await;
 }
@@ -1,4 +1,5 @@
 pub mod dark_mode;
+pub mod i18n;
 pub mod icons;
 pub mod info;
 pub mod socials;
@@ -24,6 +25,9 @@ macro_rules! button_class {
 
 #[macro_export]
 macro_rules! cell_class {
+    (size: $size:expr, $extra:expr, $colors:expr) => {
+        format!("inline-block text-center border border-solid border-black font-bold align-top {} {} {}", $size, $extra, $colors)
+    };
     ($extra:literal) => {
         concat!("inline-block text-center border border-solid border-black font-bold align-top h-8 w-8 text-2xl ", $extra)
     };
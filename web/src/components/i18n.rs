@@ -0,0 +1,117 @@
+use codee::string::JsonSerdeWasmCodec;
+use leptos::prelude::*;
+use leptos_use::storage::{use_local_storage_with_options, UseStorageOptions};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+/// UI language - strings are keyed by their English text, so English needs no table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Lang {
+    #[default]
+    En,
+    Es,
+}
+
+impl Lang {
+    fn strings(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::En => &[],
+            Self::Es => ES,
+        }
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            Self::En => "EN",
+            Self::Es => "ES",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::En => Self::Es,
+            Self::Es => Self::En,
+        }
+    }
+}
+
+const ES: &[(&str, &str)] = &[
+    ("Beginner", "Principiante"),
+    ("Intermediate", "Intermedio"),
+    ("Expert", "Experto"),
+    ("Tiny", "Diminuto"),
+    ("Evil", "Malvado"),
+    ("Small", "Pequeño"),
+    ("Large", "Grande"),
+    ("Custom", "Personalizado"),
+    ("Complete", "Completado"),
+    ("Game Over", "Fin del juego"),
+    ("Victory", "Victoria"),
+    ("Victory Click", "Clic ganador"),
+    ("Dead", "Muerto"),
+    ("Top Score", "Mejor puntuación"),
+    ("Play Game", "Jugar"),
+    ("Start Game", "Empezar"),
+];
+
+/// `key` in `lang`, or `key` itself when there's no translation
+pub fn translate(lang: Lang, key: &str) -> &str {
+    lang.strings()
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map_or(key, |&(_, s)| s)
+}
+
+#[derive(Clone, Copy)]
+struct LangContext {
+    lang: Signal<Lang>,
+    set_lang: WriteSignal<Lang>,
+}
+
+/// Provides the stored language to everything below - call once at the app root
+pub fn provide_lang() {
+    let storage_options = UseStorageOptions::<Lang, serde_json::Error, JsValue>::default()
+        .delay_during_hydration(true);
+    let (lang, set_lang, _) =
+        use_local_storage_with_options::<Lang, JsonSerdeWasmCodec>("lang", storage_options);
+    provide_context(LangContext { lang, set_lang });
+}
+
+/// `key` in the current language - call inside a closure for the view to follow changes
+pub fn t(key: &str) -> &str {
+    let lang = use_context::<LangContext>()
+        .map(|ctx| ctx.lang.get())
+        .unwrap_or_default();
+    translate(lang, key)
+}
+
+#[component]
+pub fn LangToggle() -> impl IntoView {
+    let Some(LangContext { lang, set_lang }) = use_context::<LangContext>() else {
+        return None;
+    };
+    Some(view! {
+        <button
+            type="button"
+            aria-label="language toggle"
+            class="inline-flex items-center justify-center rounded-md text-sm font-medium disabled:pointer-events-none disabled:opacity-50 border border-input bg-transparent hover:bg-gray-700 hover:text-gray-50 h-10 px-3 text-gray-900 dark:text-gray-200"
+            on:click=move |_| set_lang(lang.get_untracked().next())
+        >
+            {move || lang.get().code()}
+        </button>
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn translate_with_fallback() {
+        assert_eq!(translate(Lang::En, "Game Over"), "Game Over");
+        assert_eq!(translate(Lang::Es, "Game Over"), "Fin del juego");
+        assert_eq!(translate(Lang::En, "Beginner"), "Beginner");
+        assert_eq!(translate(Lang::Es, "Beginner"), "Principiante");
+        assert_eq!(translate(Lang::Es, "Not a key"), "Not a key");
+    }
+}
@@ -1,24 +1,64 @@
 #[cfg(feature = "ssr")]
 use minesweeper_web::backend::App;
+#[cfg(feature = "ssr")]
+use tokio::{signal, time::Duration};
+#[cfg(feature = "ssr")]
+use tracing_subscriber::EnvFilter;
 
 #[cfg(feature = "ssr")]
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    simple_logger::init_with_level(log::Level::Debug).expect("couldn't initialize logging");
+    // log records are forwarded too, so everything is filterable by `RUST_LOG`
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("debug")),
+        )
+        .init();
 
     let backend_app = App::new().await.expect("Couldn't create backend app");
     let session_cleanup_task = backend_app.start_session_cleanup();
-    let (app, addr) = backend_app.router().await;
+    let (app, addr, game_manager) = backend_app.router().await;
 
     // run our app with hyper
     // `axum::Server` is a re-export of `hyper::Server`
     log::info!("listening on http://{}", &addr);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app.into_make_service()).await?;
+    axum::serve(listener, app.into_make_service())
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            // save in-progress games before connections are closed
+            game_manager.shutdown(Duration::from_secs(10)).await;
+        })
+        .await?;
 
-    session_cleanup_task.await??;
+    session_cleanup_task.abort();
     Ok(())
 }
 
+#[cfg(feature = "ssr")]
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
 #[cfg(not(feature = "ssr"))]
 fn main() {}
@@ -1,8 +1,10 @@
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
-use serde_json::Error as SerdeJsonError;
+use serde_json::{Error as SerdeJsonError, Value};
+use thiserror::Error;
 
+use crate::app::GameMode;
 use minesweeper_lib::{
     board::Board,
     cell::PlayerCell,
@@ -13,14 +15,39 @@ use minesweeper_lib::{
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "game_message", content = "data")]
 pub enum GameMessage {
+    /// Sent to spectators ahead of the board, so the UI can be sized before it arrives
+    GameMeta {
+        rows: usize,
+        cols: usize,
+        num_mines: usize,
+        max_players: usize,
+        mode: GameMode,
+    },
     PlayerId(usize),
     PlayOutcome(PlayOutcome),
     PlayerUpdate(ClientPlayer),
+    /// Score change from a play - `PlayerUpdate` is sent instead when anything else changed
+    ScoreDelta {
+        player_id: usize,
+        delta: usize,
+        new_score: usize,
+    },
     GameState(Board<PlayerCell>),
     PlayersState(Vec<Option<ClientPlayer>>),
+    PlayerJoined(ClientPlayer),
+    PlayerLeft {
+        player_id: usize,
+    },
     GameStarted,
     SyncTimer(usize),
     Error(String),
+    /// Double-click rejected because the number of flagged neighbors doesn't match the cell
+    DoubleClickMismatch {
+        expected: u8,
+        got: u8,
+    },
+    /// Heartbeat - players should answer with `ClientMessage::Pong`
+    Ping,
 }
 
 #[cfg(feature = "ssr")]
@@ -31,11 +58,88 @@ impl GameMessage {
     }
 }
 
+impl GameMessage {
+    /// Every `game_message` tag - keep in step with the variants
+    const TAGS: &'static [&'static str] = &[
+        "GameMeta",
+        "PlayerId",
+        "PlayOutcome",
+        "PlayerUpdate",
+        "ScoreDelta",
+        "GameState",
+        "PlayersState",
+        "PlayerJoined",
+        "PlayerLeft",
+        "GameStarted",
+        "SyncTimer",
+        "Error",
+        "DoubleClickMismatch",
+        "Ping",
+    ];
+
+    pub fn from_json(s: &str) -> Result<Self, GameMessageError> {
+        serde_json::from_str::<GameMessage>(s).map_err(|e| {
+            let value = serde_json::from_str::<Value>(s).ok();
+            GameMessageError::classify(value.as_ref(), e)
+        })
+    }
+}
+
 impl FromStr for GameMessage {
-    type Err = SerdeJsonError;
+    type Err = GameMessageError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        serde_json::from_str::<GameMessage>(s)
+        GameMessage::from_json(s)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GameMessageError {
+    #[error("Unknown game message: {0}")]
+    UnknownVariant(SerdeJsonError),
+    #[error("Malformed game message: {0}")]
+    Malformed(SerdeJsonError),
+}
+
+impl GameMessageError {
+    /// Unknown if `message` is tagged with a `game_message` this build doesn't have, e.g. one
+    /// from a newer server - malformed otherwise
+    fn classify(message: Option<&Value>, error: SerdeJsonError) -> Self {
+        let tag = message
+            .and_then(|m| m.get("game_message"))
+            .and_then(Value::as_str);
+        match tag {
+            Some(tag) if !GameMessage::TAGS.contains(&tag) => {
+                GameMessageError::UnknownVariant(error)
+            }
+            _ => GameMessageError::Malformed(error),
+        }
+    }
+}
+
+/// A `GameMessage` sent over a multiplexed socket, which can follow several games at once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaggedGameMessage {
+    pub game_id: String,
+    pub message: GameMessage,
+}
+
+#[cfg(feature = "ssr")]
+impl TaggedGameMessage {
+    /// Tags an already serialized `GameMessage` - game updates are broadcast as JSON strings
+    pub fn tag_json(game_id: &str, message_json: &str) -> String {
+        let game_id = serde_json::to_string(game_id)
+            .unwrap_or_else(|_| panic!("Should be able to serialize game_id {:?}", game_id));
+        format!(r#"{{"game_id":{game_id},"message":{message_json}}}"#)
+    }
+}
+
+impl TaggedGameMessage {
+    pub fn from_json(s: &str) -> Result<Self, GameMessageError> {
+        serde_json::from_str::<TaggedGameMessage>(s).map_err(|e| {
+            let value = serde_json::from_str::<Value>(s).ok();
+            GameMessageError::classify(value.as_ref().and_then(|v| v.get("message")), e)
+        })
     }
 }
 
@@ -45,4 +149,128 @@ pub enum ClientMessage {
     Join,
     PlayGame,
     Play(Play),
+    /// Multiplexed sockets only - start following a game
+    Subscribe(String),
+    /// Multiplexed sockets only - stop following a game
+    Unsubscribe(String),
+    /// Answer to `GameMessage::Ping`
+    Pong,
+}
+
+#[cfg(test)]
+mod test {
+    use minesweeper_lib::board::BoardPoint;
+
+    use super::*;
+
+    #[test]
+    fn from_json_valid() {
+        let msg = GameMessage::from_json(r#"{"game_message":"PlayerId","data":3}"#);
+        assert!(matches!(msg, Ok(GameMessage::PlayerId(3))));
+    }
+
+    #[test]
+    fn from_json_unknown_variant() {
+        let msg = GameMessage::from_json(r#"{"game_message":"NotAMessage","data":3}"#);
+        assert!(matches!(msg, Err(GameMessageError::UnknownVariant(_))));
+    }
+
+    #[test]
+    fn every_variant_round_trips() {
+        let player = ClientPlayer {
+            player_id: 1,
+            username: "Player".to_string(),
+            ..Default::default()
+        };
+        let messages = [
+            GameMessage::GameMeta {
+                rows: 16,
+                cols: 30,
+                num_mines: 99,
+                max_players: 1,
+                mode: GameMode::ClassicExpert,
+            },
+            GameMessage::PlayerId(3),
+            GameMessage::PlayOutcome(PlayOutcome::Flag((
+                BoardPoint { row: 0, col: 1 },
+                PlayerCell::default(),
+            ))),
+            GameMessage::PlayerUpdate(player.clone()),
+            GameMessage::ScoreDelta {
+                player_id: 1,
+                delta: 5,
+                new_score: 12,
+            },
+            GameMessage::GameState(Board::new(2, 3, PlayerCell::default())),
+            GameMessage::PlayersState(vec![Some(player.clone()), None]),
+            GameMessage::PlayerJoined(player),
+            GameMessage::PlayerLeft { player_id: 2 },
+            GameMessage::GameStarted,
+            GameMessage::SyncTimer(5),
+            GameMessage::Error("Oops".to_string()),
+            GameMessage::DoubleClickMismatch {
+                expected: 3,
+                got: 2,
+            },
+            GameMessage::Ping,
+        ];
+
+        let tags = messages
+            .into_iter()
+            .map(|message| {
+                let json = serde_json::to_string(&message).unwrap();
+                let parsed = GameMessage::from_json(&json).unwrap();
+                assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+
+                let value = serde_json::from_str::<Value>(&json).unwrap();
+                value["game_message"].as_str().unwrap().to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tags, GameMessage::TAGS);
+    }
+
+    #[cfg(feature = "ssr")]
+    #[test]
+    fn tagged_messages_from_two_games() {
+        let first = TaggedGameMessage::tag_json("game1", &GameMessage::GameStarted.into_json());
+        let second = TaggedGameMessage::tag_json("game2", &GameMessage::SyncTimer(5).into_json());
+        assert_eq!(
+            first,
+            r#"{"game_id":"game1","message":{"game_message":"GameStarted"}}"#
+        );
+        assert!(matches!(
+            TaggedGameMessage::from_json(&first),
+            Ok(TaggedGameMessage { game_id, message: GameMessage::GameStarted }) if game_id == "game1"
+        ));
+        assert!(matches!(
+            TaggedGameMessage::from_json(&second),
+            Ok(TaggedGameMessage { game_id, message: GameMessage::SyncTimer(5) }) if game_id == "game2"
+        ));
+    }
+
+    #[test]
+    fn subscribe_wire_format() {
+        let json = serde_json::to_string(&ClientMessage::Subscribe("game1".to_string())).unwrap();
+        assert_eq!(json, r#"{"client_message":"Subscribe","data":"game1"}"#);
+        let msg = serde_json::from_str::<ClientMessage>(
+            r#"{"client_message":"Unsubscribe","data":"game1"}"#,
+        );
+        assert!(matches!(msg, Ok(ClientMessage::Unsubscribe(id)) if id == "game1"));
+    }
+
+    #[test]
+    fn from_json_truncated() {
+        let msg = GameMessage::from_json(r#"{"game_message":"PlayerId","da"#);
+        assert!(matches!(msg, Err(GameMessageError::Malformed(_))));
+    }
+
+    #[test]
+    fn from_json_known_variant_bad_data() {
+        let msg = GameMessage::from_json(r#"{"game_message":"PlayerId","data":"three"}"#);
+        assert!(matches!(msg, Err(GameMessageError::Malformed(_))));
+        let msg = TaggedGameMessage::from_json(
+            r#"{"game_id":"game1","message":{"game_message":"NotAMessage"}}"#,
+        );
+        assert!(matches!(msg, Err(GameMessageError::UnknownVariant(_))));
+    }
 }
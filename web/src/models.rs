@@ -1,2 +1,3 @@
+pub mod daily;
 pub mod game;
 pub mod user;
@@ -0,0 +1,137 @@
+#![cfg(feature = "ssr")]
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+/// Marks a game as the daily challenge for `day`
+#[derive(Clone, Debug, Serialize, Deserialize, FromRow)]
+pub struct DailyGame {
+    pub game_id: String,
+    pub day: NaiveDate,
+}
+
+/// A user's fastest win of the daily challenge for `day`
+#[derive(Clone, Debug, Serialize, Deserialize, FromRow)]
+pub struct DailyResult {
+    pub user: i64, // User.id
+    pub day: NaiveDate,
+    pub game_id: String,
+    pub seconds: i64,
+}
+
+impl DailyGame {
+    pub async fn create(
+        db: &SqlitePool,
+        game_id: &str,
+        day: NaiveDate,
+    ) -> Result<DailyGame, sqlx::Error> {
+        sqlx::query_as("INSERT INTO daily_games (game_id, day) VALUES (?, ?) RETURNING *")
+            .bind(game_id)
+            .bind(day)
+            .fetch_one(db)
+            .await
+    }
+
+    pub async fn get_day(db: &SqlitePool, game_id: &str) -> Result<Option<NaiveDate>, sqlx::Error> {
+        sqlx::query_scalar("SELECT day FROM daily_games WHERE game_id = ?")
+            .bind(game_id)
+            .fetch_optional(db)
+            .await
+    }
+}
+
+impl DailyResult {
+    /// Records a win, keeping whichever of it and any earlier result for the day is faster
+    pub async fn save_best(
+        db: &SqlitePool,
+        user: i64,
+        day: NaiveDate,
+        game_id: &str,
+        seconds: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO daily_results (user, day, game_id, seconds)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(user, day) DO UPDATE
+              SET game_id = excluded.game_id, seconds = excluded.seconds
+              WHERE excluded.seconds < daily_results.seconds
+            "#,
+        )
+        .bind(user)
+        .bind(day)
+        .bind(game_id)
+        .bind(seconds)
+        .execute(db)
+        .await
+        .map(|_| ())
+    }
+
+    pub async fn get_result(
+        db: &SqlitePool,
+        user: i64,
+        day: NaiveDate,
+    ) -> Result<Option<DailyResult>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM daily_results WHERE user = ? AND day = ?")
+            .bind(user)
+            .bind(day)
+            .fetch_optional(db)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::models::{
+        game::{Game, GameParameters},
+        user::User,
+    };
+
+    #[tokio::test]
+    async fn save_best_keeps_fastest() {
+        let db = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&db).await.unwrap();
+        let params = GameParameters {
+            rows: 16,
+            cols: 30,
+            num_mines: 99,
+            max_players: 1,
+        };
+        let user = User::add_user(&db, "user", "token").await.unwrap().id;
+        let day = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        for game_id in ["first", "second", "third"] {
+            Game::create_game(&db, game_id, &None, params, 1)
+                .await
+                .unwrap();
+            DailyGame::create(&db, game_id, day).await.unwrap();
+        }
+        assert_eq!(DailyGame::get_day(&db, "second").await.unwrap(), Some(day));
+
+        DailyResult::save_best(&db, user, day, "first", 120)
+            .await
+            .unwrap();
+        DailyResult::save_best(&db, user, day, "second", 90)
+            .await
+            .unwrap();
+        DailyResult::save_best(&db, user, day, "third", 200)
+            .await
+            .unwrap();
+        let best = DailyResult::get_result(&db, user, day)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(best.game_id, "second");
+        assert_eq!(best.seconds, 90);
+        assert!(DailyResult::get_result(&db, user + 1, day)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -6,7 +6,8 @@ use minesweeper_lib::{
     game::{Play, PlayOutcome},
 };
 use serde::{Deserialize, Serialize};
-use sqlx::{types::Json, FromRow, SqlitePool};
+use sqlx::{sqlite::SqliteRow, types::Json, FromRow, Row, SqlitePool};
+use thiserror::Error;
 
 use super::user::User;
 
@@ -26,6 +27,10 @@ pub struct Game {
     pub seconds: Option<i64>,
     #[sqlx(json)]
     pub final_board: Option<Vec<Vec<PlayerCell>>>,
+    /// Seed the board was generated from - never sent anywhere, it gives away every mine.
+    /// Games from before seeds were saved don't have one.
+    #[serde(skip)]
+    pub seed: Option<i64>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, FromRow)]
@@ -44,8 +49,10 @@ pub struct SimpleGameWithPlayers {
     pub seconds: Option<i64>,
     pub num_players: u8,
     pub top_score: Option<i64>,
+    pub is_daily: bool,
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct GameParameters {
     pub rows: i64,
     pub cols: i64,
@@ -53,6 +60,45 @@ pub struct GameParameters {
     pub max_players: u8,
 }
 
+impl GameParameters {
+    /// Checks the parameters describe a playable game - see `GameLimits` for size caps
+    pub fn validate(&self) -> Result<(), GameParametersError> {
+        if self.rows < 1 || self.cols < 1 {
+            return Err(GameParametersError::ZeroDimension);
+        }
+        let cells = self.rows.saturating_mul(self.cols);
+        if self.num_mines < 1 || self.num_mines >= cells {
+            return Err(GameParametersError::InvalidMines {
+                num_mines: self.num_mines,
+                cells,
+            });
+        }
+        if self.max_players < 1 {
+            return Err(GameParametersError::NoPlayers);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum GameParametersError {
+    #[error("Rows and columns must be greater than 0")]
+    ZeroDimension,
+    #[error("Invalid number of mines ({num_mines}) - must be between 1 and {cells} cells")]
+    InvalidMines { num_mines: i64, cells: i64 },
+    #[error("Game must allow at least 1 player")]
+    NoPlayers,
+    #[error("Board too large ({rows}x{cols}) - max is {max_rows}x{max_cols}")]
+    TooLarge {
+        rows: i64,
+        cols: i64,
+        max_rows: i64,
+        max_cols: i64,
+    },
+    #[error("Too many players ({max_players}) - max is {limit}")]
+    TooManyPlayers { max_players: u8, limit: u8 },
+}
+
 impl Game {
     pub async fn get_game(db: &SqlitePool, game_id: &str) -> Result<Option<Game>, sqlx::Error> {
         sqlx::query_as("SELECT * FROM games WHERE game_id = ?")
@@ -74,7 +120,8 @@ impl Game {
             SELECT
               game_id, owner, rows, cols, num_mines, max_players, is_completed, is_started, start_time, end_time, timed_out, seconds,
               ( SELECT count(*) FROM players WHERE players.game_id = games.game_id ) as num_players,
-              ( SELECT max(score) FROM players WHERE players.game_id = games.game_id ) as top_score
+              ( SELECT max(score) FROM players WHERE players.game_id = games.game_id ) as top_score,
+              EXISTS ( SELECT 1 FROM daily_games WHERE daily_games.game_id = games.game_id ) as is_daily
             FROM games
             WHERE game_id IN ( {} )
             ORDER BY start_time DESC
@@ -104,7 +151,8 @@ impl Game {
             SELECT 
               game_id, owner, rows, cols, num_mines, max_players, is_completed, is_started, start_time, end_time, timed_out, seconds,
               ( SELECT count(*) FROM players WHERE players.game_id = games.game_id ) as num_players,
-              ( SELECT max(score) FROM players WHERE players.game_id = games.game_id ) as top_score
+              ( SELECT max(score) FROM players WHERE players.game_id = games.game_id ) as top_score,
+              EXISTS ( SELECT 1 FROM daily_games WHERE daily_games.game_id = games.game_id ) as is_daily
             FROM games
             WHERE is_completed = 1 AND start_time >= Datetime('now', '{}')
             ORDER BY start_time DESC
@@ -122,12 +170,13 @@ impl Game {
         game_id: &str,
         owner: &Option<User>,
         game_parameters: GameParameters,
+        seed: u64,
     ) -> Result<Game, sqlx::Error> {
         let id = owner.as_ref().map(|u| u.id);
         sqlx::query_as(
             r#"
-            INSERT INTO games (game_id, owner, rows, cols, num_mines, max_players, final_board)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO games (game_id, owner, rows, cols, num_mines, max_players, final_board, seed)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             RETURNING *
             "#,
         )
@@ -138,10 +187,20 @@ impl Game {
         .bind(game_parameters.num_mines)
         .bind(game_parameters.max_players)
         .bind(Json(None::<Vec<Vec<PlayerCell>>>))
+        // sqlite integers are signed - the bits are kept as they are
+        .bind(seed as i64)
         .fetch_one(db)
         .await
     }
 
+    pub async fn delete_game(db: &SqlitePool, game_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM games WHERE game_id = ?")
+            .bind(game_id)
+            .execute(db)
+            .await
+            .map(|_| ())
+    }
+
     pub async fn start_game(db: &SqlitePool, game_id: &str) -> Result<(), sqlx::Error> {
         sqlx::query("UPDATE games SET is_started = 1 WHERE game_id = ?")
             .bind(game_id)
@@ -150,6 +209,19 @@ impl Game {
             .map(|_| ())
     }
 
+    pub async fn set_owner(
+        db: &SqlitePool,
+        game_id: &str,
+        owner: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE games SET owner = ? WHERE game_id = ?")
+            .bind(owner)
+            .bind(game_id)
+            .execute(db)
+            .await
+            .map(|_| ())
+    }
+
     pub async fn set_start_time(
         db: &SqlitePool,
         game_id: &str,
@@ -206,8 +278,15 @@ impl Game {
         .map(|_| ())
     }
 
-    pub async fn set_all_games_completed(db: &SqlitePool) -> Result<(), sqlx::Error> {
-        sqlx::query("UPDATE games SET is_completed = 1")
+    pub async fn get_incomplete_game_ids(db: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT game_id FROM games WHERE is_completed = 0")
+            .fetch_all(db)
+            .await
+    }
+
+    pub async fn set_completed(db: &SqlitePool, game_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE games SET is_completed = 1 WHERE game_id = ?")
+            .bind(game_id)
             .execute(db)
             .await
             .map(|_| ())
@@ -450,13 +529,51 @@ impl Player {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, FromRow)]
+/// Leading byte of a versioned game log - bare (v0) logs are JSON arrays starting with `[`
+const GAME_LOG_MAGIC: u8 = b'~';
+const GAME_LOG_VERSION: u8 = b'1';
+
+/// Serializes a game log as `GAME_LOG_MAGIC`, `GAME_LOG_VERSION`, then the JSON log
+pub fn encode_game_log(log: &[(Play, PlayOutcome)]) -> Result<String, serde_json::Error> {
+    let json = serde_json::to_string(log)?;
+    let mut encoded = String::with_capacity(json.len() + 2);
+    encoded.push(GAME_LOG_MAGIC as char);
+    encoded.push(GAME_LOG_VERSION as char);
+    encoded.push_str(&json);
+    Ok(encoded)
+}
+
+/// Parses a stored game log, treating logs without the magic prefix as v0 (bare JSON)
+pub fn decode_game_log(stored: &str) -> anyhow::Result<Vec<(Play, PlayOutcome)>> {
+    let json = match stored.as_bytes() {
+        [GAME_LOG_MAGIC, GAME_LOG_VERSION, ..] => &stored[2..],
+        [GAME_LOG_MAGIC, version, ..] => {
+            anyhow::bail!("Unsupported game log version: {}", *version as char)
+        }
+        _ => stored,
+    };
+    Ok(serde_json::from_str(json)?)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GameLog {
     pub game_id: String,
-    #[sqlx(json)]
     pub log: Vec<(Play, PlayOutcome)>,
 }
 
+impl<'r> FromRow<'r, SqliteRow> for GameLog {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let stored: String = row.try_get("log")?;
+        Ok(GameLog {
+            game_id: row.try_get("game_id")?,
+            log: decode_game_log(&stored).map_err(|e| sqlx::Error::ColumnDecode {
+                index: "log".to_string(),
+                source: e.into(),
+            })?,
+        })
+    }
+}
+
 impl GameLog {
     pub async fn get_log(db: &SqlitePool, game_id: &str) -> Result<Option<GameLog>, sqlx::Error> {
         sqlx::query_as("SELECT * FROM game_log WHERE game_id = ?")
@@ -465,12 +582,19 @@ impl GameLog {
             .await
     }
 
+    /// Saves the log for a game, replacing any saved earlier
     pub async fn save_log(
         db: &SqlitePool,
         game_id: &str,
         log: Vec<(Play, PlayOutcome)>,
     ) -> Result<GameLog, sqlx::Error> {
-        sqlx::query_as(
+        let encoded = encode_game_log(&log).map_err(|e| sqlx::Error::Encode(e.into()))?;
+        let mut transaction = db.begin().await?;
+        sqlx::query("DELETE FROM game_log WHERE game_id = ?")
+            .bind(game_id)
+            .execute(&mut *transaction)
+            .await?;
+        let game_log = sqlx::query_as(
             r#"
             INSERT INTO game_log (game_id, log)
             VALUES (?, ?)
@@ -478,8 +602,101 @@ impl GameLog {
             "#,
         )
         .bind(game_id)
-        .bind(Json(log))
-        .fetch_one(db)
-        .await
+        .bind(encoded)
+        .fetch_one(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(game_log)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use minesweeper_lib::{
+        board::BoardPoint,
+        cell::{Cell, RevealedCell},
+        game::Action,
+    };
+
+    use super::*;
+
+    fn to_json(log: &[(Play, PlayOutcome)]) -> String {
+        serde_json::to_string(log).unwrap()
+    }
+
+    fn sample_log() -> Vec<(Play, PlayOutcome)> {
+        let point = BoardPoint { row: 1, col: 2 };
+        vec![(
+            Play {
+                player: 0,
+                action: Action::Reveal,
+                point,
+            },
+            PlayOutcome::Success(vec![(
+                point,
+                RevealedCell {
+                    player: 0,
+                    contents: Cell::Empty(1),
+                },
+            )]),
+        )]
+    }
+
+    fn params(rows: i64, cols: i64, num_mines: i64, max_players: u8) -> GameParameters {
+        GameParameters {
+            rows,
+            cols,
+            num_mines,
+            max_players,
+        }
+    }
+
+    #[test]
+    fn parameters_validate() {
+        assert_eq!(params(9, 9, 10, 1).validate(), Ok(()));
+        assert_eq!(
+            params(0, 9, 10, 1).validate(),
+            Err(GameParametersError::ZeroDimension)
+        );
+        assert_eq!(
+            params(9, 9, 81, 1).validate(),
+            Err(GameParametersError::InvalidMines {
+                num_mines: 81,
+                cells: 81
+            })
+        );
+        assert_eq!(
+            params(9, 9, 0, 1).validate(),
+            Err(GameParametersError::InvalidMines {
+                num_mines: 0,
+                cells: 81
+            })
+        );
+        assert_eq!(
+            params(9, 9, 10, 0).validate(),
+            Err(GameParametersError::NoPlayers)
+        );
+    }
+
+    #[test]
+    fn v1_round_trip() {
+        let log = sample_log();
+        let encoded = encode_game_log(&log).unwrap();
+        assert!(encoded.starts_with("~1["));
+        assert_eq!(to_json(&decode_game_log(&encoded).unwrap()), to_json(&log));
+    }
+
+    #[test]
+    fn v0_still_decodes() {
+        let log = sample_log();
+        let v0 = to_json(&log);
+        assert_eq!(to_json(&decode_game_log(&v0).unwrap()), v0);
+    }
+
+    #[test]
+    fn unknown_version_rejected() {
+        let v1 = encode_game_log(&sample_log()).unwrap();
+        let v9 = format!("~9{}", &v1[2..]);
+        assert!(decode_game_log(&v9).is_err());
     }
 }